@@ -1,6 +1,8 @@
-use cgmath::Transform;
 use eframe::egui;
 use itertools::Itertools;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use symmetries::*;
 
 const MAX_NDIM: u8 = 8;
@@ -12,12 +14,14 @@ fn main() {
         options,
         Box::new(|_cc| {
             let mut dim_mappings = vec![vector![0.0, 0.0, 0.0]; MAX_NDIM as _];
-            for i in 0..4 {
-                dim_mappings[i] = Vector::unit(i as _);
+            for (i, v) in dim_mappings.iter_mut().enumerate().take(4) {
+                *v = Vector::unit(i as _);
             }
 
             Box::new(PolytopeDemo {
                 polygons: vec![],
+                polygon_colors: vec![],
+                log: String::new(),
                 ndim: 3,
                 dim_mappings,
 
@@ -25,13 +29,13 @@ fn main() {
 
                 cd: "4,3,3,3".to_string(),
                 cd_error: false,
+                groups: Arc::new(GroupCache::new()),
                 poles: vec![Vector::unit(0)],
                 arrows: vec![],
+                generation: None,
 
                 camera_rot: Matrix::EMPTY_IDENT,
                 active_axes: [0, 1, 2],
-                pitch: 0.,
-                yaw: 0.,
                 w_offset: 4.,
             })
         }),
@@ -41,6 +45,8 @@ fn main() {
 #[derive(Debug)]
 struct PolytopeDemo {
     polygons: Vec<Polygon>,
+    polygon_colors: Vec<egui::Color32>,
+    log: String,
     ndim: u8,
     dim_mappings: Vec<Vector<f32>>,
 
@@ -48,17 +54,27 @@ struct PolytopeDemo {
 
     cd: String,
     cd_error: bool,
+    /// Groups that have already been generated, so that changing only the
+    /// poles doesn't regenerate the group.
+    groups: Arc<GroupCache>,
     poles: Vec<Vector<f32>>,
 
     arrows: Vec<Vector<f32>>,
+    generation: Option<Generation>,
 
     camera_rot: Matrix<f32>,
     active_axes: [u8; 3],
-    pitch: f32,
-    yaw: f32,
     w_offset: f32,
 }
 
+/// Shape being generated on a background thread.
+#[derive(Debug)]
+struct Generation {
+    progress: Arc<Mutex<SliceProgress>>,
+    cancel: Arc<AtomicBool>,
+    result: mpsc::Receiver<Result<GeneratedShape, CoxeterError>>,
+}
+
 impl PolytopeDemo {
     fn is_axis_flat(&self, axis: u8) -> bool {
         self.camera_rot.get(axis, axis) > 1. - 0.00001
@@ -104,11 +120,67 @@ impl PolytopeDemo {
             + &((&tm * &tm).scale(1. / (1. + current.dot(target))));
         self.camera_rot = &m0 * &self.camera_rot;
     }
+
+    /// Displays the polygons in an arena, colored by cell if there are
+    /// multiple cells or by facet otherwise.
+    fn show_arena(&mut self, arena: &PolytopeArena) -> Result<(), PolygonError> {
+        let mesh = arena.mesh()?;
+        let cells = arena.cells()?;
+        let groups: Vec<Vec<u32>> = if cells.len() > 1 {
+            cells.into_iter().map(|cell| cell.polygons).collect()
+        } else {
+            mesh.polygons_by_facet()
+                .into_iter()
+                .map(|(_, polygons)| polygons)
+                .collect()
+        };
+        self.polygons = mesh.to_polygons();
+        self.polygon_colors = vec![egui::Color32::TRANSPARENT; self.polygons.len()];
+        for (i, group) in groups.iter().enumerate() {
+            let hue = i as f32 / groups.len() as f32;
+            for &p in group {
+                self.polygon_colors[p as usize] = egui::color::Hsva::new(hue, 0.7, 0.9, 1.0).into();
+            }
+        }
+        Ok(())
+    }
+
+    /// Displays the result of the background generation, if it has finished.
+    fn poll_generation(&mut self) {
+        let result = match &self.generation {
+            Some(generation) => match generation.result.try_recv() {
+                Ok(result) => result,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.generation = None;
+                    return;
+                }
+            },
+            None => return,
+        };
+        self.generation = None;
+        let result = result.and_then(|shape| {
+            self.ndim = shape.diagram.ndim();
+            for p in &mut self.poles {
+                p.set_ndim(self.ndim);
+            }
+            self.arrows = shape.diagram.mirrors().into_iter().map(|m| m.0).collect();
+            self.arrows.extend(shape.poles);
+            for (i, stats) in shape.arena.slice_log().iter().enumerate() {
+                self.log += &format!("cut {i}: {stats}\n");
+            }
+            Ok(self.show_arena(&shape.arena)?)
+        });
+        if let Err(e) = result {
+            self.cd_error = true;
+            self.log += &format!("{e}\n");
+        }
+    }
 }
 
 impl eframe::App for PolytopeDemo {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let mut log = String::new();
+        self.poll_generation();
 
         egui::SidePanel::new(egui::containers::panel::Side::Right, "right").show(ctx, |ui| {
             ui.label("W-Offset");
@@ -120,40 +192,16 @@ impl eframe::App for PolytopeDemo {
 
             for axis in &mut self.active_axes {
                 ui.horizontal(|ui| {
-                    ui.selectable_value(axis, 0, "x");
-                    ui.selectable_value(axis, 1, "y");
-                    ui.selectable_value(axis, 2, "z");
-                    ui.selectable_value(axis, 3, "w");
-                    ui.selectable_value(axis, 4, "u");
-                    ui.selectable_value(axis, 5, "v");
-                    ui.selectable_value(axis, 6, "dim7");
-                    ui.selectable_value(axis, 7, "dim8");
+                    for (i, name) in (0..MAX_NDIM).zip(AXIS_NAMES) {
+                        ui.selectable_value(axis, i, name);
+                    }
                 });
             }
             ui.horizontal(|ui| {
-                if ui.selectable_label(self.is_axis_flat(0), "x").clicked() {
-                    self.flatten_axis(0);
-                }
-                if ui.selectable_label(self.is_axis_flat(1), "y").clicked() {
-                    self.flatten_axis(1);
-                }
-                if ui.selectable_label(self.is_axis_flat(2), "z").clicked() {
-                    self.flatten_axis(2);
-                }
-                if ui.selectable_label(self.is_axis_flat(3), "w").clicked() {
-                    self.flatten_axis(3);
-                }
-                if ui.selectable_label(self.is_axis_flat(4), "u").clicked() {
-                    self.flatten_axis(4);
-                }
-                if ui.selectable_label(self.is_axis_flat(5), "v").clicked() {
-                    self.flatten_axis(5);
-                }
-                if ui.selectable_label(self.is_axis_flat(6), "dim7").clicked() {
-                    self.flatten_axis(6);
-                }
-                if ui.selectable_label(self.is_axis_flat(7), "dim8").clicked() {
-                    self.flatten_axis(7);
+                for (i, name) in (0..MAX_NDIM).zip(AXIS_NAMES) {
+                    if ui.selectable_label(self.is_axis_flat(i), name).clicked() {
+                        self.flatten_axis(i);
+                    }
                 }
             });
             if ui.button("Reset Camera").clicked() {
@@ -167,7 +215,13 @@ impl eframe::App for PolytopeDemo {
                     .speed(0.1),
             );
             if ui.button("Generate cube").clicked() {
-                self.polygons = PolytopeArena::new_cube(self.ndim, 1.0).polygons();
+                let result = match PolytopeArena::new_cube(self.ndim, 1.0) {
+                    Ok(arena) => self.show_arena(&arena).map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                if let Err(e) = result {
+                    self.log += &format!("{e}\n");
+                }
             }
             ui.collapsing("Coxeter diagram", |ui| {
                 ui.text_edit_singleline(&mut self.cd);
@@ -175,7 +229,7 @@ impl eframe::App for PolytopeDemo {
                 ui.strong("Base facets");
                 ui.horizontal(|ui| {
                     if ui.button("+").clicked() {
-                        self.poles.push(Vector::EMPTY);
+                        self.poles.push(Vector::zero(self.ndim));
                     }
                     if ui.button("-").clicked() && self.poles.len() > 1 {
                         self.poles.pop();
@@ -185,34 +239,51 @@ impl eframe::App for PolytopeDemo {
                     vector_edit(ui, p, self.ndim);
                 }
 
-                if ui.button("Generate!").clicked() || self.auto_generate {
-                    self.cd_error = false;
-                    let xs = self
-                        .cd
-                        .split(',')
-                        .map(|s| s.trim().parse().unwrap_or(0))
-                        .collect_vec();
-                    if xs.iter().any(|&x| x <= 1) {
-                        self.cd_error = true;
-                    } else {
-                        let cd = CoxeterDiagram::with_edges(xs);
-                        self.ndim = cd.ndim();
-                        self.arrows = cd.mirrors().iter().map(|v| v.0.clone()).collect();
-                        let m = Matrix::from_cols(cd.mirrors().iter().rev().map(|v| &v.0))
-                            .inverse()
-                            .transpose();
-                        let group = cd.generators();
-                        for p in &mut self.poles {
-                            p.truncate(self.ndim);
-                        }
-                        let poles = self
-                            .poles
-                            .iter()
-                            .map(|v| m.transform(v))
-                            .collect::<Vec<_>>();
-                        self.arrows.extend_from_slice(&poles);
-                        self.polygons = shape_geom(self.ndim, &group, &poles);
+                if let Some(generation) = &self.generation {
+                    let progress = *generation.progress.lock().unwrap();
+                    ui.add(egui::ProgressBar::new(progress.fraction()).text(format!(
+                        "{}/{} facets, {} polytopes",
+                        progress.planes_completed, progress.plane_count, progress.polytope_count,
+                    )));
+                    if ui.button("Cancel").clicked() {
+                        generation.cancel.store(true, Ordering::Relaxed);
                     }
+                } else if ui.button("Generate!").clicked() || self.auto_generate {
+                    self.cd_error = false;
+                    let options = SliceOptions {
+                        log_slices: true,
+                        min_polygon_area: 1e-6,
+                        ..Default::default()
+                    };
+                    let spec = self.poles.iter().fold(
+                        ShapeSpec::new(self.cd.clone())
+                            .options(options)
+                            .group_cache(self.groups.clone()),
+                        |spec, pole| spec.pole_mirror_basis(pole.clone()),
+                    );
+                    let progress = Arc::new(Mutex::new(SliceProgress::default()));
+                    let cancel = Arc::new(AtomicBool::new(false));
+                    let (sender, receiver) = mpsc::channel();
+                    let (thread_progress, thread_cancel) = (progress.clone(), cancel.clone());
+                    let ctx = ui.ctx().clone();
+                    std::thread::spawn(move || {
+                        let result = spec.build_with_progress(|p| {
+                            *thread_progress.lock().unwrap() = p;
+                            ctx.request_repaint();
+                            match thread_cancel.load(Ordering::Relaxed) {
+                                true => ControlFlow::Break(()),
+                                false => ControlFlow::Continue(()),
+                            }
+                        });
+                        // The receiver is gone if the demo has closed.
+                        let _ = sender.send(result);
+                        ctx.request_repaint();
+                    });
+                    self.generation = Some(Generation {
+                        progress,
+                        cancel,
+                        result: receiver,
+                    });
                 }
                 ui.checkbox(&mut self.auto_generate, "Auto generate");
                 ui.colored_label(egui::Color32::RED, if self.cd_error { "error" } else { "" });
@@ -223,9 +294,7 @@ impl eframe::App for PolytopeDemo {
                 ui.horizontal(|ui| {
                     ui.label(format!("Dim {dim}"));
                     if ui.button("N").clicked() {
-                        if v.dot(&*v) != 0.0 {
-                            *v = &*v * (1.0 / v.dot(&*v).sqrt());
-                        }
+                        v.normalize();
                     }
                     vector_edit(ui, v, 4);
                 });
@@ -234,7 +303,7 @@ impl eframe::App for PolytopeDemo {
             ui.separator();
             ui.label("Log:");
             egui::ScrollArea::new([false, true]).show(ui, |ui| {
-                ui.add(egui::TextEdit::multiline(&mut log).interactive(false));
+                ui.add(egui::TextEdit::multiline(&mut self.log).interactive(false));
             });
         });
 
@@ -247,37 +316,31 @@ impl eframe::App for PolytopeDemo {
                     let ndrot = &Matrix::from_cols(self.dim_mappings.clone()) * &self.camera_rot;
                     // let rot = cgmath::Matrix3::from_angle_x(cgmath::Rad(self.pitch))
                     //     * cgmath::Matrix3::from_angle_y(cgmath::Rad(self.yaw));
+                    let [a0, a1, a2] = self.active_axes;
+                    let project = |p: &Vector<f32>| {
+                        let v = ndrot.transform(p).permuted(&[a0, a1, a2, 3]);
+                        let v = Matrix::perspective_project(v, 3, self.w_offset)?;
+                        Some(egui::plot::Value::new(v[0], v[1]))
+                    };
                     for (i, p) in self.polygons.iter().enumerate() {
-                        plot_ui.polygon(
-                            egui::plot::Polygon::new(egui::plot::Values::from_values_iter(
-                                p.verts
-                                    .iter()
-                                    .map(|p| {
-                                        let mut v = ndrot.transform(p);
-                                        let w = v[3] + self.w_offset;
-                                        v = v / w;
-                                        cgmath::point3(v[0], v[1], v[2])
-                                    })
-                                    .map(|xy| egui::plot::Value::new(xy.x, xy.y)),
-                            ))
-                            .name(i),
-                        );
+                        // Skip polygons that are partially behind the camera.
+                        if let Some(values) =
+                            p.verts.iter().map(project).collect::<Option<Vec<_>>>()
+                        {
+                            plot_ui.polygon(
+                                egui::plot::Polygon::new(egui::plot::Values::from_values(values))
+                                    .color(self.polygon_colors[i])
+                                    .name(i),
+                            );
+                        }
                     }
+                    let arrow_tips = self.arrows.iter().filter_map(project).collect_vec();
                     plot_ui.arrows(egui::plot::Arrows::new(
-                        egui::plot::Values::from_values_iter(
-                            vec![egui::plot::Value::new(0, 0); self.arrows.len()].into_iter(),
-                        ),
-                        egui::plot::Values::from_values_iter(
-                            self.arrows
-                                .iter()
-                                .map(|p| {
-                                    let mut v = ndrot.transform(p);
-                                    let w = v[3] + self.w_offset;
-                                    v = v / w;
-                                    cgmath::point3(v[0], v[1], v[2])
-                                })
-                                .map(|xy| egui::plot::Value::new(xy.x, xy.y)),
-                        ),
+                        egui::plot::Values::from_values(vec![
+                            egui::plot::Value::new(0, 0);
+                            arrow_tips.len()
+                        ]),
+                        egui::plot::Values::from_values(arrow_tips),
                     ))
                 });
             if r.response.dragged_by(egui::PointerButton::Secondary) {
@@ -1,12 +1,120 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::polytope::Polygon;
+use crate::util::EPSILON;
 use crate::{group::*, matrix::*, vector::*};
 
+/// Error returned when constructing or using a [`CoxeterDiagram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagramError {
+    /// An edge label is less than 2, so it doesn't describe a pair of
+    /// mirrors.
+    InvalidEdge {
+        /// Index of the edge.
+        index: usize,
+        /// Label of the edge.
+        value: usize,
+    },
+    /// An edge label in a string is not a number.
+    ParseEdge {
+        /// Index of the edge.
+        index: usize,
+        /// Text of the edge.
+        text: String,
+    },
+    /// The number of ring flags doesn't match the number of nodes.
+    WrongRingCount {
+        /// Number of nodes.
+        expected: usize,
+        /// Number of ring flags.
+        got: usize,
+    },
+    /// No nodes are ringed, so the uniform polytope or tiling is a single
+    /// point.
+    NoRingedNodes,
+    /// The operation is not supported for diagrams with this many nodes.
+    UnsupportedRank {
+        /// Number of nodes.
+        ndim: u8,
+    },
+    /// The diagram doesn't describe a compact hyperbolic group.
+    NotHyperbolic,
+    /// The diagram doesn't describe a finite group, such as a Euclidean or
+    /// hyperbolic tiling.
+    NotFinite,
+}
+impl fmt::Display for DiagramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagramError::InvalidEdge { index, value } => {
+                write!(f, "edge {index} has label {value}, which is less than 2")
+            }
+            DiagramError::ParseEdge { index, text } => {
+                write!(f, "edge {index} has label {text:?}, which is not a number")
+            }
+            DiagramError::WrongRingCount { expected, got } => {
+                write!(f, "expected {expected} ring flags; got {got}")
+            }
+            DiagramError::NoRingedNodes => write!(f, "no nodes are ringed"),
+            DiagramError::UnsupportedRank { ndim } => {
+                write!(f, "diagrams with {ndim} nodes are not supported")
+            }
+            DiagramError::NotHyperbolic => {
+                write!(f, "diagram does not describe a compact hyperbolic group")
+            }
+            DiagramError::NotFinite => write!(f, "diagram does not describe a finite group"),
+        }
+    }
+}
+impl std::error::Error for DiagramError {}
+
 /// Linear Coxeter diagram with unlabeled vertices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CoxeterDiagram {
     edges: Vec<usize>,
 }
+impl FromStr for CoxeterDiagram {
+    type Err = DiagramError;
+
+    /// Parses a comma-separated list of edge labels, such as `"4,3"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let edges = s
+            .split(',')
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .enumerate()
+            .map(|(index, text)| {
+                text.parse().map_err(|_| DiagramError::ParseEdge {
+                    index,
+                    text: text.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Self::try_with_edges(edges)
+    }
+}
 impl CoxeterDiagram {
+    /// Constructs a linear Coxeter diagram with the given edge labels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any edge label is less than 2. See
+    /// [`CoxeterDiagram::try_with_edges()`].
+    #[track_caller]
     pub fn with_edges(edges: Vec<usize>) -> Self {
-        Self { edges }
+        Self::try_with_edges(edges).unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Constructs a linear Coxeter diagram with the given edge labels, or
+    /// returns an error if any edge label is less than 2.
+    pub fn try_with_edges(edges: Vec<usize>) -> Result<Self, DiagramError> {
+        if let Some((index, &value)) = edges.iter().enumerate().find(|(_, &e)| e < 2) {
+            return Err(DiagramError::InvalidEdge { index, value });
+        }
+        Ok(Self { edges })
     }
 
     /// Number of dimensions described by the Coxeter diagram's group.
@@ -15,8 +123,24 @@ impl CoxeterDiagram {
     }
 
     pub fn mirrors(&self) -> Vec<Mirror> {
+        let ret = self.mirrors_unchecked();
+        for mirror in &ret {
+            debug_assert!(
+                mirror.0.is_finite(),
+                "non-finite mirror vector {} in Coxeter diagram {:?}",
+                mirror.0,
+                self.edges,
+            );
+        }
+        ret
+    }
+    /// Returns the mirror vectors, which may be non-finite if the diagram
+    /// doesn't describe a finite group.
+    fn mirrors_unchecked(&self) -> Vec<Mirror> {
+        let ndim = self.ndim();
         let mut ret = vec![];
-        let mut last = Vector::unit(0);
+        let mut last = Vector::zero(ndim);
+        last[0] = 1.0;
         for (i, &edge) in self.edges.iter().enumerate() {
             ret.push(Mirror(last.clone()));
             // The final mirror vectors will look like this, with each row as a
@@ -55,7 +179,7 @@ impl CoxeterDiagram {
             // vector will be normalized.
             let z = (1.0 - y * y).sqrt();
             // Actually construct that vector.
-            last = Vector::EMPTY;
+            last = Vector::zero(ndim);
             last[i as u8] = y;
             last[i as u8 + 1] = z;
         }
@@ -70,6 +194,499 @@ impl CoxeterDiagram {
         let gens: Vec<_> = self.mirrors().into_iter().map(|m| m.into()).collect();
         Group::from_generators(&gens)
     }
+    /// Returns the symmetry group of the diagram, or an error if it has more
+    /// than `max_order` elements or the diagram doesn't describe a finite
+    /// group.
+    pub fn try_group(&self, max_order: u32) -> Result<Group, GroupError> {
+        let mirrors = self.mirrors_unchecked();
+        if let Some(index) = mirrors.iter().position(|m| !m.0.is_finite()) {
+            return Err(GroupError::NonFiniteGenerator { index });
+        }
+        let gens: Vec<_> = mirrors.into_iter().map(|m| m.into()).collect();
+        Group::try_from_generators(&gens, max_order)
+    }
+    /// Returns the symmetry group of the diagram from `cache`, generating it
+    /// and adding it to the cache if it isn't there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group cannot be generated. See [`CoxeterDiagram::group()`].
+    pub fn group_cached(&self, cache: &GroupCache) -> Arc<Group> {
+        cache
+            .get_or_insert_with(self, || Ok::<_, Infallible>(self.clone().group()))
+            .unwrap_or_else(|e| match e {})
+    }
+    /// Returns the symmetry group of the diagram from `cache`, generating it
+    /// and adding it to the cache if it isn't there. See
+    /// [`CoxeterDiagram::try_group()`].
+    ///
+    /// Returns an error if the group has more than `max_order` elements, even
+    /// if it is already in the cache.
+    pub fn try_group_cached(
+        &self,
+        cache: &GroupCache,
+        max_order: u32,
+    ) -> Result<Arc<Group>, GroupError> {
+        let group = cache.get_or_insert_with(self, || self.try_group(max_order))?;
+        if group.order() > max_order {
+            return Err(GroupError::TooManyElements { limit: max_order });
+        }
+        Ok(group)
+    }
+
+    /// Returns which nodes are ringed in the Wythoff construction of the
+    /// uniform polytope with a vertex at `point`: a node is ringed if `point`,
+    /// moved into the fundamental region of the group, is not on its mirror.
+    /// No nodes are ringed for a non-finite point.
+    ///
+    /// Returns an error if the diagram doesn't describe a finite group.
+    pub fn ringed_nodes(&self, point: &Vector<f32>) -> Result<Vec<bool>, DiagramError> {
+        let n = self.ndim() as usize;
+        let order = self.subgroup_order(&vec![true; n])?;
+        if !point.is_finite() {
+            return Ok(vec![false; n]);
+        }
+        // Alternate signs so that adjacent mirror vectors are at an obtuse
+        // angle, which bounds the fundamental region.
+        let normals: Vec<Vector<f32>> = self
+            .mirrors()
+            .into_iter()
+            .enumerate()
+            .map(|(i, m)| if i % 2 == 0 { m.0 } else { -m.0 })
+            .collect();
+        let eps = EPSILON * point.mag();
+
+        // Reflect the point through any mirror that it is on the wrong side
+        // of. Each reflection moves it closer to the interior of the
+        // fundamental region, so this takes fewer steps than the order of the
+        // group.
+        let mut p = point.clone();
+        for _ in 0..order {
+            let Some((normal, dot)) = normals
+                .iter()
+                .map(|normal| (normal, normal.dot(&p)))
+                .find(|&(_, dot)| dot < -eps)
+            else {
+                break;
+            };
+            p = &p - normal * (2.0 * dot);
+        }
+        Ok(normals.iter().map(|normal| normal.dot(&p) > eps).collect())
+    }
+
+    /// Returns the number of elements of each rank of the uniform polytope
+    /// with the given ringed nodes, indexed by rank. The polytope itself is
+    /// the only element of the highest rank.
+    ///
+    /// Each element corresponds to a set of nodes in which every connected
+    /// component contains a ringed node, and the number of such elements is
+    /// the order of the group divided by the order of their stabilizer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ringed` doesn't have one flag per node or the diagram
+    /// doesn't describe a finite group. See
+    /// [`CoxeterDiagram::try_element_counts()`].
+    #[track_caller]
+    pub fn element_counts(&self, ringed: &[bool]) -> Vec<usize> {
+        self.try_element_counts(ringed)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Returns the number of elements of each rank of the uniform polytope
+    /// with the given ringed nodes, or an error if `ringed` doesn't have one
+    /// flag per node or the diagram doesn't describe a finite group. See
+    /// [`CoxeterDiagram::element_counts()`].
+    pub fn try_element_counts(&self, ringed: &[bool]) -> Result<Vec<usize>, DiagramError> {
+        let n = self.ndim() as usize;
+        if ringed.len() != n {
+            return Err(DiagramError::WrongRingCount {
+                expected: n,
+                got: ringed.len(),
+            });
+        }
+        // Every subset of the nodes is considered.
+        if n >= 32 {
+            return Err(DiagramError::UnsupportedRank { ndim: self.ndim() });
+        }
+        let connected = |i: usize| self.edges[i] != 2; // nodes `i` and `i+1`
+        let group_order = self.subgroup_order(&vec![true; n])?;
+
+        let mut counts = vec![0; n + 1];
+        for subset in 0_u32..1 << n {
+            let in_subset = |i: usize| subset & (1 << i) != 0;
+            // Every component of the subset must contain a ringed node.
+            let mut component_has_ring = false;
+            let mut valid = true;
+            for (i, &is_ringed) in ringed.iter().enumerate() {
+                if in_subset(i) {
+                    component_has_ring |= is_ringed;
+                }
+                let component_ends = i + 1 == n || !in_subset(i + 1) || !connected(i);
+                if in_subset(i) && component_ends {
+                    valid &= component_has_ring;
+                    component_has_ring = false;
+                }
+            }
+            if !valid {
+                continue;
+            }
+            // The stabilizer also contains the reflections of unringed nodes
+            // that commute with the whole subset.
+            let stabilizer: Vec<bool> = (0..n)
+                .map(|i| {
+                    let adjacent = (i > 0 && connected(i - 1) && in_subset(i - 1))
+                        || (i + 1 < n && connected(i) && in_subset(i + 1));
+                    in_subset(i) || (!ringed[i] && !adjacent)
+                })
+                .collect();
+            counts[subset.count_ones() as usize] +=
+                group_order / self.subgroup_order(&stabilizer)?;
+        }
+        Ok(counts)
+    }
+    /// Returns the tiles of the uniform hyperbolic tiling with the given
+    /// ringed nodes, projected to the Poincaré disk. Only the tiles `g * t`
+    /// are included, where `t` is a tile around the fundamental triangle and
+    /// `g` is a product of at most `max_word_length` generators.
+    ///
+    /// Only diagrams with 3 nodes that describe a compact hyperbolic group,
+    /// such as `[7, 3]`, are supported.
+    pub fn hyperbolic_tiling(
+        &self,
+        rings: &[bool],
+        max_word_length: usize,
+    ) -> Result<HyperbolicTiling, DiagramError> {
+        let ndim = self.ndim();
+        if ndim != 3 {
+            return Err(DiagramError::UnsupportedRank { ndim });
+        }
+        if rings.len() != 3 {
+            return Err(DiagramError::WrongRingCount {
+                expected: 3,
+                got: rings.len(),
+            });
+        }
+        if !rings.contains(&true) {
+            return Err(DiagramError::NoRingedNodes);
+        }
+        let normals = self
+            .hyperbolic_mirrors()
+            .ok_or(DiagramError::NotHyperbolic)?;
+        let generators: Vec<Matrix<f32>> = normals.iter().map(minkowski_reflection).collect();
+
+        // Wythoff construction: the base vertex is on the mirror of each
+        // unringed node and equidistant from the mirrors of the ringed nodes.
+        let base_vertex =
+            hyperboloid_point(&normals, std::array::from_fn(|i| rings[i] as u8 as f32))
+                .ok_or(DiagramError::NotHyperbolic)?;
+
+        // Each pair of mirrors meets at the center of a tile, whose vertices
+        // are the orbit of the base vertex under those two reflections. Tiles
+        // are labeled by the remaining node.
+        let mut base_tiles = vec![];
+        for (i, j, k) in [(0, 1, 2), (0, 2, 1), (1, 2, 0)] {
+            let mut values = [0.0; 3];
+            values[k] = 1.0;
+            let center = hyperboloid_point(&normals, values).ok_or(DiagramError::NotHyperbolic)?;
+            let mut verts = vec![base_vertex.clone()];
+            let mut next = 0;
+            while next < verts.len() {
+                for gen in [&generators[i], &generators[j]] {
+                    let v = gen.transform(&verts[next]);
+                    if !verts.iter().any(|old| old.approx_eq(&v)) {
+                        verts.push(v);
+                    }
+                }
+                next += 1;
+            }
+            if verts.len() < 3 {
+                continue;
+            }
+            // Sort the vertices by angle around the center, using two
+            // spacelike vectors tangent to the hyperboloid at the center.
+            let u = &normals[i];
+            let w = &normals[j] - u * minkowski_dot(&normals[j], u);
+            let angle = |v: &Vector<f32>| minkowski_dot(v, &w).atan2(minkowski_dot(v, u));
+            verts.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+            base_tiles.push((k, center, verts));
+        }
+
+        // The group acts freely on the interior of the fundamental triangle,
+        // so each element is identified by the image of a point there.
+        let interior = hyperboloid_point(&normals, [1.0; 3]).ok_or(DiagramError::NotHyperbolic)?;
+        let mut images = HyperbolicPointSet::default();
+        images.insert(interior.clone());
+        let mut elements = vec![Matrix::ident(3)];
+        let mut frontier = 0..1;
+        for _ in 0..max_word_length {
+            let start = elements.len();
+            for g in frontier {
+                for gen in &generators {
+                    let new = &elements[g] * gen;
+                    if images.insert(new.transform(&interior)) {
+                        elements.push(new);
+                    }
+                }
+            }
+            frontier = start..elements.len();
+        }
+
+        let mut centers = HyperbolicPointSet::default();
+        let mut polygons = vec![];
+        for g in &elements {
+            for (k, center, verts) in &base_tiles {
+                if !centers.insert(g.transform(center)) {
+                    continue;
+                }
+                let mut verts: Vec<Vector<f32>> = verts
+                    .iter()
+                    .map(|v| poincare_disk(&g.transform(v)))
+                    .collect();
+                let twice_area: f32 = (0..verts.len())
+                    .map(|a| {
+                        let b = (a + 1) % verts.len();
+                        verts[a][0] * verts[b][1] - verts[b][0] * verts[a][1]
+                    })
+                    .sum();
+                if twice_area < 0.0 {
+                    verts.reverse();
+                }
+                polygons.push(Polygon {
+                    verts,
+                    facet: Some(*k),
+                });
+            }
+        }
+
+        Ok(HyperbolicTiling {
+            generators,
+            polygons,
+        })
+    }
+    /// Returns the normal vectors of the mirrors of a diagram with 3 nodes in
+    /// Minkowski space, or `None` if the diagram doesn't describe a compact
+    /// hyperbolic group. See [`minkowski_dot()`].
+    ///
+    /// Unlike [`CoxeterDiagram::mirrors()`], neighboring normals have a
+    /// negative inner product, so that the fundamental triangle is where the
+    /// inner products with all the normals have the same sign.
+    fn hyperbolic_mirrors(&self) -> Option<[Vector<f32>; 3]> {
+        let [cos_p, cos_q] =
+            [self.edges[0], self.edges[1]].map(|edge| (std::f32::consts::PI / edge as f32).cos());
+        let x = -cos_p;
+        let y = (1.0 - x * x).sqrt();
+        // The last normal is perpendicular to the first, so only the second
+        // affects its `y` coordinate. Its `z` coordinate is only real if the
+        // normal is spacelike, which happens exactly when the group is
+        // hyperbolic.
+        let last_y = -cos_q / y;
+        let last_z_squared = last_y * last_y - 1.0;
+        if last_z_squared <= EPSILON {
+            return None;
+        }
+        Some([
+            vector![1.0, 0.0, 0.0],
+            vector![x, y, 0.0],
+            vector![0.0, last_y, last_z_squared.sqrt()],
+        ])
+    }
+    /// Returns the order of the subgroup generated by the reflections of a
+    /// set of nodes, which is the product of the orders of its connected
+    /// components, or an error if it is infinite.
+    fn subgroup_order(&self, nodes: &[bool]) -> Result<usize, DiagramError> {
+        let mut order: usize = 1;
+        let mut start = 0;
+        for i in 0..nodes.len() {
+            let run_ends = i + 1 == nodes.len() || !nodes[i + 1] || self.edges[i] == 2;
+            if !nodes[i] {
+                start = i + 1;
+            } else if run_ends {
+                let component_order = self.component_order(&self.edges[start..i])?;
+                order = order
+                    .checked_mul(component_order)
+                    .ok_or(DiagramError::UnsupportedRank { ndim: self.ndim() })?;
+                start = i + 1;
+            }
+        }
+        Ok(order)
+    }
+    /// Returns the order of the group of a connected diagram with the given
+    /// edges, none of which are 2, or an error if it is infinite.
+    ///
+    /// The diagram is linear, so the only finite groups are `A_n`, `B_n`,
+    /// `F_4`, `H_3`, `H_4`, and `I_2(p)`. The branching groups `D_n` and
+    /// `E_6`-`E_8` can't occur.
+    fn component_order(&self, edges: &[usize]) -> Result<usize, DiagramError> {
+        let overflow = DiagramError::UnsupportedRank { ndim: self.ndim() };
+        let n = edges.len() as u32 + 1;
+        let factorial =
+            |n: u32| crate::util::checked_factorial(n).and_then(|x| usize::try_from(x).ok());
+        let all_3 = |edges: &[usize]| edges.iter().all(|&e| e == 3);
+        match edges {
+            [] => Ok(2),
+            [p] => p.checked_mul(2).ok_or(overflow),
+            _ if all_3(edges) => factorial(n + 1).ok_or(overflow),
+            [4, rest @ ..] | [rest @ .., 4] if all_3(rest) => 1_usize
+                .checked_shl(n)
+                .zip(factorial(n))
+                .and_then(|(a, b)| a.checked_mul(b))
+                .ok_or(overflow),
+            [3, 4, 3] => Ok(1152),
+            [5, 3] | [3, 5] => Ok(120),
+            [5, 3, 3] | [3, 3, 5] => Ok(14400),
+            _ => Err(DiagramError::NotFinite),
+        }
+    }
+}
+
+/// Symmetry groups of Coxeter diagrams that have already been generated, so
+/// that each is only generated once. See [`CoxeterDiagram::group_cached()`].
+///
+/// Diagrams are compared exactly, so `[4, 3]` and `[3, 4]` are cached
+/// separately: they have the same abstract group, but different mirrors. The
+/// generators of a group are in the order of the nodes, and its matrices are
+/// in the basis of [`CoxeterDiagram::mirrors()`], so reusing the group of a
+/// relabeled diagram would require both renumbering its generators and
+/// conjugating it into a different basis.
+#[derive(Debug, Default)]
+pub struct GroupCache {
+    groups: Mutex<HashMap<CoxeterDiagram, Arc<Group>>>,
+}
+impl GroupCache {
+    /// Constructs an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of groups in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+    /// Removes every group from the cache. Groups that are still in use
+    /// elsewhere are not freed until they are dropped.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the group for `diagram`, calling `generate` and caching the
+    /// result if there isn't one. Errors are not cached.
+    ///
+    /// The cache is not locked while `generate` runs, so other threads can use
+    /// it in the meantime. If two threads generate the same group at once, the
+    /// first one to finish is kept.
+    fn get_or_insert_with<E>(
+        &self,
+        diagram: &CoxeterDiagram,
+        generate: impl FnOnce() -> Result<Group, E>,
+    ) -> Result<Arc<Group>, E> {
+        if let Some(group) = self.lock().get(diagram) {
+            return Ok(Arc::clone(group));
+        }
+        let group = Arc::new(generate()?);
+        Ok(Arc::clone(
+            self.lock().entry(diagram.clone()).or_insert(group),
+        ))
+    }
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CoxeterDiagram, Arc<Group>>> {
+        // The map is never left in an inconsistent state, so a panic while
+        // it was locked doesn't matter.
+        self.groups.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Part of a uniform hyperbolic tiling. See
+/// [`CoxeterDiagram::hyperbolic_tiling()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperbolicTiling {
+    /// Reflection through each mirror of the diagram, acting on the
+    /// hyperboloid model in Minkowski space. See [`minkowski_dot()`].
+    pub generators: Vec<Matrix<f32>>,
+    /// Tiles projected to the Poincaré disk, with counterclockwise winding
+    /// order. The `facet` of each tile is the node whose mirror doesn't pass
+    /// through the center of the tile in the fundamental triangle that it
+    /// comes from.
+    pub polygons: Vec<Polygon>,
+}
+
+/// Set of points on the hyperbolic plane, hashed by direction so that finding
+/// an approximately equal point takes constant time instead of comparing
+/// against every point. See [`Vector::approx_eq()`].
+#[derive(Debug)]
+struct HyperbolicPointSet {
+    points: Vec<Vector<f32>>,
+    /// Indices of the points, by the direction of each point.
+    grid: PointGrid,
+}
+impl Default for HyperbolicPointSet {
+    fn default() -> Self {
+        Self {
+            points: vec![],
+            grid: PointGrid::new(16.0 * Self::DIRECTION_EPS),
+        }
+    }
+}
+impl HyperbolicPointSet {
+    /// Maximum difference between the directions of approximately equal
+    /// points in each component. Points on the hyperboloid have magnitude at
+    /// least 1, so normalizing them at most doubles the relative tolerance of
+    /// [`Vector::approx_eq()`], which is spread over 3 components.
+    const DIRECTION_EPS: f32 = 8.0 * EPSILON;
+
+    /// Adds `point` to the set and returns `true`. If there is already an
+    /// approximately equal point, returns `false` instead and does nothing.
+    fn insert(&mut self, point: Vector<f32>) -> bool {
+        let direction = &point / point.mag();
+        if self
+            .grid
+            .nearby(&direction, Self::DIRECTION_EPS)
+            .any(|i| self.points[i].approx_eq(&point))
+        {
+            return false;
+        }
+        self.grid.insert(&direction, self.points.len());
+        self.points.push(point);
+        true
+    }
+}
+
+/// Returns the inner product of two vectors in 3D Minkowski space, with
+/// signature `(+, +, -)`. The hyperbolic plane is the sheet of the hyperboloid
+/// `minkowski_dot(v, v) = -1` with positive `z`.
+pub fn minkowski_dot(a: &Vector<f32>, b: &Vector<f32>) -> f32 {
+    a.get(0) * b.get(0) + a.get(1) * b.get(1) - a.get(2) * b.get(2)
+}
+/// Returns the reflection through the plane in Minkowski space with the
+/// spacelike unit normal vector `normal`.
+fn minkowski_reflection(normal: &Vector<f32>) -> Matrix<f32> {
+    let mut ret = Matrix::ident(3);
+    for x in 0..3 {
+        let signed = if x == 2 { -normal[x] } else { normal[x] };
+        for y in 0..3 {
+            *ret.get_mut(x, y) = ret.get(x, y) - 2.0 * normal[y] * signed;
+        }
+    }
+    ret
+}
+/// Returns the point on the hyperbolic plane whose inner products with
+/// `normals` are proportional to `values`, or `None` if there is no such
+/// point.
+fn hyperboloid_point(normals: &[Vector<f32>; 3], values: [f32; 3]) -> Option<Vector<f32>> {
+    let duals = normals.iter().map(|n| vector![n[0], n[1], -n[2]]);
+    let rows = Matrix::from_cols(duals.collect::<Vec<_>>()).transpose();
+    let p = rows.inverse().transform(Vector(values.to_vec()));
+    let norm_squared = -minkowski_dot(&p, &p);
+    if !norm_squared.is_finite() || norm_squared <= 0.0 {
+        return None;
+    }
+    let p = p / norm_squared.sqrt();
+    Some(if p[2] < 0.0 { -p } else { p })
+}
+/// Projects a point on the hyperbolic plane to the Poincaré disk.
+fn poincare_disk(p: &Vector<f32>) -> Vector<f32> {
+    vector![p[0], p[1]] / (1.0 + p[2])
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -77,6 +694,8 @@ pub struct MirrorGenerator {
     mirrors: Vec<Mirror>,
 }
 impl From<MirrorGenerator> for Matrix<f32> {
+    // Every `MirrorGenerator` has at least one mirror.
+    #[allow(clippy::expect_used)]
     fn from(gen: MirrorGenerator) -> Self {
         gen.mirrors
             .into_iter()
@@ -90,6 +709,11 @@ impl From<MirrorGenerator> for Matrix<f32> {
 pub struct Mirror(pub Vector<f32>);
 impl From<Mirror> for Matrix<f32> {
     fn from(mirror: Mirror) -> Self {
+        debug_assert!(
+            mirror.0.is_finite(),
+            "non-finite mirror vector {}",
+            mirror.0
+        );
         let ndim = mirror.0.ndim();
         let mut ret = Matrix::ident(ndim);
         for x in 0..ndim {
@@ -0,0 +1,180 @@
+use std::fmt;
+
+use crate::coxeter::DiagramError;
+use crate::group::GroupError;
+use crate::mesh::{OffParseError, SymmetryDefect};
+use crate::polytope::{ArenaError, HullError, PolygonError, SectionError, ShapeGeomError};
+
+/// Any error returned by this crate, for applications that don't need to
+/// distinguish them. Each variant wraps the error type of one module.
+#[derive(Debug)]
+pub enum CoxeterError {
+    /// Invalid Coxeter diagram. See [`DiagramError`].
+    Diagram(DiagramError),
+    /// Failure to construct a group. See [`GroupError`].
+    Group(GroupError),
+    /// Failure to construct a shape. See [`ShapeGeomError`].
+    ShapeGeom(ShapeGeomError),
+    /// Failure to construct or modify an arena. See [`ArenaError`].
+    Arena(ArenaError),
+    /// Failure to take a cross-section. See [`SectionError`].
+    Section(SectionError),
+    /// Failure to construct a convex hull. See [`HullError`].
+    Hull(HullError),
+    /// Invalid polygon in an arena. See [`PolygonError`].
+    Polygon(PolygonError),
+    /// Mesh that is not symmetric. See [`SymmetryDefect`].
+    Symmetry(SymmetryDefect),
+    /// Failure to read an OFF file. See [`OffParseError`].
+    OffParse(OffParseError),
+}
+impl fmt::Display for CoxeterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoxeterError::Diagram(e) => write!(f, "invalid Coxeter diagram: {e}"),
+            CoxeterError::Group(e) => write!(f, "cannot construct group: {e}"),
+            CoxeterError::ShapeGeom(e) => write!(f, "cannot construct shape: {e}"),
+            CoxeterError::Arena(e) => e.fmt(f),
+            CoxeterError::Section(e) => write!(f, "cannot take cross-section: {e}"),
+            CoxeterError::Hull(e) => write!(f, "cannot construct convex hull: {e}"),
+            CoxeterError::Polygon(e) => e.fmt(f),
+            CoxeterError::Symmetry(e) => write!(f, "mesh is not symmetric: {e}"),
+            CoxeterError::OffParse(e) => write!(f, "cannot read OFF file: {e}"),
+        }
+    }
+}
+impl std::error::Error for CoxeterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CoxeterError::Diagram(e) => Some(e),
+            CoxeterError::Group(e) => Some(e),
+            CoxeterError::ShapeGeom(e) => Some(e),
+            CoxeterError::Arena(e) => Some(e),
+            CoxeterError::Section(e) => Some(e),
+            CoxeterError::Hull(e) => Some(e),
+            CoxeterError::Polygon(e) => Some(e),
+            CoxeterError::Symmetry(e) => Some(e),
+            CoxeterError::OffParse(e) => Some(e),
+        }
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($variant:ident($error:ty)),* $(,)?) => {
+        $(
+            impl From<$error> for CoxeterError {
+                fn from(e: $error) -> Self {
+                    CoxeterError::$variant(e)
+                }
+            }
+        )*
+    };
+}
+impl_from_error!(
+    Diagram(DiagramError),
+    Group(GroupError),
+    ShapeGeom(ShapeGeomError),
+    Arena(ArenaError),
+    Section(SectionError),
+    Hull(HullError),
+    Polygon(PolygonError),
+    Symmetry(SymmetryDefect),
+    OffParse(OffParseError),
+);
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{CoxeterDiagram, Group, Hyperplane, Matrix, PolytopeArena, Vector};
+
+    /// Returns an error from `f` as a [`CoxeterError`], as an application
+    /// would with `?`.
+    fn wrap<T: fmt::Debug>(f: impl FnOnce() -> Result<T, CoxeterError>) -> CoxeterError {
+        f().unwrap_err()
+    }
+
+    #[test]
+    fn test_errors_reachable() {
+        let e = wrap(|| Ok("4,x,3".parse::<CoxeterDiagram>()?));
+        assert!(matches!(
+            &e,
+            CoxeterError::Diagram(DiagramError::ParseEdge { index: 1, text }) if text == "x",
+        ));
+        assert!(e.to_string().contains("\"x\""));
+        assert!(e.source().is_some());
+
+        let e = wrap(|| Ok(CoxeterDiagram::try_with_edges(vec![4, 1])?));
+        assert!(matches!(
+            e,
+            CoxeterError::Diagram(DiagramError::InvalidEdge { index: 1, value: 1 }),
+        ));
+        let cube = CoxeterDiagram::with_edges(vec![4, 3]);
+        let e = wrap(|| Ok(cube.try_element_counts(&[true])?));
+        assert!(matches!(
+            e,
+            CoxeterError::Diagram(DiagramError::WrongRingCount {
+                expected: 3,
+                got: 1,
+            }),
+        ));
+
+        // Hyperbolic groups have no spherical mirror vectors.
+        let e = wrap(|| Ok(CoxeterDiagram::with_edges(vec![7, 3]).try_group(1000)?));
+        assert!(matches!(
+            e,
+            CoxeterError::Group(GroupError::NonFiniteGenerator { .. }),
+        ));
+        assert_eq!(cube.try_group(48).map(|g| g.order()), Ok(48));
+        assert_eq!(
+            cube.try_group(47).map(|g| g.order()),
+            Err(GroupError::TooManyElements { limit: 47 }),
+        );
+        let (s, c) = 1.0_f32.sin_cos();
+        let rotation = Matrix::from_cols([vector![c, s], vector![-s, c]]);
+        assert!(matches!(
+            Group::try_from_generators(&[rotation], 100),
+            Err(GroupError::TooManyElements { limit: 100 }),
+        ));
+
+        let e = wrap(|| {
+            Ok(crate::shape_geom(
+                3,
+                &cube.clone().generators(),
+                &[],
+                &Default::default(),
+            )?)
+        });
+        assert!(matches!(
+            e,
+            CoxeterError::ShapeGeom(ShapeGeomError::NoBaseFacets)
+        ));
+        assert_eq!(e.to_string(), "cannot construct shape: no base facets");
+
+        let e = wrap(|| Ok(PolytopeArena::new_cube(255, 1.0)?));
+        assert!(matches!(
+            e,
+            CoxeterError::Arena(ArenaError::DimensionTooLarge { ndim: 255 })
+        ));
+
+        let square = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let e = wrap(|| Ok(square.cross_section(&Hyperplane::new(vector![0.0, 0.0, 1.0], 0.0))?));
+        assert!(matches!(
+            e,
+            CoxeterError::Section(SectionError::PlaneOutsideSpace { ndim: 2 })
+        ));
+
+        let e = wrap(|| Ok(PolytopeArena::from_convex_hull(&[vector![1.0]])?));
+        assert!(matches!(
+            e,
+            CoxeterError::Hull(HullError::TooFewPoints { .. })
+        ));
+
+        let e = wrap(|| Ok(crate::Mesh::read_off("OFF\n1 2".as_bytes())?));
+        assert!(matches!(e, CoxeterError::OffParse(_)));
+
+        assert_eq!(Hyperplane::try_new(vector![0.0, 0.0], 1.0), None);
+        assert!(Hyperplane::try_new(vector![0.0, 2.0], 1.0).is_some());
+    }
+}
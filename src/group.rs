@@ -1,6 +1,84 @@
 use itertools::Itertools;
+use std::fmt;
 
 use crate::matrix::*;
+use crate::perm::Permutation;
+use crate::util::{par_map, Tolerance, EPSILON};
+use crate::vector::*;
+
+/// Error returned by [`Group::try_from_generators()`],
+/// [`Group::from_generators_with_options()`], and [`Group::conjugated()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GroupError {
+    /// The group has more than the maximum number of elements, which usually
+    /// means that it is infinite.
+    TooManyElements {
+        /// Maximum number of elements.
+        limit: u32,
+    },
+    /// A generator has an element that is NaN or infinite.
+    NonFiniteGenerator {
+        /// Index of the generator.
+        index: usize,
+    },
+    /// An element is not orthogonal, so [`GroupOptions::orthonormalize`]
+    /// cannot be used. The generators are checked first, so if any generator
+    /// is not orthogonal then this is that generator.
+    NotOrthogonal {
+        /// Element that is not orthogonal.
+        element: GroupElement,
+    },
+    /// The matrix passed to [`Group::conjugated()`] is not invertible.
+    SingularMatrix,
+    /// The matrix passed to [`Group::conjugated()`] is not orthogonal up to
+    /// scale, so the conjugated group would not be orthogonal. See
+    /// [`Group::conjugated_non_orthogonal()`].
+    NonOrthogonalMatrix,
+}
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupError::TooManyElements { limit } => {
+                write!(f, "group has more than {limit} elements")
+            }
+            GroupError::NonFiniteGenerator { index } => {
+                write!(f, "generator {index} is not finite")
+            }
+            GroupError::NotOrthogonal { element } => {
+                write!(f, "group element {} is not orthogonal", element.0)
+            }
+            GroupError::SingularMatrix => write!(f, "matrix is not invertible"),
+            GroupError::NonOrthogonalMatrix => {
+                write!(f, "matrix is not orthogonal up to scale")
+            }
+        }
+    }
+}
+impl std::error::Error for GroupError {}
+
+/// Options for [`Group::from_generators_with_options()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GroupOptions {
+    /// Tolerance for considering two matrices to be the same element. See
+    /// [`Group::from_generators_with_tolerance()`].
+    pub tolerance: Tolerance,
+    /// Maximum number of elements in the group.
+    pub max_order: u32,
+    /// Whether to orthonormalize each new element's matrix, which corrects
+    /// the floating-point error that builds up in products of many
+    /// generators. This requires every generator to be orthogonal, and every
+    /// element is checked to be orthogonal afterward.
+    pub orthonormalize: bool,
+}
+impl Default for GroupOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: Tolerance::default(),
+            max_order: u32::MAX,
+            orthonormalize: false,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Group {
@@ -10,8 +88,9 @@ pub struct Group {
     /// element).
     generator_count: u8,
 
-    /// Matrix for each element.
-    elem_matrices: Vec<Matrix<f32>>,
+    /// Matrix for each element, stored contiguously with `ndim * ndim`
+    /// elements each in column-major order. See [`Group::matrix()`].
+    elem_matrices: Vec<f32>,
     /// Decomposition into generators for each element.
     elem_decompositions: Vec<Vec<GroupElement>>,
     /// For each generator, the result of composing each element with that
@@ -30,14 +109,74 @@ impl Group {
         Self {
             ndim,
             generator_count: 0,
-            elem_matrices: vec![Matrix::ident(ndim)],
+            elem_matrices: Matrix::ident(ndim).view().elems().to_vec(),
             elem_decompositions: vec![vec![]],
             elem_successors: vec![],
             elem_inverses: vec![GroupElement(0)],
         }
     }
 
+    /// Constructs the group generated by `generators`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a generator has a non-finite element. If the generators
+    /// generate an infinite group, this never returns; use
+    /// [`Group::try_from_generators()`] to limit the order of the group.
     pub fn from_generators(generators: &[Matrix<f32>]) -> Self {
+        Self::from_generators_with_tolerance(generators, Tolerance::default())
+    }
+    /// Constructs the group generated by `generators`, considering two
+    /// matrices to be the same element if every pair of corresponding
+    /// elements is equal within `tolerance`.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`Group::from_generators()`].
+    pub fn from_generators_with_tolerance(
+        generators: &[Matrix<f32>],
+        tolerance: Tolerance,
+    ) -> Self {
+        let options = GroupOptions {
+            tolerance,
+            ..Default::default()
+        };
+        Self::from_generators_with_options(generators, &options).unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Constructs the group generated by `generators`, or returns an error if
+    /// it has more than `max_order` elements.
+    pub fn try_from_generators(
+        generators: &[Matrix<f32>],
+        max_order: u32,
+    ) -> Result<Self, GroupError> {
+        let options = GroupOptions {
+            max_order,
+            ..Default::default()
+        };
+        Self::from_generators_with_options(generators, &options)
+    }
+    /// Constructs the group generated by `generators`, or returns an error if
+    /// it cannot be constructed with `options`.
+    pub fn from_generators_with_options(
+        generators: &[Matrix<f32>],
+        options: &GroupOptions,
+    ) -> Result<Self, GroupError> {
+        let GroupOptions {
+            tolerance,
+            max_order,
+            orthonormalize,
+        } = *options;
+
+        if let Some(index) = generators.iter().position(|m| !m.is_finite()) {
+            return Err(GroupError::NonFiniteGenerator { index });
+        }
+        if orthonormalize {
+            if let Some(i) = generators.iter().position(|m| !m.is_orthogonal()) {
+                let element = GroupElement(i as u32 + 1);
+                return Err(GroupError::NotOrthogonal { element });
+            }
+        }
+
         let ndim = generators.iter().map(|m| m.ndim()).max().unwrap_or(0);
         let mut ret = Self::new_trivial(ndim);
         ret.generator_count = generators.len() as _;
@@ -46,35 +185,54 @@ impl Group {
 
         // TODO: compute period of each generator and make sure it's smallish.
 
-        // Find all group elements.
+        enter_span!(DEBUG, "generate_group", ndim, generators = generators.len());
+
+        // Find all group elements. Elements are processed in order of word
+        // length, so each level of the search ends at the order of the group
+        // when the level started.
         let mut next_unprocessed = 0;
+        let mut level = (0, 1);
         while next_unprocessed < ret.order() {
             let e = GroupElement(next_unprocessed);
+            if next_unprocessed == level.1 {
+                trace_event!(TRACE, level = level.0, order = ret.order());
+                level = (level.0 + 1, ret.order());
+            }
 
             for (i, generator_matrix) in generators.iter().enumerate() {
                 let gen = GroupElement(i as u32 + 1);
 
-                let m = ret.matrix(e) * generator_matrix;
+                let mut m = ret.matrix(e) * generator_matrix;
+                if orthonormalize {
+                    // The generators are orthogonal, so this can only fail if
+                    // something is badly wrong, which the check below catches.
+                    m = m.orthonormalized().unwrap_or(m);
+                }
 
-                let successor_element = if m.approx_eq(&Matrix::EMPTY_IDENT) {
+                let successor_element = if m.approx_eq_with(&Matrix::EMPTY_IDENT, tolerance) {
                     ret.elem_inverses[gen.idx()] = e;
 
                     // e * gen = I
                     GroupElement::IDENT
-                } else if let Some((j, _)) = ret.elem_matrices[1..]
-                    .iter()
-                    .find_position(|old| old.approx_eq(&m))
+                } else if let Some(existing) = ret
+                    .elements()
+                    .skip(1)
+                    .find(|&old| ret.matrix(old).approx_eq_with(&m, tolerance))
                 {
                     // e * gen = existing element
-                    GroupElement(j as u32 + 1)
+                    existing
                 } else {
-                    ret.elem_matrices.push(m);
+                    if ret.order() >= max_order {
+                        return Err(GroupError::TooManyElements { limit: max_order });
+                    }
+                    ret.elem_matrices
+                        .extend_from_slice(m.at_least_ndim(ndim).view().elems());
 
                     let decomposition = ret.decompose(e).iter().copied().chain([gen]).collect();
                     ret.elem_decompositions.push(decomposition);
 
                     // e * gen = new element
-                    GroupElement(ret.elem_matrices.len() as u32 - 1)
+                    GroupElement(ret.order() - 1)
                 };
 
                 ret.elem_successors[i].push(successor_element);
@@ -83,6 +241,8 @@ impl Group {
             next_unprocessed += 1;
         }
 
+        trace_event!(DEBUG, order = ret.order(), "found all group elements");
+
         // TODO: error if any generator has identity as its inverse
 
         ret.elem_inverses
@@ -103,14 +263,42 @@ impl Group {
             }
         }
 
-        ret
+        if orthonormalize {
+            let tight = Tolerance::absolute(EPSILON * EPSILON);
+            if let Some(element) = ret
+                .elements()
+                .find(|&e| !ret.matrix(e).is_orthogonal_with(tight))
+            {
+                return Err(GroupError::NotOrthogonal { element });
+            }
+        }
+
+        Ok(ret)
     }
 
     pub fn ndim(&self) -> u8 {
         self.ndim
     }
-    pub fn matrix(&self, e: GroupElement) -> &Matrix<f32> {
-        &self.elem_matrices[e.idx()]
+    /// Returns the matrix of an element. Use [`MatrixRef::to_owned()`] to
+    /// get a [`Matrix`].
+    ///
+    /// # Migration
+    ///
+    /// This used to return `&Matrix<f32>`. Calling `.clone()` on the result
+    /// still compiles, but now returns another `MatrixRef`, so replace it with
+    /// `.to_owned()`. In the meantime, [`Group::matrix_owned()`] returns an
+    /// owned matrix.
+    pub fn matrix(&self, e: GroupElement) -> MatrixRef<'_> {
+        let stride = self.ndim as usize * self.ndim as usize;
+        MatrixRef::new(self.ndim, &self.elem_matrices[e.idx() * stride..][..stride])
+    }
+    /// Returns a copy of the matrix of an element.
+    #[deprecated(
+        note = "use `Group::matrix()`, and `MatrixRef::to_owned()` if an owned matrix \
+                         is needed"
+    )]
+    pub fn matrix_owned(&self, e: GroupElement) -> Matrix<f32> {
+        self.matrix(e).to_owned()
     }
     pub fn decompose(&self, e: GroupElement) -> &[GroupElement] {
         &self.elem_decompositions[e.idx()]
@@ -125,17 +313,112 @@ impl Group {
     }
 
     pub fn order(&self) -> u32 {
-        self.elem_matrices.len() as _
+        self.elem_decompositions.len() as _
     }
-    pub fn elements(&self) -> impl Iterator<Item = GroupElement> + ExactSizeIterator {
+    pub fn elements(&self) -> impl ExactSizeIterator<Item = GroupElement> {
         (0..self.order()).map(GroupElement)
     }
-    pub fn generators(&self) -> impl Iterator<Item = GroupElement> + ExactSizeIterator {
+    pub fn generators(&self) -> impl ExactSizeIterator<Item = GroupElement> {
         (1..self.generator_count as u32 + 1).map(GroupElement)
     }
+
+    /// Returns the orbit of a point under the group, without duplicates. Each
+    /// point is paired with the first element that maps `point` to it, and
+    /// points are returned in order of that element.
+    ///
+    /// Points are considered duplicates if they are approximately equal,
+    /// relative to the magnitude of `point`.
+    pub fn orbit(&self, point: &Vector<f32>) -> Vec<(GroupElement, Vector<f32>)> {
+        self.orbit_with_parallelism(point, false)
+    }
+    /// Returns the orbit of `point` like [`Group::orbit()`], transforming it by
+    /// each element in parallel if `parallel` is `true` and the `rayon`
+    /// feature is enabled.
+    pub(crate) fn orbit_with_parallelism(
+        &self,
+        point: &Vector<f32>,
+        parallel: bool,
+    ) -> Vec<(GroupElement, Vector<f32>)> {
+        let eps = EPSILON * point.mag();
+        let elements = self.elements().collect_vec();
+        let images = par_map(parallel, &elements, |&e| {
+            (e, self.matrix(e).transform(point))
+        });
+        let mut grid = PointGrid::new(16.0 * eps);
+        let mut ret: Vec<(GroupElement, Vector<f32>)> = vec![];
+        for (e, p) in images {
+            if !grid
+                .nearby(&p, eps)
+                .any(|i| ret[i].1.approx_eq_eps(&p, eps))
+            {
+                grid.insert(&p, ret.len());
+                ret.push((e, p));
+            }
+        }
+        ret
+    }
+
+    /// Returns the permutation of `points` induced by the element `e`, which
+    /// sends `i` to the index of the image of `points[i]`, or `None` if `e`
+    /// does not map `points` onto itself. Points are compared relative to the
+    /// largest magnitude among them.
+    pub fn permutation_action(
+        &self,
+        e: GroupElement,
+        points: &[Vector<f32>],
+    ) -> Option<Permutation> {
+        let eps = EPSILON * points.iter().map(|p| p.mag()).fold(0.0, f32::max);
+        let m = self.matrix(e);
+        let one_line = points
+            .iter()
+            .map(|p| {
+                let image = m.transform(p);
+                points.iter().position(|q| q.approx_eq_eps(&image, eps))
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        Permutation::from_one_line(one_line)
+    }
+
+    /// Returns the group conjugated by `m`, which acts on points transformed
+    /// by `m` the same way that this group acts on the original points. Each
+    /// element keeps its index, decomposition, and inverse.
+    ///
+    /// Returns an error if `m` is not invertible, or if it is not orthogonal
+    /// up to scale, in which case the conjugated group would not be
+    /// orthogonal. See [`Matrix::is_orthogonal_up_to_scale()`] and
+    /// [`Group::conjugated_non_orthogonal()`].
+    pub fn conjugated(&self, m: &Matrix<f32>) -> Result<Group, GroupError> {
+        if m.is_invertible() && !m.is_orthogonal_up_to_scale() {
+            return Err(GroupError::NonOrthogonalMatrix);
+        }
+        self.conjugated_non_orthogonal(m)
+    }
+    /// Returns the group conjugated by `m` like [`Group::conjugated()`], even
+    /// if `m` is not orthogonal up to scale.
+    ///
+    /// Returns an error if `m` is not invertible.
+    pub fn conjugated_non_orthogonal(&self, m: &Matrix<f32>) -> Result<Group, GroupError> {
+        if !m.is_invertible() {
+            return Err(GroupError::SingularMatrix);
+        }
+        let ndim = std::cmp::max(self.ndim, m.ndim());
+        let m = &(m * &Matrix::ident(ndim));
+        let m_inv = &m.inverse();
+        Ok(Group {
+            ndim,
+            elem_matrices: self
+                .elements()
+                .flat_map(|e| {
+                    let g = self.matrix(e).to_owned();
+                    (&(m * &g) * m_inv).view().elems().to_vec()
+                })
+                .collect(),
+            ..self.clone()
+        })
+    }
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GroupElement(u32);
 impl GroupElement {
     pub const IDENT: Self = Self(0);
@@ -144,3 +427,203 @@ impl GroupElement {
         self.0 as _
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_storage() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3, 3]).generators();
+        let group = Group::from_generators(&generators);
+        assert_eq!(group.order(), 384);
+
+        // Each matrix is the product of its decomposition.
+        for e in group.elements() {
+            let product = group
+                .decompose(e)
+                .iter()
+                .fold(Matrix::ident(4), |m, &g| &m * &generators[g.idx() - 1]);
+            assert!(group.matrix(e).approx_eq(&product));
+            assert_eq!(group.matrix(e), group.matrix(e).to_owned());
+            #[allow(deprecated)]
+            let owned = group.matrix_owned(e);
+            assert_eq!(group.matrix(e), owned);
+            for g in group.generators() {
+                let composed = group.compose(e, g);
+                let product = group.matrix(e) * &group.matrix(g).to_owned();
+                assert!(group.matrix(composed).approx_eq(&product));
+            }
+        }
+    }
+
+    #[test]
+    fn test_orbit() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        assert_eq!(group.order(), 48);
+
+        let orbit = group.orbit(&vector![1.0]);
+        assert_eq!(orbit.len(), 6);
+        assert_eq!(orbit[0].0, GroupElement::IDENT);
+        assert!(orbit[0].1.approx_eq(vector![1.0]));
+        for (e, point) in &orbit {
+            assert!(group.matrix(*e).transform(vector![1.0]).approx_eq(point));
+        }
+
+        let orbit = group.orbit(&vector![1.0, 2.0, 3.0]);
+        assert_eq!(orbit.len(), 48);
+        assert!(orbit
+            .iter()
+            .map(|(e, _)| e)
+            .eq(group.elements().collect_vec().iter()));
+
+        // Points near a mirror have images that are approximately equal to
+        // each other in chains, so approximate equality is not transitive.
+        for y in [0.0003, 0.0005, 0.0007, 0.001, 0.0015] {
+            let point = vector![1.0, y, 0.3];
+            let eps = EPSILON * point.mag();
+            let orbit = group.orbit(&point);
+            assert_eq!(orbit[0], (GroupElement::IDENT, point.clone()));
+            assert!(orbit.windows(2).all(|w| w[0].0 < w[1].0));
+            for (i, (_, a)) in orbit.iter().enumerate() {
+                for (_, b) in &orbit[..i] {
+                    assert!(!a.approx_eq_eps(b, eps), "{a} {b}");
+                }
+            }
+            for e in group.elements() {
+                let image = group.matrix(e).transform(&point);
+                assert!(orbit.iter().any(|(_, p)| p.approx_eq_eps(&image, eps)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_permutation_action() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let axes = [vector![1.0], vector![0.0, 1.0], vector![0.0, 0.0, 1.0]];
+        let points: Vec<Vector<f32>> = axes.iter().flat_map(|v| [v.clone(), -v]).collect();
+        let actions: Vec<Permutation> = group
+            .elements()
+            .map(|e| group.permutation_action(e, &points).unwrap())
+            .collect();
+        assert_eq!(actions[0], Permutation::ident(6));
+        // The cube's symmetries act faithfully on its faces.
+        assert!(actions.iter().all_unique());
+        for e1 in group.elements() {
+            for e2 in group.generators() {
+                let p = &actions[group.compose(e1, e2).idx()];
+                // The matrix of `e1 * e2` applies `e2` first.
+                assert_eq!(*p, actions[e1.idx()].compose(&actions[e2.idx()]));
+            }
+        }
+        // Permutations of the coordinate axes have permutation matrices.
+        let axis_permutations = group
+            .elements()
+            .filter(|&e| {
+                let m = group.matrix(e);
+                Permutation::all(3).any(|p| Matrix::from_permutation(&p).approx_eq(m))
+            })
+            .count();
+        assert_eq!(axis_permutations, 6);
+        for p in Permutation::all(3) {
+            let m = Matrix::<f32>::from_permutation(&p);
+            assert_eq!(m.determinant(), if p.parity() { -1.0 } else { 1.0 });
+            assert_eq!(
+                m.transform(vector![1.0, 2.0, 3.0]),
+                p.apply_to_vector(vector![1.0, 2.0, 3.0])
+            );
+        }
+
+        assert_eq!(
+            group.permutation_action(GroupElement(1), &[vector![1.0, 2.0, 3.0]]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        assert_eq!(Group::from_generators(&generators).order(), 48);
+
+        // Stretching the group makes some elements of its matrices large, so
+        // they are compared with a relative tolerance.
+        let m = Matrix::from_cols([vector![1000.0], vector![0.0, 1.0], vector![0.0, 0.0, 1.0]]);
+        let stretched = Group::from_generators(&generators)
+            .conjugated_non_orthogonal(&m)
+            .unwrap();
+        let stretched_generators: Vec<Matrix<f32>> = stretched
+            .generators()
+            .map(|g| stretched.matrix(g).to_owned())
+            .collect();
+        let tolerance = Tolerance {
+            abs: EPSILON,
+            rel: EPSILON,
+        };
+        let group = Group::from_generators_with_tolerance(&stretched_generators, tolerance);
+        assert_eq!(group.order(), 48);
+
+        // A tolerance that is too loose merges distinct elements.
+        let group = Group::from_generators_with_tolerance(&generators, Tolerance::absolute(1.5));
+        assert!(group.order() < 48);
+    }
+
+    fn orthonormalized_group_order(edges: Vec<usize>) -> u32 {
+        let options = GroupOptions {
+            tolerance: Tolerance::absolute(1e-4),
+            orthonormalize: true,
+            ..Default::default()
+        };
+        let generators = crate::CoxeterDiagram::with_edges(edges).generators();
+        let group = Group::from_generators_with_options(&generators, &options).unwrap();
+        assert!(group.elements().all(|e| group.matrix(e).is_orthogonal()));
+        group.order()
+    }
+
+    /// Slow in debug builds. Run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_orthonormalize_120_cell() {
+        assert_eq!(orthonormalized_group_order(vec![5, 3, 3]), 14400);
+    }
+
+    #[test]
+    fn test_orthonormalize() {
+        assert_eq!(orthonormalized_group_order(vec![5, 3]), 120);
+        assert_eq!(orthonormalized_group_order(vec![3, 3, 3]), 120);
+        assert_eq!(orthonormalized_group_order(vec![4, 3, 3]), 384);
+
+        // Scale one generator slightly, which is still orthogonal within
+        // `EPSILON`. Without orthonormalization the error compounds with
+        // every product, so the group never closes.
+        let mut generators = crate::CoxeterDiagram::with_edges(vec![5, 3]).generators();
+        generators[0] = generators[0].clone().scale(1.0002);
+        let mut options = GroupOptions {
+            tolerance: Tolerance::absolute(1e-4),
+            max_order: 1000,
+            orthonormalize: false,
+        };
+        assert_eq!(
+            Group::from_generators_with_options(&generators, &options).map(|g| g.order()),
+            Err(GroupError::TooManyElements { limit: 1000 }),
+        );
+        options.orthonormalize = true;
+        assert_eq!(
+            Group::from_generators_with_options(&generators, &options).map(|g| g.order()),
+            Ok(120),
+        );
+
+        let options = GroupOptions {
+            orthonormalize: true,
+            ..Default::default()
+        };
+
+        let m = Matrix::from_cols([vector![2.0], vector![0.0, 1.0]]);
+        assert_eq!(
+            Group::from_generators_with_options(&[Matrix::ident(2), m], &options)
+                .map(|g| g.order()),
+            Err(GroupError::NotOrthogonal {
+                element: GroupElement(2),
+            }),
+        );
+    }
+}
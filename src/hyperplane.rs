@@ -0,0 +1,230 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::group::Group;
+use crate::matrix::Matrix;
+use crate::vector::{Real, Vector, VectorRef};
+
+/// Hyperplane `{x : x · n̂ = distance}`, where `n̂` is a unit normal vector.
+///
+/// The side of the hyperplane that the normal points away from is considered
+/// "inside" for the purposes of slicing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperplane<N: Real = f32> {
+    normal: Vector<N>,
+    distance: N,
+}
+impl<N: Real> Hyperplane<N> {
+    /// Constructs a hyperplane perpendicular to `normal` at signed distance
+    /// `distance` from the origin. `normal` does not need to be normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `normal` is zero.
+    // Documented panic; see `Hyperplane::try_new()`.
+    #[allow(clippy::expect_used)]
+    #[track_caller]
+    pub fn new(normal: impl VectorRef<N>, distance: N) -> Self {
+        let normal = normal
+            .normalized()
+            .expect("hyperplane normal must be nonzero");
+        Self { normal, distance }
+    }
+    /// Constructs a hyperplane perpendicular to `normal` at signed distance
+    /// `distance` from the origin, or returns `None` if `normal` is zero.
+    pub fn try_new(normal: impl VectorRef<N>, distance: N) -> Option<Self> {
+        Some(Self {
+            normal: normal.normalized()?,
+            distance,
+        })
+    }
+    /// Constructs the hyperplane through `pole` perpendicular to it. Unlike
+    /// [`Hyperplane::new()`], `pole` may be arbitrarily small, since its
+    /// magnitude is the scale of the hyperplane.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pole` is zero.
+    #[track_caller]
+    pub fn from_pole(pole: impl VectorRef<N>) -> Self {
+        let distance = pole.mag();
+        assert!(distance > N::zero(), "hyperplane normal must be nonzero");
+        Self {
+            normal: pole.iter().map(|x| x / distance).collect(),
+            distance,
+        }
+    }
+
+    /// Returns the unit normal vector of the hyperplane.
+    pub fn normal(&self) -> &Vector<N> {
+        &self.normal
+    }
+    /// Returns the signed distance of the hyperplane from the origin.
+    pub fn distance(&self) -> N {
+        self.distance
+    }
+    /// Returns the pole of the hyperplane, which is the point on the
+    /// hyperplane closest to the origin.
+    pub fn pole(&self) -> Vector<N> {
+        &self.normal * self.distance
+    }
+
+    /// Returns the signed distance from a point to the hyperplane, which is
+    /// positive outside the hyperplane and negative inside it.
+    pub fn signed_distance(&self, point: impl VectorRef<N>) -> N {
+        point.dot(&self.normal) - self.distance
+    }
+
+    /// Converts the hyperplane to a different floating-point type.
+    // Conversion between float types cannot fail.
+    #[allow(clippy::expect_used)]
+    pub fn cast<M: Real>(&self) -> Hyperplane<M> {
+        Hyperplane {
+            normal: self
+                .normal
+                .iter()
+                .map(|x| M::from(x).expect("float conversion failed"))
+                .collect(),
+            distance: M::from(self.distance).expect("float conversion failed"),
+        }
+    }
+}
+impl Hyperplane {
+    /// Returns hyperplanes tangent to the sphere with center `center` and
+    /// radius `radius`, which together approximate the ball when slicing by
+    /// all of them using [`PolytopeArena::slice_by_all()`].
+    ///
+    /// The points of tangency are spread over the hemisphere facing the
+    /// origin, which is where the sphere meets a polytope centered at the
+    /// origin, or over the whole sphere if `center` is the origin. The first
+    /// plane is tangent at the point of the sphere closest to the origin. In
+    /// 1D there is only that one.
+    ///
+    /// [`PolytopeArena::slice_by_all()`]: crate::PolytopeArena::slice_by_all
+    // A unit vector is linearly independent.
+    #[allow(clippy::expect_used)]
+    pub fn sphere_approximation(
+        center: &Vector<f32>,
+        radius: f32,
+        facets: usize,
+        ndim: u8,
+    ) -> Vec<Hyperplane> {
+        if ndim == 0 || facets == 0 {
+            return vec![];
+        }
+        let center = center.pad(ndim);
+        let (axis, max_angle) = match (&center * -1.0).normalized() {
+            Some(axis) => (axis, FRAC_PI_2),
+            None => (Vector::unit(0).pad(ndim), PI),
+        };
+        let basis = Matrix::complete_basis(std::slice::from_ref(&axis), ndim)
+            .expect("unit vector is linearly independent");
+
+        // Spread points over the unit ball in the remaining dimensions using
+        // the low-discrepancy R_d sequence, and map each one to a direction at
+        // an angle from the axis proportional to its distance from the center.
+        let d = ndim as usize - 1;
+        let phi = (0..32).fold(2.0_f64, |x, _| (1.0 + x).powf(1.0 / (d as f64 + 1.0)));
+        let alpha: Vec<f64> = (1..=d as i32).map(|i| phi.powi(-i)).collect();
+        let mut directions = vec![axis.clone()];
+        let mut k = 0;
+        while d > 0 && directions.len() < facets {
+            k += 1;
+            let p: Vec<f32> = alpha
+                .iter()
+                .map(|a| ((0.5 + k as f64 * a).fract() * 2.0 - 1.0) as f32)
+                .collect();
+            let r = p.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if r == 0.0 || r > 1.0 {
+                continue;
+            }
+            let angle = r * max_angle;
+            let direction = p.iter().zip(1..).fold(&axis * angle.cos(), |v, (&x, col)| {
+                &v + &(&basis.col(col).to_vector() * (x / r * angle.sin()))
+            });
+            directions.push(direction);
+        }
+
+        directions
+            .into_iter()
+            .map(|n| {
+                let distance = n.dot(&center) + radius;
+                Hyperplane::new(n, distance)
+            })
+            .collect()
+    }
+    /// Returns hyperplanes tangent to the sphere with center `center` and
+    /// radius `radius` at the orbit of `direction` under `group`. The result is
+    /// symmetric if `group` fixes `center`. Returns no hyperplanes if
+    /// `direction` is zero. See [`Hyperplane::sphere_approximation()`].
+    pub fn sphere_approximation_from_orbit(
+        center: &Vector<f32>,
+        radius: f32,
+        group: &Group,
+        direction: &Vector<f32>,
+    ) -> Vec<Hyperplane> {
+        let Some(direction) = direction.normalized() else {
+            return vec![];
+        };
+        group
+            .orbit(&direction)
+            .into_iter()
+            .map(|(_, n)| {
+                let distance = n.dot(center) + radius;
+                Hyperplane::new(n, distance)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{f32_approx_eq, EPSILON};
+
+    #[test]
+    fn test_hyperplane() {
+        let plane = Hyperplane::from_pole(vector![0.0, 3.0, 4.0]);
+        assert!(plane.normal().approx_eq(vector![0.0, 0.6, 0.8]));
+        assert_eq!(plane.distance(), 5.0);
+        assert!(plane.pole().approx_eq(vector![0.0, 3.0, 4.0]));
+        assert!(f32_approx_eq(
+            plane.signed_distance(vector![0.0, 3.0, 4.0]),
+            0.0
+        ));
+        assert!(f32_approx_eq(plane.signed_distance(Vector::EMPTY), -5.0));
+        assert!(f32_approx_eq(
+            plane.signed_distance(vector![0.0, 6.0, 8.0]),
+            5.0
+        ));
+
+        let plane = Hyperplane::new(vector![0.0, 0.0, -2.0], 0.5);
+        assert!(plane.normal().approx_eq(vector![0.0, 0.0, -1.0]));
+        assert!(f32_approx_eq(
+            plane.signed_distance(vector![7.0, 0.0, 1.0]),
+            -1.5
+        ));
+    }
+
+    #[test]
+    fn test_sphere_approximation() {
+        let center = vector![-2.0, -2.0, -2.0];
+        let planes = Hyperplane::sphere_approximation(&center, 4.8, 16, 3);
+        assert_eq!(planes.len(), 16);
+        let axis = vector![1.0, 1.0, 1.0].normalized().unwrap();
+        assert!(planes[0].normal().approx_eq(&axis));
+        for plane in &planes {
+            // Tangent to the sphere, on the hemisphere facing the origin.
+            assert!(f32_approx_eq(plane.signed_distance(&center), -4.8));
+            assert!(plane.normal().dot(&axis) >= -EPSILON);
+        }
+
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let planes =
+            Hyperplane::sphere_approximation_from_orbit(&Vector::EMPTY, 2.0, &group, &vector![1.0]);
+        assert_eq!(planes.len(), 6);
+        assert!(planes.iter().all(|plane| plane.distance() == 2.0));
+        let planes =
+            Hyperplane::sphere_approximation_from_orbit(&Vector::EMPTY, 2.0, &group, &vector![]);
+        assert!(planes.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+
+/// Abstract incidence lattice of one or more polytopes, without coordinates.
+///
+/// Elements are grouped by rank and densely numbered within each rank. Each
+/// element stores the sorted indices of its children, which are elements of
+/// the rank below. Vertices have no children, and the empty face is omitted.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Lattice {
+    /// Children of each element, indexed by rank and then by element index.
+    ranks: Vec<Vec<Vec<u32>>>,
+}
+impl Lattice {
+    /// Constructs a lattice from the children of each element, indexed by rank
+    /// and then by element index. Child lists do not need to be sorted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a vertex has children or if a child index is out of range.
+    #[track_caller]
+    pub fn new(mut ranks: Vec<Vec<Vec<u32>>>) -> Self {
+        for rank in 0..ranks.len() {
+            let child_count = match rank.checked_sub(1) {
+                Some(r) => ranks[r].len(),
+                None => 0,
+            };
+            for children in &mut ranks[rank] {
+                assert!(
+                    children.iter().all(|&c| (c as usize) < child_count),
+                    "child index out of range in rank {rank}",
+                );
+                children.sort_unstable();
+                children.dedup();
+            }
+        }
+        Self { ranks }
+    }
+
+    /// Returns the rank of the highest elements, or `None` if the lattice is
+    /// empty.
+    pub fn rank(&self) -> Option<u8> {
+        (self.ranks.len() as u8).checked_sub(1)
+    }
+    /// Returns the number of elements of each rank, indexed by rank.
+    pub fn element_counts(&self) -> Vec<usize> {
+        self.ranks.iter().map(Vec::len).collect()
+    }
+    /// Returns the children of every element of a rank.
+    pub fn elements(&self, rank: u8) -> &[Vec<u32>] {
+        self.ranks.get(rank as usize).map_or(&[], |r| r)
+    }
+    /// Returns the sorted parents of each element, indexed by rank and then
+    /// by element index.
+    fn parents(&self) -> Vec<Vec<Vec<u32>>> {
+        let mut ret: Vec<Vec<Vec<u32>>> =
+            self.ranks.iter().map(|r| vec![vec![]; r.len()]).collect();
+        for (rank, elements) in self.ranks.iter().enumerate().skip(1) {
+            for (i, children) in elements.iter().enumerate() {
+                for &c in children {
+                    ret[rank - 1][c as usize].push(i as u32);
+                }
+            }
+        }
+        ret
+    }
+    /// Returns the sorted indices of the vertices of an element.
+    fn vertices_of(&self, rank: usize, index: u32) -> Vec<u32> {
+        let mut elements = vec![index];
+        for r in (1..=rank).rev() {
+            elements = elements
+                .iter()
+                .flat_map(|&e| &self.ranks[r][e as usize])
+                .copied()
+                .sorted_unstable()
+                .dedup()
+                .collect();
+        }
+        elements
+    }
+
+    /// Returns the dual lattice, in which the rank of every element is
+    /// reversed. Each polytope of the highest rank is replaced by its dual,
+    /// so the dual of a cube's lattice is an octahedron's.
+    pub fn dual(&self) -> Self {
+        let n = match self.ranks.len().checked_sub(1) {
+            None | Some(0) => return self.clone(),
+            Some(n) => n,
+        };
+        let parents = self.parents();
+        // Element `i` of rank `r` becomes element `i` of rank `n - 1 - r`,
+        // except that the polytopes of rank `n` keep their rank and contain
+        // their former vertices.
+        let mut ranks: Vec<Vec<Vec<u32>>> = (0..n)
+            .rev()
+            .map(|r| match r == n - 1 {
+                true => vec![vec![]; self.ranks[r].len()],
+                false => parents[r].clone(),
+            })
+            .collect();
+        ranks.push(
+            (0..self.ranks[n].len() as u32)
+                .map(|top| self.vertices_of(n, top))
+                .collect(),
+        );
+        Self { ranks }
+    }
+
+    /// Returns a canonical relabeling of the lattice, such that two lattices
+    /// have the same canonical form if and only if they are isomorphic.
+    ///
+    /// Returns `None` if the lattice is not the face lattice of disjoint
+    /// polytopes of the same rank, because some element is not below a
+    /// polytope of the highest rank, some elements are shared between them, or
+    /// the diamond property doesn't hold.
+    ///
+    /// This takes time quadratic in the number of flags, so it is only
+    /// suitable for small polytopes.
+    pub fn canonical_form(&self) -> Option<Self> {
+        let n = match self.ranks.len().checked_sub(1) {
+            None => return Some(Self::default()),
+            Some(n) => n,
+        };
+        let parents = self.parents();
+
+        // Canonicalize each polytope separately, then sort them.
+        let mut components = vec![];
+        for top in 0..self.ranks[n].len() as u32 {
+            let mut best = None;
+            for flag in self.flags_of(n, top) {
+                let form = self.relabel_from_flag(flag, &parents)?;
+                if best.as_ref().is_none_or(|best| form < *best) {
+                    best = Some(form);
+                }
+            }
+            components.push(best?);
+        }
+        components.sort();
+
+        let mut ranks: Vec<Vec<Vec<u32>>> = vec![vec![]; n + 1];
+        for component in components {
+            let offsets = ranks.iter().map(|r| r.len() as u32).collect_vec();
+            for (rank, elements) in component.into_iter().enumerate() {
+                ranks[rank].extend(elements.into_iter().map(|children| {
+                    children
+                        .into_iter()
+                        .map(|c| c + offsets[rank - 1])
+                        .collect()
+                }));
+            }
+        }
+        let ret = Self { ranks };
+        (ret.element_counts() == self.element_counts()).then_some(ret)
+    }
+    /// Returns whether two lattices are isomorphic. Always returns `false` if
+    /// either lattice has no canonical form.
+    ///
+    /// See [`Lattice::canonical_form()`].
+    pub fn is_isomorphic(&self, other: &Self) -> bool {
+        self.element_counts() == other.element_counts()
+            && match (self.canonical_form(), other.canonical_form()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            }
+    }
+
+    /// Returns every flag of an element, indexed by rank.
+    // Every flag starts with the element itself.
+    #[allow(clippy::expect_used)]
+    fn flags_of(&self, rank: usize, index: u32) -> Vec<Vec<u32>> {
+        let mut flags = vec![vec![index]];
+        for r in (1..=rank).rev() {
+            flags = flags
+                .into_iter()
+                .flat_map(|flag| {
+                    let last = *flag.last().expect("flag is empty");
+                    self.ranks[r][last as usize].iter().map(move |&c| {
+                        let mut flag = flag.clone();
+                        flag.push(c);
+                        flag
+                    })
+                })
+                .collect();
+        }
+        for flag in &mut flags {
+            flag.reverse();
+        }
+        flags
+    }
+    /// Returns the flag that differs from `flag` only in rank `rank`, or
+    /// `None` if there isn't exactly one.
+    fn adjacent_flag(
+        &self,
+        flag: &[u32],
+        rank: usize,
+        parents: &[Vec<Vec<u32>>],
+    ) -> Option<Vec<u32>> {
+        let above = &self.ranks[rank + 1][flag[rank + 1] as usize];
+        let mut candidates = above.iter().filter(|&&e| {
+            e != flag[rank]
+                && match rank.checked_sub(1) {
+                    Some(r) => parents[r][flag[r] as usize].binary_search(&e).is_ok(),
+                    None => true,
+                }
+        });
+        let e = *candidates.next()?;
+        if candidates.next().is_some() {
+            return None;
+        }
+        let mut ret = flag.to_vec();
+        ret[rank] = e;
+        Some(ret)
+    }
+    /// Relabels the elements of the polytope containing `flag` in the order
+    /// that they are reached by a breadth-first search of its flags, starting
+    /// from `flag`. Returns the children of each relabeled element, indexed
+    /// by rank and then by new index.
+    fn relabel_from_flag(
+        &self,
+        flag: Vec<u32>,
+        parents: &[Vec<Vec<u32>>],
+    ) -> Option<Vec<Vec<Vec<u32>>>> {
+        let n = flag.len() - 1;
+        let mut labels: Vec<HashMap<u32, u32>> = vec![HashMap::new(); n + 1];
+        let mut order: Vec<Vec<u32>> = vec![vec![]; n + 1];
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(flag.clone());
+        queue.push_back(flag);
+        while let Some(flag) = queue.pop_front() {
+            for (rank, &e) in flag.iter().enumerate() {
+                labels[rank].entry(e).or_insert_with(|| {
+                    order[rank].push(e);
+                    order[rank].len() as u32 - 1
+                });
+            }
+            for rank in 0..n {
+                let adjacent = self.adjacent_flag(&flag, rank, parents)?;
+                if seen.insert(adjacent.clone()) {
+                    queue.push_back(adjacent);
+                }
+            }
+        }
+
+        order
+            .iter()
+            .enumerate()
+            .map(|(rank, elements)| {
+                elements
+                    .iter()
+                    .map(|&e| {
+                        let children = &self.ranks[rank][e as usize];
+                        let mut new_children = children
+                            .iter()
+                            .map(|c| labels[rank - 1].get(c).copied())
+                            .collect::<Option<Vec<u32>>>()?;
+                        new_children.sort_unstable();
+                        Some(new_children)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Writes the lattice as JSON: an array with one entry per rank, each of
+    /// which is an array with the child indices of each element.
+    pub fn write_json(&self, mut w: impl io::Write) -> io::Result<()> {
+        write!(w, "[")?;
+        for (rank, elements) in self.ranks.iter().enumerate() {
+            if rank > 0 {
+                write!(w, ",")?;
+            }
+            write!(w, "[")?;
+            for (i, children) in elements.iter().enumerate() {
+                if i > 0 {
+                    write!(w, ",")?;
+                }
+                write!(w, "[{}]", children.iter().join(","))?;
+            }
+            write!(w, "]")?;
+        }
+        write!(w, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_cube_lattice() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap().incidence_lattice();
+        assert_eq!(cube.element_counts(), [8, 12, 6, 1]);
+        assert!(cube.elements(2).iter().all(|face| face.len() == 4));
+        assert!(cube.elements(1).iter().all(|edge| edge.len() == 2));
+        assert_eq!(cube.dual().dual(), cube);
+
+        let generators = CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let pole = vector![1.0, 1.0, 1.0] / 3.0_f32.sqrt();
+        let octahedron = shape_arena(3, &generators, &[pole], &SliceOptions::default())
+            .unwrap()
+            .incidence_lattice();
+        assert_eq!(octahedron.element_counts(), [6, 12, 8, 1]);
+        assert!(octahedron.is_isomorphic(&cube.dual()));
+        assert!(!octahedron.is_isomorphic(&cube));
+
+        // A cube built from the same symmetry has different labels but the
+        // same structure.
+        let other_cube = shape_arena(3, &generators, &[Vector::unit(0)], &SliceOptions::default())
+            .unwrap()
+            .incidence_lattice();
+        assert!(other_cube.is_isomorphic(&cube));
+        assert!(cube.dual().is_isomorphic(&octahedron));
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        // Two triangles with vertices listed in different orders.
+        let a = Lattice::new(vec![
+            vec![vec![]; 3],
+            vec![vec![0, 1], vec![1, 2], vec![2, 0]],
+            vec![vec![0, 1, 2]],
+        ]);
+        let b = Lattice::new(vec![
+            vec![vec![]; 3],
+            vec![vec![2, 1], vec![0, 2], vec![1, 0]],
+            vec![vec![2, 0, 1]],
+        ]);
+        assert_ne!(a, b);
+        assert_eq!(a.canonical_form(), b.canonical_form());
+        assert!(a.is_isomorphic(&a.dual()));
+
+        // A vertex with three edges doesn't satisfy the diamond property.
+        let c = Lattice::new(vec![vec![vec![]; 3], vec![vec![0, 1, 2]]]);
+        assert_eq!(c.canonical_form(), None);
+    }
+
+    #[test]
+    fn test_lattice_json() {
+        let square = PolytopeArena::new_cube(2, 1.0).unwrap().incidence_lattice();
+        let mut json = vec![];
+        square.write_json(&mut json).unwrap();
+        let parsed: Vec<Vec<Vec<u32>>> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(Lattice::new(parsed), square);
+    }
+}
@@ -1,22 +1,42 @@
 //! Coxeter diagrams for puzzle symmetry groups.
 
 // #![warn(missing_docs)]
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
 
+#[macro_use]
+mod trace;
 #[macro_use]
 mod vector;
 #[macro_use]
 mod matrix;
 mod coxeter;
+mod error;
 mod group;
+mod hyperplane;
+mod lattice;
+mod mesh;
+mod perm;
 mod polytope;
+mod spec;
 // mod shape;
+#[doc(hidden)]
+pub mod testutil;
 mod util;
 
 pub use coxeter::*;
+pub use error::*;
 pub use group::*;
+pub use hyperplane::*;
+pub use lattice::*;
 pub use matrix::*;
+pub use mesh::*;
+pub use perm::*;
 pub use polytope::*;
+pub use spec::*;
 // pub use shape::*;
+pub use util::{
+    approx_eq_rel, approx_eq_ulps, binomial, checked_factorial, factorial, multinomial, Tolerance,
+};
 pub use vector::*;
 
 #[cfg(test)]
@@ -59,6 +79,230 @@ mod tests {
         // assert_group_order(vec![3; 5], 5040);
     }
 
+    #[test]
+    fn test_mirror_ndim() {
+        for edges in [vec![], vec![5], vec![4, 3], vec![3, 3, 3, 3]] {
+            let cd = CoxeterDiagram::with_edges(edges);
+            for mirror in cd.mirrors() {
+                assert_eq!(mirror.0.ndim(), cd.ndim());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "non-finite mirror vector")]
+    fn test_nan_mirrors() {
+        CoxeterDiagram::with_edges(vec![7, 3]).mirrors();
+    }
+
+    #[test]
+    fn test_group_cache() {
+        use std::sync::Arc;
+
+        let cache = GroupCache::new();
+        let cube = CoxeterDiagram::with_edges(vec![4, 3]);
+        let a = cube.group_cached(&cache);
+        let b = cube.group_cached(&cache);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cube.try_group_cached(&cache, 48).map(|g| g.order()), Ok(48));
+        assert_eq!(
+            cube.try_group_cached(&cache, 47).map(|g| g.order()),
+            Err(GroupError::TooManyElements { limit: 47 }),
+        );
+
+        let octahedron = CoxeterDiagram::with_edges(vec![3, 4]);
+        let b = octahedron.group_cached(&cache);
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 2);
+        // The groups are isomorphic, but their generators are different
+        // matrices, so neither could be used in place of the other.
+        assert_eq!(a.order(), b.order());
+        assert!(a
+            .generators()
+            .zip(b.generators())
+            .any(|(g, h)| !a.matrix(g).approx_eq(b.matrix(h))));
+        let hyperbolic = CoxeterDiagram::with_edges(vec![7, 3]);
+        assert!(hyperbolic.try_group_cached(&cache, 1000).is_err());
+        assert_eq!(cache.len(), 2);
+
+        let shape = shape_geom_with_group(&a, &[vector![1.0]], &SliceOptions::default());
+        assert_eq!(shape.unwrap().facets.len(), 6);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(!Arc::ptr_eq(&a, &cube.group_cached(&cache)));
+    }
+
+    #[test]
+    fn test_mirror_gram_matrix() {
+        use std::f32::consts::PI;
+
+        let mirrors = CoxeterDiagram::with_edges(vec![4, 3]).mirrors();
+        let vectors = mirrors.into_iter().map(|m| m.0).collect::<Vec<_>>();
+        let gram = Matrix::gram(&vectors);
+        let (a, b) = ((PI / 4.0).cos(), (PI / 3.0).cos());
+        assert!(gram.approx_eq(&matrix![[1.0, a, 0.0], [a, 1.0, b], [0.0, b, 1.0]]));
+    }
+
+    #[test]
+    fn test_element_counts() {
+        let options = SliceOptions::default();
+        for (edges, pole, expected) in [
+            (vec![4, 3], vector![1.0], vec![8, 12, 6, 1]),
+            (vec![4, 3], vector![1.0, 1.0, 1.0], vec![6, 12, 8, 1]),
+            (vec![4, 3, 3], vector![1.0], vec![16, 32, 24, 8, 1]),
+            (vec![5, 3], vector![1.0, 0.3], vec![]),
+            (vec![3, 3, 3], vector![1.0, 0.5, 0.25], vec![]),
+        ] {
+            let cd = CoxeterDiagram::with_edges(edges);
+            let ndim = cd.ndim();
+            let generators = cd.clone().generators();
+            let arena = shape_arena(ndim, &generators, std::slice::from_ref(&pole), &options);
+            let arena = arena.unwrap();
+            if !expected.is_empty() {
+                assert_eq!(arena.element_counts(), expected);
+            }
+            let expected_euler = if ndim % 2 == 1 { 2 } else { 0 };
+            assert_eq!(arena.euler_characteristic(), expected_euler);
+
+            // The shape is the dual of the uniform polytope with a vertex at
+            // the pole.
+            let mut predicted = cd.element_counts(&cd.ringed_nodes(&pole).unwrap());
+            predicted[..ndim as usize].reverse();
+            assert_eq!(arena.element_counts(), predicted, "{cd:?}");
+        }
+
+        // Orders of large groups are computed without generating them.
+        let h4 = CoxeterDiagram::with_edges(vec![5, 3, 3]);
+        assert_eq!(
+            h4.element_counts(&[true, false, false, false]),
+            [600, 1200, 720, 120, 1],
+        );
+        assert_eq!(
+            h4.element_counts(&[false, false, false, true]),
+            [120, 720, 1200, 600, 1],
+        );
+        let f4 = CoxeterDiagram::with_edges(vec![3, 4, 3]);
+        assert_eq!(
+            f4.element_counts(&[true, false, false, false]),
+            [24, 96, 96, 24, 1],
+        );
+        for edges in [
+            vec![7, 3],
+            vec![4, 4],
+            vec![4, 3, 4],
+            vec![3, 3, 3, 3, 3, 3, 3, 3, 3, 4, 3],
+        ] {
+            let n = edges.len() + 1;
+            let cd = CoxeterDiagram::with_edges(edges);
+            assert_eq!(
+                cd.try_element_counts(&vec![true; n]),
+                Err(DiagramError::NotFinite),
+            );
+        }
+
+        let cube = CoxeterDiagram::with_edges(vec![4, 3]);
+        assert_eq!(cube.ringed_nodes(&vector![]), Ok(vec![false; 3]));
+        assert_eq!(cube.ringed_nodes(&vector![f32::NAN]), Ok(vec![false; 3]));
+
+        // Every point in an orbit has the same rings.
+        for (edges, pole, expected) in [
+            (vec![4, 3], vector![1.0], [false, false, true]),
+            (vec![5, 3], vector![1.0, 0.3], [true, true, true]),
+        ] {
+            let cd = CoxeterDiagram::with_edges(edges);
+            for (_, p) in cd.clone().group().orbit(&pole) {
+                assert_eq!(cd.ringed_nodes(&p).unwrap(), expected, "{p}");
+            }
+        }
+
+        let hyperbolic = CoxeterDiagram::with_edges(vec![7, 3]);
+        assert_eq!(
+            hyperbolic.ringed_nodes(&vector![1.0]),
+            Err(DiagramError::NotFinite),
+        );
+    }
+
+    #[test]
+    fn test_hyperbolic_tiling() {
+        let cd = CoxeterDiagram::with_edges(vec![7, 3]);
+        let tiling_with_length = |max_word_length: usize| {
+            let tiling = cd
+                .hyperbolic_tiling(&[true, false, false], max_word_length)
+                .unwrap();
+            for polygon in &tiling.polygons {
+                assert_eq!(polygon.verts.len(), 7);
+                assert_eq!(polygon.facet, Some(2));
+                assert!(polygon.verts.iter().all(|v| v.mag() < 1.0));
+            }
+            let central = &tiling.polygons[0];
+            assert!(vector::centroid(&central.verts).unwrap().mag() < 1e-4);
+            let tiles_around = |v: &Vector<f32>| {
+                tiling
+                    .polygons
+                    .iter()
+                    .filter(|polygon| polygon.verts.iter().any(|u| u.approx_eq_eps(v, 1e-4)))
+                    .count()
+            };
+            let vertex_tiles: Vec<usize> = central.verts.iter().map(tiles_around).collect();
+            let neighbors = tiling.polygons[1..]
+                .iter()
+                .filter(|polygon| {
+                    let shared =
+                        |v: &Vector<f32>| central.verts.iter().any(|u| u.approx_eq_eps(v, 1e-4));
+                    polygon.verts.iter().any(shared)
+                })
+                .count();
+            (tiling, vertex_tiles, neighbors)
+        };
+
+        // The central tile is surrounded by 7 others, with 3 tiles around
+        // each vertex. The tile opposite the fundamental triangle is across
+        // a mirror that takes 7 reflections to reach.
+        let (tiling, vertex_tiles, count) = tiling_with_length(6);
+        for g in &tiling.generators {
+            assert!((g * g).approx_eq(&Matrix::ident(3)));
+        }
+        assert_eq!(count, 6);
+        assert_eq!(vertex_tiles.iter().filter(|&&n| n == 3).count(), 5);
+        let (_, vertex_tiles, count) = tiling_with_length(7);
+        assert_eq!(count, 7);
+        assert_eq!(vertex_tiles, [3; 7]);
+
+        // Longer words reach more tiles.
+        let more = cd.hyperbolic_tiling(&[true, false, false], 8).unwrap();
+        assert!(more.polygons.len() > tiling.polygons.len());
+        let truncated = cd.hyperbolic_tiling(&[true, true, false], 3).unwrap();
+        assert!(truncated.polygons.iter().any(|p| p.verts.len() == 14));
+        assert!(truncated.polygons.iter().any(|p| p.verts.len() == 3));
+
+        assert_eq!(
+            CoxeterDiagram::with_edges(vec![4, 3]).hyperbolic_tiling(&[true, false, false], 6),
+            Err(DiagramError::NotHyperbolic),
+        );
+        assert_eq!(
+            CoxeterDiagram::with_edges(vec![4, 4]).hyperbolic_tiling(&[true, false, false], 6),
+            Err(DiagramError::NotHyperbolic),
+        );
+        assert_eq!(
+            cd.hyperbolic_tiling(&[true, false], 6),
+            Err(DiagramError::WrongRingCount {
+                expected: 3,
+                got: 2
+            }),
+        );
+        assert_eq!(
+            cd.hyperbolic_tiling(&[false; 3], 6),
+            Err(DiagramError::NoRingedNodes),
+        );
+        assert_eq!(
+            CoxeterDiagram::with_edges(vec![5, 3, 3]).hyperbolic_tiling(&[true; 4], 6),
+            Err(DiagramError::UnsupportedRank { ndim: 4 }),
+        );
+    }
+
     fn assert_group_order(edges: Vec<usize>, expected: u32) {
         let group = CoxeterDiagram::with_edges(edges).group();
         assert_eq!(group.order(), expected);
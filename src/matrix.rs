@@ -1,8 +1,8 @@
-use itertools::{Itertools, Permutations};
-use num_traits::{Num, Signed};
+use num_traits::{Num, NumCast, Signed, ToPrimitive};
 use std::ops::*;
 
-use crate::util::{f32_approx_eq, permutation_parity};
+use crate::perm::Permutation;
+use crate::util::{f32_approx_eq, permutation_parity, Tolerance, EPSILON};
 use crate::vector::{Vector, VectorRef};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -51,6 +51,18 @@ impl<N: Clone + Num> Matrix<N> {
         }
     }
 
+    /// Returns the permutation matrix that moves each axis `i` to axis
+    /// `p.image(i)`, so that transforming a vector by it is equivalent to
+    /// [`Permutation::apply_to_vector()`].
+    pub fn from_permutation(p: &Permutation) -> Self {
+        let ndim = p.len() as u8;
+        let mut ret = Self::zero(ndim);
+        for i in 0..ndim {
+            *ret.get_mut(i, p.image(i as usize) as u8) = N::one();
+        }
+        ret
+    }
+
     pub fn from_outer_product(u: impl VectorRef<N>, v: impl VectorRef<N>) -> Self {
         let dim = std::cmp::max(u.ndim(), v.ndim());
         let u = &u;
@@ -65,16 +77,46 @@ impl<N: Clone + Num> Matrix<N> {
     pub fn ndim(&self) -> u8 {
         self.ndim
     }
+    /// Converts the matrix to a different scalar type, returning `None` if any
+    /// element cannot be represented in the new type.
+    pub fn cast<M: Clone + Num + NumCast>(&self) -> Option<Matrix<M>>
+    where
+        N: ToPrimitive,
+    {
+        Some(Matrix {
+            ndim: self.ndim,
+            elems: self
+                .elems
+                .iter()
+                .cloned()
+                .map(M::from)
+                .collect::<Option<_>>()?,
+        })
+    }
+    /// Grows or shrinks the matrix to `ndim` dimensions. New rows and columns
+    /// are padded with the identity matrix.
+    pub fn resize(&mut self, ndim: u8) {
+        if ndim == self.ndim {
+            return;
+        }
+        let elems = (0..ndim)
+            .flat_map(|col| (0..ndim).map(move |row| (col, row)))
+            .map(|(col, row)| self.get(col, row))
+            .collect();
+        *self = Matrix { ndim, elems };
+    }
+    /// Returns the matrix, padded with the identity matrix so that it has at
+    /// least `ndim` dimensions.
+    #[must_use]
+    pub fn at_least_ndim(mut self, ndim: u8) -> Self {
+        if self.ndim < ndim {
+            self.resize(ndim);
+        }
+        self
+    }
 
     pub fn get(&self, col: u8, row: u8) -> N {
-        let ndim = self.ndim();
-        if col < ndim && row < ndim {
-            self.elems[col as usize * ndim as usize + row as usize].clone()
-        } else if col == row {
-            N::one()
-        } else {
-            N::zero()
-        }
+        self.view().get(col, row)
     }
     pub fn get_mut(&mut self, col: u8, row: u8) -> &mut N {
         let ndim = self.ndim();
@@ -105,31 +147,33 @@ impl<N: Clone + Num> Matrix<N> {
     }
 
     pub fn transform(&self, v: impl VectorRef<N>) -> Vector<N> {
-        let ndim = std::cmp::max(self.ndim(), v.ndim());
-        (0..ndim)
-            .map(|i| {
-                (0..ndim)
-                    .map(|j| self.get(j, i) * v.get(j))
-                    .fold(N::zero(), |a, b| a + b)
-            })
-            .collect()
+        self.view().transform(v)
+    }
+    /// Returns a borrowed view of the matrix.
+    pub fn view(&self) -> MatrixRef<'_, N> {
+        MatrixRef {
+            ndim: self.ndim,
+            elems: &self.elems,
+        }
     }
 
     pub fn determinant(&self) -> N
     where
         N: Signed,
     {
-        (0..self.ndim)
-            .permutations(self.ndim as _)
+        // Permutations are in lexicographic order, so the parity of each one
+        // can be computed from its index.
+        Permutation::all(self.ndim as usize)
             .enumerate()
             .map(|(i, p)| {
                 let parity = match permutation_parity(i) {
                     true => -N::one(),
                     false => N::one(),
                 };
-                p.into_iter()
+                p.one_line()
+                    .iter()
                     .enumerate()
-                    .map(|(j, k)| self.get(j as _, k))
+                    .map(|(j, &k)| self.get(j as _, k as _))
                     .fold(N::one(), |x, y| x * y)
                     * parity
             })
@@ -176,6 +220,133 @@ macro_rules! matrix {
     };
 }
 
+/// Borrowed view of a square matrix, such as one returned by
+/// [`Group::matrix()`](crate::Group::matrix). Use [`MatrixRef::to_owned()`]
+/// to convert it to a [`Matrix`].
+#[derive(Debug, Copy, Clone)]
+pub struct MatrixRef<'a, N: Clone + Num = f32> {
+    ndim: u8,
+    /// Elements stored in **column-major** order.
+    elems: &'a [N],
+}
+impl<'a, N: Clone + Num> From<&'a Matrix<N>> for MatrixRef<'a, N> {
+    fn from(m: &'a Matrix<N>) -> Self {
+        m.view()
+    }
+}
+impl<N: Clone + Num> From<MatrixRef<'_, N>> for Matrix<N> {
+    fn from(m: MatrixRef<'_, N>) -> Self {
+        m.to_owned()
+    }
+}
+impl<'a, N: Clone + Num> MatrixRef<'a, N> {
+    /// Constructs a view of `elems`, which are in column-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `elems` does not have `ndim * ndim` elements.
+    pub fn new(ndim: u8, elems: &'a [N]) -> Self {
+        assert_eq!(ndim as usize * ndim as usize, elems.len());
+        Self { ndim, elems }
+    }
+
+    /// Returns the number of rows and columns of the matrix.
+    pub fn ndim(&self) -> u8 {
+        self.ndim
+    }
+    /// Returns an element of the matrix. Like [`Matrix::get()`], elements
+    /// beyond the size of the matrix are those of the identity matrix.
+    pub fn get(&self, col: u8, row: u8) -> N {
+        let ndim = self.ndim;
+        if col < ndim && row < ndim {
+            self.elems[col as usize * ndim as usize + row as usize].clone()
+        } else if col == row {
+            N::one()
+        } else {
+            N::zero()
+        }
+    }
+    /// Returns the elements of the matrix in column-major order.
+    pub fn elems(&self) -> &'a [N] {
+        self.elems
+    }
+    /// Copies the matrix into a new [`Matrix`].
+    pub fn to_owned(&self) -> Matrix<N> {
+        Matrix {
+            ndim: self.ndim,
+            elems: self.elems.to_vec(),
+        }
+    }
+
+    /// Transforms a vector by the matrix. See [`Matrix::transform()`].
+    pub fn transform(&self, v: impl VectorRef<N>) -> Vector<N> {
+        let ndim = std::cmp::max(self.ndim(), v.ndim());
+        (0..ndim)
+            .map(|i| {
+                (0..ndim)
+                    .map(|j| self.get(j, i) * v.get(j))
+                    .fold(N::zero(), |a, b| a + b)
+            })
+            .collect()
+    }
+}
+impl<'a> MatrixRef<'a> {
+    /// See [`Matrix::approx_eq()`].
+    pub fn approx_eq<'b>(&self, other: impl Into<MatrixRef<'b>>) -> bool {
+        self.approx_eq_with(other, Tolerance::APPROX_EQ)
+    }
+    /// See [`Matrix::approx_eq_with()`].
+    pub fn approx_eq_with<'b>(
+        &self,
+        other: impl Into<MatrixRef<'b>>,
+        tolerance: Tolerance,
+    ) -> bool {
+        let other = other.into();
+        let ndim = std::cmp::max(self.ndim(), other.ndim());
+        (0..ndim).all(|x| (0..ndim).all(|y| tolerance.approx_eq(self.get(x, y), other.get(x, y))))
+    }
+    /// See [`Matrix::is_finite()`].
+    pub fn is_finite(&self) -> bool {
+        self.elems.iter().all(|x| x.is_finite())
+    }
+    /// See [`Matrix::is_orthogonal()`].
+    pub fn is_orthogonal(&self) -> bool {
+        self.is_orthogonal_with(Tolerance::APPROX_EQ)
+    }
+    /// See [`Matrix::is_orthogonal_with()`].
+    pub fn is_orthogonal_with(&self, tolerance: Tolerance) -> bool {
+        // The matrix times its transpose has the dot products of the rows.
+        let ndim = self.ndim;
+        (0..ndim).all(|i| {
+            (0..ndim).all(|j| {
+                let dot = (0..ndim).map(|k| self.get(k, i) * self.get(k, j)).sum();
+                tolerance.approx_eq(dot, if i == j { 1.0 } else { 0.0 })
+            })
+        })
+    }
+}
+impl<N: Clone + Num + PartialEq> PartialEq<Matrix<N>> for MatrixRef<'_, N> {
+    fn eq(&self, other: &Matrix<N>) -> bool {
+        self.ndim == other.ndim && self.elems == other.elems.as_slice()
+    }
+}
+impl<N: Clone + Num + std::fmt::Debug> Mul<&Matrix<N>> for MatrixRef<'_, N> {
+    type Output = Matrix<N>;
+
+    fn mul(self, rhs: &Matrix<N>) -> Self::Output {
+        let new_ndim = std::cmp::max(self.ndim(), rhs.ndim());
+        let mut new_matrix = Matrix::zero(new_ndim);
+        for x in 0..new_ndim {
+            for y in 0..new_ndim {
+                *new_matrix.get_mut(x, y) = (0..new_ndim)
+                    .map(|i| self.get(i, y) * rhs.get(x, i))
+                    .fold(N::zero(), |a, b| a + b);
+            }
+        }
+        new_matrix
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct MatrixCol<'a, N: Clone + Num> {
     matrix: &'a Matrix<N>,
@@ -209,7 +380,7 @@ impl<N: Clone + Num> VectorRef<N> for MatrixRow<'_, N> {
 impl_vector_ops!(impl<N> for MatrixCol<'_, N>);
 impl_vector_ops!(impl<N> for MatrixRow<'_, N>);
 
-impl<'a, N: Clone + Num + std::fmt::Debug> Mul for &'a Matrix<N> {
+impl<N: Clone + Num + std::fmt::Debug> Mul for &Matrix<N> {
     type Output = Matrix<N>;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -230,7 +401,7 @@ impl<'a, N: Clone + Num + std::fmt::Debug> Mul for &'a Matrix<N> {
         new_matrix
     }
 }
-impl<'a, N: Clone + Num + std::fmt::Debug> Add for &'a Matrix<N> {
+impl<N: Clone + Num + std::fmt::Debug> Add for &Matrix<N> {
     type Output = Matrix<N>;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -242,7 +413,7 @@ impl<'a, N: Clone + Num + std::fmt::Debug> Add for &'a Matrix<N> {
         )
     }
 }
-impl<'a, N: Clone + Num + std::fmt::Debug> Sub for &'a Matrix<N> {
+impl<N: Clone + Num + std::fmt::Debug> Sub for &Matrix<N> {
     type Output = Matrix<N>;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -255,9 +426,148 @@ impl<'a, N: Clone + Num + std::fmt::Debug> Sub for &'a Matrix<N> {
     }
 }
 impl Matrix<f32> {
-    pub fn approx_eq(&self, other: &Self) -> bool {
-        let ndim = std::cmp::max(self.ndim(), other.ndim());
-        (0..ndim).all(|x| (0..ndim).all(|y| f32_approx_eq(self.get(x, y), other.get(x, y))))
+    /// Returns whether the two matrices are approximately equal, using
+    /// [`Tolerance::APPROX_EQ`]. See [`Matrix::approx_eq_with()`].
+    pub fn approx_eq<'a>(&self, other: impl Into<MatrixRef<'a>>) -> bool {
+        self.view().approx_eq(other)
+    }
+    /// Returns whether every element of the matrix is equal to the
+    /// corresponding element of `other` within `tolerance`, relative to the
+    /// magnitude of those elements.
+    ///
+    /// Use [`Tolerance::default()`] for a purely absolute comparison.
+    pub fn approx_eq_with<'a>(
+        &self,
+        other: impl Into<MatrixRef<'a>>,
+        tolerance: Tolerance,
+    ) -> bool {
+        self.view().approx_eq_with(other, tolerance)
+    }
+
+    /// Returns whether every element of the matrix is finite (not NaN or
+    /// infinite).
+    pub fn is_finite(&self) -> bool {
+        self.view().is_finite()
+    }
+
+    /// Returns whether the matrix is orthogonal (within `EPSILON`), meaning
+    /// that its columns are orthonormal.
+    pub fn is_orthogonal(&self) -> bool {
+        self.is_orthogonal_with(Tolerance::APPROX_EQ)
+    }
+    /// Returns whether the matrix times its transpose is equal to the
+    /// identity within `tolerance`. See [`Matrix::approx_eq_with()`].
+    pub fn is_orthogonal_with(&self, tolerance: Tolerance) -> bool {
+        self.view().is_orthogonal_with(tolerance)
+    }
+    /// Returns whether the matrix is a nonzero multiple of an orthogonal
+    /// matrix (within `EPSILON`), meaning that its columns are orthogonal and
+    /// all have the same magnitude.
+    pub fn is_orthogonal_up_to_scale(&self) -> bool {
+        if self.ndim() == 0 {
+            return true;
+        }
+        let scale = (self.cols().map(|col| col.mag2()).sum::<f32>() / self.ndim() as f32).sqrt();
+        scale > 0.0 && self.clone().scale(scale.recip()).is_orthogonal()
+    }
+    /// Returns whether the matrix is invertible (within `EPSILON`), relative
+    /// to its scale.
+    ///
+    /// The determinant is compared to the product of the magnitudes of the
+    /// columns, which is the largest that it can be for columns of those
+    /// magnitudes. Multiplying the matrix by a nonzero scalar doesn't change
+    /// the result.
+    pub fn is_invertible(&self) -> bool {
+        let max_determinant: f32 = self.cols().map(|col| col.mag()).product();
+        self.is_finite() && self.determinant().abs() > EPSILON * max_determinant
+    }
+    /// Returns the nearest orthogonal matrix to this one, or `None` if the
+    /// matrix is too far from orthogonal.
+    ///
+    /// This is the orthogonal factor of the polar decomposition, computed by
+    /// Newton-Schulz iteration, which only converges for matrices that are
+    /// already nearly orthogonal. It is useful for removing floating-point
+    /// drift from a product of many orthogonal matrices: unlike
+    /// Gram-Schmidt, it doesn't depend on the order of the columns, so
+    /// nearby matrices are corrected to nearby matrices.
+    pub fn orthonormalized(&self) -> Option<Matrix<f32>> {
+        let ident = Matrix::ident(self.ndim);
+        let mut ret = self.clone();
+        for _ in 0..8 {
+            // X <- X (3I - X^T X) / 2
+            let error = &(&ret.transpose() * &ret) - &ident;
+            if error.elems.iter().all(|x| x.abs() <= f32::EPSILON) {
+                break;
+            }
+            ret = &ret * &(&ident - &error.scale(0.5));
+        }
+        ret.is_orthogonal_with(Tolerance::absolute(EPSILON * EPSILON))
+            .then_some(ret)
+    }
+    /// Returns whether the matrix is orthogonal with determinant 1 (within
+    /// `EPSILON`), meaning that it is a rotation.
+    pub fn is_special_orthogonal(&self) -> bool {
+        self.is_orthogonal() && f32_approx_eq(self.determinant(), 1.0)
+    }
+
+    /// Returns the Gram matrix of a set of vectors, whose elements are the
+    /// pairwise dot products of the vectors.
+    pub fn gram(vectors: &[impl VectorRef<f32>]) -> Matrix<f32> {
+        vectors
+            .iter()
+            .flat_map(|a| vectors.iter().map(move |b| a.dot(b)))
+            .collect()
+    }
+
+    /// Extends a set of linearly independent vectors to an orthonormal basis
+    /// of `ndim`-dimensional space, returned as the columns of an orthogonal
+    /// matrix. The first `partial.len()` columns span the same subspace as
+    /// `partial` (via Gram-Schmidt) and the rest are filled in from the unit
+    /// vectors.
+    ///
+    /// Returns `None` if `partial` is (nearly) linearly dependent or has more
+    /// than `ndim` vectors.
+    pub fn complete_basis(partial: &[Vector<f32>], ndim: u8) -> Option<Matrix<f32>> {
+        if partial.len() > ndim as usize {
+            return None;
+        }
+
+        let reject = |basis: &[Vector<f32>], v: &Vector<f32>| {
+            basis.iter().fold(v.pad(ndim), |v, b| &v - &(b * v.dot(b)))
+        };
+
+        let mut basis: Vec<Vector<f32>> = Vec::with_capacity(ndim as _);
+        for v in partial {
+            debug_assert!(v.ndim() <= ndim, "vector has more than {ndim} dimensions");
+            basis.push(reject(&basis, v).normalized()?);
+        }
+        while basis.len() < ndim as usize {
+            // Pick whichever unit vector has the largest component outside the
+            // current basis, for numerical stability.
+            let v = (0..ndim)
+                .map(|axis| reject(&basis, &Vector::unit(axis)))
+                .max_by(|a, b| a.mag2().total_cmp(&b.mag2()))?;
+            basis.push(v.normalized()?);
+        }
+
+        Some(Matrix::from_cols(basis))
+    }
+
+    /// Projects `v` along `from_axis` onto the first `from_axis` axes using a
+    /// perspective projection, with the camera positioned at `-camera_distance`
+    /// along `from_axis`. Any axes after `from_axis` are ignored.
+    ///
+    /// Returns `None` if the point is at or behind the camera.
+    pub fn perspective_project(
+        v: impl VectorRef<f32>,
+        from_axis: u8,
+        camera_distance: f32,
+    ) -> Option<Vector<f32>> {
+        let w = v.get(from_axis) + camera_distance;
+        if w < EPSILON {
+            return None;
+        }
+        Some((0..from_axis).map(|i| v.get(i) / w).collect())
     }
 }
 
@@ -275,6 +585,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_matrix_op_properties() {
+        let mut rng = crate::testutil::Rng::new(1);
+        for ndim in 1..=5 {
+            for _ in 0..10 {
+                let a = rng.rotation(ndim);
+                let b = rng.rotation(ndim);
+                let c = rng.rotation(ndim);
+                let u = rng.vector(ndim, -2.0, 2.0);
+                let v = rng.vector(ndim, -2.0, 2.0);
+
+                assert!((&(&a * &b) * &c).approx_eq(&(&a * &(&b * &c))));
+                assert!((&a * &b).is_special_orthogonal());
+                assert!(a.inverse().approx_eq(&a.transpose()));
+                assert!((&a * &a.inverse()).approx_eq(&Matrix::ident(ndim)));
+                assert!(a.transpose().transpose().approx_eq(&a));
+                assert!(f32_approx_eq(
+                    a.transform(&u).dot(a.transform(&v)),
+                    u.dot(&v)
+                ));
+                assert!(a
+                    .transform(b.transform(&u))
+                    .approx_eq((&a * &b).transform(&u)));
+            }
+        }
+    }
+
     #[test]
     fn test_determinant() {
         // let m = matrix![[-2, -1, 2], [2, 1, 4], [-3, 3, -1]];
@@ -297,4 +634,112 @@ mod tests {
         let m = matrix![[1, 2, 3], [4, 5, 6], [7, 8, 9]].transpose();
         assert_eq!(m, matrix![[1, 4, 7], [2, 5, 8], [3, 6, 9]])
     }
+
+    #[test]
+    fn test_resize() {
+        let (s, c) = (0.6, 0.8);
+        let mut m = matrix![[c, s], [-s, c]];
+        m.resize(5);
+        assert_eq!(m.ndim(), 5);
+        let v = m.transform(vector![1.0, 0.0, 3.0, -4.0, 5.0]);
+        assert!(v.approx_eq(vector![c, s, 3.0, -4.0, 5.0]));
+
+        m.resize(1);
+        assert_eq!(m, matrix![[c]]);
+
+        let m = matrix![[1, 2], [3, 4]].at_least_ndim(3);
+        assert_eq!(m, matrix![[1, 2, 0], [3, 4, 0], [0, 0, 1]]);
+        assert_eq!(m.clone().at_least_ndim(2), m);
+    }
+
+    #[test]
+    fn test_orthonormalized() {
+        let (s, c) = 0.5_f32.sin_cos();
+        let rotation = Matrix::from_cols([
+            vector![c, s, 0.0],
+            vector![-s, c, 0.0],
+            vector![0.0, 0.0, 1.0],
+        ]);
+        let mut drifted = rotation.clone();
+        *drifted.get_mut(0, 0) += 0.01;
+        *drifted.get_mut(1, 2) -= 0.01;
+        assert!(!drifted.is_orthogonal_with(Tolerance::absolute(1e-3)));
+        let fixed = drifted.orthonormalized().unwrap();
+        assert!(fixed.is_orthogonal_with(Tolerance::absolute(1e-6)));
+        assert!(fixed.approx_eq_with(&rotation, Tolerance::absolute(0.03)));
+        assert!(Matrix::<f32>::zero(2).orthonormalized().is_none());
+    }
+
+    #[test]
+    fn test_invertible() {
+        let small = Matrix::ident(3).scale(0.05);
+        assert!(small.determinant().abs() < EPSILON);
+        assert!(small.is_invertible());
+        assert!(small.is_orthogonal_up_to_scale());
+
+        let (s, c) = 0.5_f32.sin_cos();
+        let rotation = Matrix::from_cols([vector![c, s], vector![-s, c]]);
+        assert!(rotation.clone().scale(1000.0).is_orthogonal_up_to_scale());
+        let stretched = Matrix::from_cols([vector![2.0], vector![0.0, 1.0]]);
+        assert!(stretched.is_invertible());
+        assert!(!stretched.is_orthogonal_up_to_scale());
+
+        let singular = Matrix::from_cols([vector![1.0, 2.0], vector![2.0, 4.0001]]);
+        assert!(!singular.is_invertible());
+        assert!(!singular.clone().scale(1000.0).is_invertible());
+        assert!(!Matrix::<f32>::zero(2).is_invertible());
+        assert!(!Matrix::<f32>::zero(2).is_orthogonal_up_to_scale());
+        assert!(!Matrix::ident(2).scale(f32::NAN).is_invertible());
+    }
+
+    #[test]
+    fn test_complete_basis() {
+        let partial = [vector![0.6, 0.0, 0.8], vector![0.0, 0.0, 0.0, 1.0]];
+        let m = Matrix::complete_basis(&partial, 4).unwrap();
+        assert_eq!(m.ndim(), 4);
+        assert!((&m * &m.transpose()).approx_eq(&Matrix::ident(4)));
+        assert!(partial[0].approx_eq(m.col(0)));
+        assert!(partial[1].approx_eq(m.col(1)));
+
+        // Non-orthonormal input spans the same subspace.
+        let partial = [vector![2.0, 0.0, 0.0, 2.0], vector![1.0, 1.0]];
+        let m = Matrix::complete_basis(&partial, 4).unwrap();
+        assert!((&m * &m.transpose()).approx_eq(&Matrix::ident(4)));
+        for v in &partial {
+            let in_span = m.col(0) * m.col(0).dot(v) + m.col(1) * m.col(1).dot(v);
+            assert!(in_span.approx_eq(v));
+        }
+
+        // Dependent and nearly-dependent input is rejected.
+        let partial = [vector![1.0, 2.0, 0.0, 1.0], vector![2.0, 4.0, 0.0, 2.0]];
+        assert_eq!(Matrix::complete_basis(&partial, 4), None);
+        let partial = [vector![1.0, 2.0, 0.0, 1.0], vector![1.0, 2.0, 0.0, 1.00001]];
+        assert_eq!(Matrix::complete_basis(&partial, 4), None);
+    }
+
+    #[test]
+    fn test_perspective_project() {
+        let v = vector![1.0, 2.0, -3.0, 1.0];
+        let projected = Matrix::perspective_project(&v, 3, 4.0).unwrap();
+        assert!(projected.approx_eq(vector![0.2, 0.4, -0.6]));
+        assert_eq!(projected.ndim(), 3);
+
+        // Points at or behind the camera don't produce inf or NaN.
+        let v = vector![1.0, 2.0, -3.0, -4.0];
+        assert_eq!(Matrix::perspective_project(&v, 3, 4.0), None);
+        let v = vector![1.0, 2.0, -3.0, -5.0];
+        assert_eq!(Matrix::perspective_project(&v, 3, 4.0), None);
+
+        // Missing axes are treated as zero.
+        let projected = Matrix::perspective_project(vector![2.0], 3, 4.0).unwrap();
+        assert!(projected.approx_eq(vector![0.5, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_cast() {
+        let m = matrix![[1, 2], [-3, 4]];
+        assert_eq!(m.cast::<f32>(), Some(matrix![[1.0, 2.0], [-3.0, 4.0]]));
+        assert_eq!(m.cast::<f32>().unwrap().cast::<i32>(), Some(m.clone()));
+        assert_eq!(m.cast::<u32>(), None);
+    }
 }
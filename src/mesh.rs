@@ -0,0 +1,1004 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{fmt, io};
+
+use crate::group::Group;
+use crate::matrix::MatrixRef;
+use crate::polytope::{triangulate_loop, Polygon};
+use crate::util::EPSILON;
+use crate::vector::{centroid, weld_points, Vector, VectorRef};
+
+/// Polygon mesh with a shared vertex buffer.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mesh {
+    /// Vertex positions.
+    pub verts: Vec<Vector<f32>>,
+    /// Polygons, each of which is a list of indices into `verts` in order
+    /// around the boundary of the polygon.
+    pub polygons: Vec<Vec<u32>>,
+    /// Facet index of each polygon. See [`Polygon::facet`].
+    pub facets: Vec<Option<usize>>,
+}
+impl Mesh {
+    /// Returns the polygons of the mesh, with each vertex position copied into
+    /// every polygon that uses it.
+    pub fn to_polygons(&self) -> Vec<Polygon> {
+        self.polygons
+            .iter()
+            .zip(&self.facets)
+            .map(|(polygon, &facet)| Polygon {
+                verts: polygon
+                    .iter()
+                    .map(|&i| self.verts[i as usize].clone())
+                    .collect(),
+                facet,
+            })
+            .collect()
+    }
+
+    /// Returns a copy of the mesh with every vertex coordinate beyond the
+    /// first `ndim` dropped.
+    pub fn truncated(&self, ndim: u8) -> Mesh {
+        Mesh {
+            verts: self
+                .verts
+                .iter()
+                .map(|v| v.iter().take(ndim as usize).collect())
+                .collect(),
+            polygons: self.polygons.clone(),
+            facets: self.facets.clone(),
+        }
+    }
+
+    /// Reverses each polygon whose normal points toward the centroid of the
+    /// vertices of the mesh, so that every polygon winds counterclockwise
+    /// when viewed from outside. This is only correct for shapes that are
+    /// star-shaped around their centroid, such as convex polytopes. Meshes
+    /// with vertices beyond 3D are unchanged.
+    pub fn orient_outward(&mut self) {
+        if self.verts.iter().any(|v| v.ndim() > 3) {
+            return;
+        }
+        let Some(center) = centroid(&self.verts) else {
+            return;
+        };
+        let polygons = self.to_polygons();
+        for (indices, polygon) in self.polygons.iter_mut().zip(polygons) {
+            let (Some(normal), Some(polygon_center)) = (polygon.normal(), centroid(&polygon.verts))
+            else {
+                continue;
+            };
+            if normal.dot(polygon_center - &center) < 0.0 {
+                indices.reverse();
+            }
+        }
+    }
+
+    /// Writes the mesh in Wavefront OBJ format, with each polygon oriented
+    /// outward. See [`Mesh::orient_outward()`].
+    ///
+    /// OBJ files are Y-up, so the axes are cycled so that `up_axis` becomes
+    /// the Y axis. Returns an error if any vertex has a nonzero coordinate
+    /// beyond the third axis; use [`Mesh::truncated()`] or project the mesh to
+    /// 3D first.
+    pub fn write_obj(&self, w: &mut impl io::Write, up_axis: u8) -> io::Result<()> {
+        if up_axis >= 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("up axis must be 0, 1, or 2; got {up_axis}"),
+            ));
+        }
+        self.check_3d("OBJ")?;
+        let mut mesh = self.truncated(3);
+        mesh.orient_outward();
+
+        let axes = [(up_axis + 2) % 3, up_axis, (up_axis + 1) % 3];
+        for v in &mesh.verts {
+            let [x, y, z] = axes.map(|axis| {
+                // Round to avoid writing `-0.000000`.
+                (v.get(axis) * 1e6).round() / 1e6 + 0.0
+            });
+            writeln!(w, "v {x:.6} {y:.6} {z:.6}")?;
+        }
+        for polygon in &mesh.polygons {
+            write!(w, "f")?;
+            for &i in polygon {
+                write!(w, " {}", i + 1)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the mesh in binary STL format, triangulating each polygon and
+    /// orienting it outward. See [`Mesh::orient_outward()`].
+    ///
+    /// Returns an error if any vertex has a nonzero coordinate beyond the third
+    /// axis; use [`Mesh::truncated()`] or project the mesh to 3D first.
+    pub fn write_stl_binary(&self, w: &mut impl io::Write) -> io::Result<()> {
+        self.check_3d("STL")?;
+        let mut mesh = self.truncated(3);
+        mesh.orient_outward();
+
+        let (verts, triangles) = mesh.triangulate();
+        let triangle_count = u32::try_from(triangles.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "too many triangles for STL")
+        })?;
+
+        let mut header = [0_u8; 80];
+        let title = b"binary STL";
+        header[..title.len()].copy_from_slice(title);
+        w.write_all(&header)?;
+        w.write_all(&triangle_count.to_le_bytes())?;
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|i| &verts[i as usize]);
+            let normal = (b - a)
+                .cross(c - a)
+                .normalized()
+                .unwrap_or_else(|| Vector::zero(3));
+            for v in [&normal, a, b, c] {
+                for axis in 0..3 {
+                    w.write_all(&v.get(axis).to_le_bytes())?;
+                }
+            }
+            w.write_all(&0_u16.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Returns an error if any vertex has a nonzero coordinate beyond the third
+    /// axis.
+    fn check_3d(&self, format: &str) -> io::Result<()> {
+        match self
+            .verts
+            .iter()
+            .find(|v| v.iter().skip(3).any(|x| x.abs() > EPSILON))
+        {
+            Some(v) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot export {}D vertex {v} to {format}; project the mesh to 3D first",
+                    v.ndim(),
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the mesh in OFF format. Meshes with vertices beyond 3D are
+    /// written in the nOFF variant so that no coordinates are lost.
+    pub fn write_off(&self, w: &mut impl io::Write) -> io::Result<()> {
+        let ndim = self
+            .verts
+            .iter()
+            .map(|v| v.ndim())
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        let edge_count = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| {
+                let next = polygon.iter().cycle().skip(1);
+                polygon
+                    .iter()
+                    .zip(next)
+                    .map(|(&a, &b)| (a.min(b), a.max(b)))
+            })
+            .collect::<HashSet<_>>()
+            .len();
+
+        if ndim == 3 {
+            writeln!(w, "OFF")?;
+        } else {
+            writeln!(w, "nOFF")?;
+            writeln!(w, "{ndim}")?;
+        }
+        writeln!(
+            w,
+            "{} {} {}",
+            self.verts.len(),
+            self.polygons.len(),
+            edge_count
+        )?;
+        for v in &self.verts {
+            let coords = (0..ndim).map(|axis| v.get(axis).to_string());
+            writeln!(w, "{}", coords.collect::<Vec<_>>().join(" "))?;
+        }
+        for polygon in &self.polygons {
+            write!(w, "{}", polygon.len())?;
+            for &i in polygon {
+                write!(w, " {i}")?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+    /// Reads a mesh in OFF or nOFF format. Colors and other trailing data on
+    /// each face are ignored.
+    pub fn read_off(r: impl io::BufRead) -> Result<Mesh, OffParseError> {
+        let mut lines = vec![];
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let line = line.split('#').next().unwrap_or("").trim().to_owned();
+            if !line.is_empty() {
+                lines.push((i + 1, line));
+            }
+        }
+        let mut lines = lines.into_iter();
+        let mut next_line = || lines.next().ok_or(OffParseError::UnexpectedEof);
+
+        let (_, header) = next_line()?;
+        let ndim = match header.as_str() {
+            "OFF" => 3,
+            "nOFF" => {
+                let (line, s) = next_line()?;
+                parse_token(line, &s)?
+            }
+            _ => return Err(OffParseError::BadHeader(header)),
+        };
+
+        let (line, counts) = next_line()?;
+        let counts = counts
+            .split_whitespace()
+            .map(|token| parse_token::<usize>(line, token))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (vert_count, face_count) = match counts[..] {
+            [v, f, ..] => (v, f),
+            _ => return Err(OffParseError::UnexpectedEof),
+        };
+
+        let mut mesh = Mesh::default();
+        for _ in 0..vert_count {
+            let (line, s) = next_line()?;
+            let coords = s
+                .split_whitespace()
+                .take(ndim as usize)
+                .map(|token| parse_token::<f32>(line, token))
+                .collect::<Result<Vec<_>, _>>()?;
+            if coords.len() < ndim as usize {
+                return Err(OffParseError::UnexpectedEof);
+            }
+            mesh.verts.push(Vector(coords));
+        }
+        for face in 0..face_count {
+            let (line, s) = next_line()?;
+            let mut tokens = s.split_whitespace();
+            let len: usize = parse_token(line, tokens.next().unwrap_or(""))?;
+            if len < 3 {
+                return Err(OffParseError::FaceTooSmall { face, len });
+            }
+            let polygon = tokens
+                .take(len)
+                .map(|token| {
+                    let index = parse_token::<u32>(line, token)?;
+                    if index as usize >= vert_count {
+                        return Err(OffParseError::IndexOutOfRange { face, index });
+                    }
+                    Ok(index)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if polygon.len() < len {
+                return Err(OffParseError::UnexpectedEof);
+            }
+            mesh.polygons.push(polygon);
+            mesh.facets.push(None);
+        }
+        Ok(mesh)
+    }
+
+    /// Removes every polygon with area less than `min_area`, and then every
+    /// vertex that is no longer used by any polygon. Returns the number of
+    /// polygons removed.
+    pub fn remove_small_polygons(&mut self, min_area: f32) -> usize {
+        let keep = self.polygons_with_min_area(min_area);
+        self.retain_polygons(&keep)
+    }
+    /// Returns whether each polygon has area at least `min_area`.
+    pub(crate) fn polygons_with_min_area(&self, min_area: f32) -> Vec<bool> {
+        self.to_polygons()
+            .iter()
+            .map(|polygon| polygon.area() >= min_area)
+            .collect()
+    }
+    /// Removes each polygon whose corresponding element of `keep` is `false`,
+    /// and then every vertex that is no longer used by any polygon. Returns
+    /// the number of polygons removed.
+    pub(crate) fn retain_polygons(&mut self, keep: &[bool]) -> usize {
+        let old_len = self.polygons.len();
+        retain_by_mask(&mut self.polygons, keep);
+        retain_by_mask(&mut self.facets, keep);
+        self.remove_unused_verts();
+        old_len - self.polygons.len()
+    }
+    /// Merges vertices within `eps` of each other, such as after reading a
+    /// mesh in which each polygon has its own copy of its vertices. Repeated
+    /// vertices in a polygon are collapsed, polygons left with fewer than 3
+    /// vertices are removed, and then unused vertices are removed. Returns
+    /// the number of polygons removed.
+    ///
+    /// Each vertex is merged into another vertex within `eps` of it, so the
+    /// positions of the remaining vertices are unchanged. Vertices are sorted
+    /// along the first axis so that this takes `O(n log n)` time for most
+    /// meshes.
+    pub fn simplify(&mut self, eps: f32) -> usize {
+        let canonical = weld_points(&self.verts, eps);
+        for polygon in &mut self.polygons {
+            for i in polygon.iter_mut() {
+                *i = canonical[*i as usize] as u32;
+            }
+            polygon.dedup();
+            while polygon.len() > 1 && polygon.first() == polygon.last() {
+                polygon.pop();
+            }
+        }
+
+        let old_len = self.polygons.len();
+        let keep: Vec<bool> = self.polygons.iter().map(|p| p.len() >= 3).collect();
+        retain_by_mask(&mut self.polygons, &keep);
+        retain_by_mask(&mut self.facets, &keep);
+        self.remove_unused_verts();
+        old_len - self.polygons.len()
+    }
+    /// Removes every vertex that is not used by any polygon, preserving the
+    /// order of the remaining vertices.
+    // Every vertex still referenced by a polygon was given a new index.
+    #[allow(clippy::expect_used)]
+    pub fn remove_unused_verts(&mut self) {
+        let mut new_indices = vec![None; self.verts.len()];
+        for &i in self.polygons.iter().flatten() {
+            new_indices[i as usize] = Some(0);
+        }
+        let mut next = 0;
+        for index in &mut new_indices {
+            if index.is_some() {
+                *index = Some(next);
+                next += 1;
+            }
+        }
+        let keep: Vec<bool> = new_indices.iter().map(Option::is_some).collect();
+        retain_by_mask(&mut self.verts, &keep);
+        for i in self.polygons.iter_mut().flatten() {
+            *i = new_indices[*i as usize].expect("vertex is used");
+        }
+    }
+
+    /// Returns the indices of the polygons belonging to each facet, sorted by
+    /// facet index. Polygons without a facet are omitted.
+    ///
+    /// Facet indices are assigned deterministically (see [`facet_orbit()`]),
+    /// so regenerating the same shape gives the same groups.
+    ///
+    /// [`facet_orbit()`]: crate::facet_orbit
+    pub fn polygons_by_facet(&self) -> Vec<(usize, Vec<u32>)> {
+        let mut groups: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+        for (i, facet) in self.facets.iter().enumerate() {
+            if let Some(facet) = facet {
+                groups.entry(*facet).or_default().push(i as u32);
+            }
+        }
+        groups.into_iter().collect()
+    }
+
+    /// Splits each polygon into triangles, returning the vertex buffer and a
+    /// list of triangles. See [`Polygon::triangulate()`].
+    pub fn triangulate(&self) -> (Vec<Vector<f32>>, Vec<[u32; 3]>) {
+        let triangles = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| {
+                let verts: Vec<Vector<f32>> = polygon
+                    .iter()
+                    .map(|&i| self.verts[i as usize].clone())
+                    .collect();
+                triangulate_loop(&verts)
+                    .into_iter()
+                    .map(|tri| tri.map(|i| polygon[i]))
+            })
+            .collect();
+        (self.verts.clone(), triangles)
+    }
+
+    /// Checks that every generator of `group` maps the mesh onto itself, with
+    /// vertices within `eps` of each other considered equal, and that
+    /// polygons of the same facet are mapped onto polygons of the same facet.
+    /// Returns the first defect found.
+    ///
+    /// Facets are only checked for meshes of at most 3 dimensions. In higher
+    /// dimensions, each polygon is shared by multiple facets but only tagged
+    /// with one of them, so the tags aren't preserved by symmetries.
+    ///
+    /// This is slow and intended for debugging.
+    pub fn check_symmetry(&self, group: &Group, eps: f32) -> Result<(), SymmetryDefect> {
+        self.check_symmetry_under(group.generators().map(|g| group.matrix(g)), eps)
+    }
+    /// Checks that each matrix maps the mesh onto itself. See
+    /// [`Mesh::check_symmetry()`].
+    pub(crate) fn check_symmetry_under<'a>(
+        &self,
+        generators: impl IntoIterator<Item = MatrixRef<'a>>,
+        eps: f32,
+    ) -> Result<(), SymmetryDefect> {
+        let polygon_key = |polygon: &[u32]| {
+            let mut key = polygon.to_vec();
+            key.sort_unstable();
+            key
+        };
+        let polygons_by_verts: HashMap<Vec<u32>, u32> = self
+            .polygons
+            .iter()
+            .enumerate()
+            .map(|(i, polygon)| (polygon_key(polygon), i as u32))
+            .collect();
+
+        let check_facets = self
+            .verts
+            .iter()
+            .all(|v| v.iter().skip(3).all(|x| x.abs() <= eps));
+
+        for (generator, m) in generators.into_iter().enumerate() {
+            let mut vertex_map = Vec::with_capacity(self.verts.len());
+            for (vertex, v) in self.verts.iter().enumerate() {
+                let image = m.transform(v);
+                match self.verts.iter().position(|w| w.approx_eq_eps(&image, eps)) {
+                    Some(i) => vertex_map.push(i as u32),
+                    None => {
+                        return Err(SymmetryDefect::Vertex {
+                            generator,
+                            vertex: vertex as u32,
+                            image,
+                        })
+                    }
+                }
+            }
+
+            let mut facet_map: HashMap<Option<usize>, Option<usize>> = HashMap::new();
+            for (polygon, verts) in self.polygons.iter().enumerate() {
+                let polygon = polygon as u32;
+                let image_verts: Vec<u32> = verts.iter().map(|&v| vertex_map[v as usize]).collect();
+                let image = *polygons_by_verts
+                    .get(&polygon_key(&image_verts))
+                    .ok_or(SymmetryDefect::Polygon { generator, polygon })?;
+                let facet = self.facets[polygon as usize];
+                let image_facet = self.facets[image as usize];
+                if check_facets && *facet_map.entry(facet).or_insert(image_facet) != image_facet {
+                    return Err(SymmetryDefect::Facet { generator, polygon });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the first point where a ray hits the mesh, or `None` if it
+    /// misses. The ray consists of the points `origin + dir * t` for `t >= 0`.
+    ///
+    /// Coordinates beyond the third axis are ignored, so 4D meshes should be
+    /// projected to 3D first. If the ray hits multiple polygons at the same
+    /// point, such as on a shared edge, the polygon with the smallest index is
+    /// returned.
+    pub fn raycast(&self, origin: &Vector<f32>, dir: &Vector<f32>) -> Option<RayHit> {
+        let to_3d = |v: &Vector<f32>| -> Vector<f32> { (0..3).map(|i| v.get(i)).collect() };
+        let origin = to_3d(origin);
+        let dir = to_3d(dir);
+
+        let mut best: Option<RayHit> = None;
+        for (i, polygon) in self.polygons.iter().enumerate() {
+            let verts: Vec<Vector<f32>> = polygon
+                .iter()
+                .map(|&v| to_3d(&self.verts[v as usize]))
+                .collect();
+            for [a, b, c] in triangulate_loop(&verts) {
+                let t = match ray_triangle_intersection(
+                    &origin,
+                    &dir,
+                    [&verts[a], &verts[b], &verts[c]],
+                ) {
+                    Some(t) => t,
+                    None => continue,
+                };
+                // Only replace the best hit if this one is significantly
+                // closer, so that ties go to the earliest polygon.
+                if best
+                    .as_ref()
+                    .is_none_or(|hit| t < hit.t - EPSILON * EPSILON)
+                {
+                    best = Some(RayHit {
+                        polygon: i as u32,
+                        facet: self.facets.get(i).copied().flatten(),
+                        t,
+                        point: &origin + &dir * t,
+                    });
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Point where a ray hits a [`Mesh`]. See [`Mesh::raycast()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RayHit {
+    /// Index of the polygon that was hit.
+    pub polygon: u32,
+    /// Facet of the polygon that was hit. See [`Polygon::facet`].
+    pub facet: Option<usize>,
+    /// Parameter along the ray, in units of the ray's direction vector.
+    pub t: f32,
+    /// Point that was hit.
+    pub point: Vector<f32>,
+}
+
+/// Returns the parameter `t` at which the ray `origin + dir * t` intersects a
+/// triangle, or `None` if it misses or `t` is negative. Triangles are
+/// two-sided.
+fn ray_triangle_intersection(
+    origin: &Vector<f32>,
+    dir: &Vector<f32>,
+    [a, b, c]: [&Vector<f32>; 3],
+) -> Option<f32> {
+    // Möller–Trumbore algorithm
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = dir.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < EPSILON * EPSILON * EPSILON {
+        return None; // The ray is parallel to the triangle.
+    }
+    let inv_det = 1.0 / det;
+    let offset = origin - a;
+    let u = offset.dot(&p) * inv_det;
+    let q = offset.cross(&edge1);
+    let v = dir.dot(&q) * inv_det;
+    // Allow a little slack so that rays through shared edges aren't missed.
+    let slack = EPSILON * EPSILON;
+    if u < -slack || v < -slack || u + v > 1.0 + slack {
+        return None;
+    }
+    let t = edge2.dot(&q) * inv_det;
+    (t >= 0.0).then_some(t)
+}
+
+/// Way in which a [`Mesh`] is not symmetric. See [`Mesh::check_symmetry()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymmetryDefect {
+    /// A generator maps a vertex to a point that is not a vertex.
+    Vertex {
+        /// Index of the generator.
+        generator: usize,
+        /// Index of the vertex.
+        vertex: u32,
+        /// Image of the vertex under the generator.
+        image: Vector<f32>,
+    },
+    /// A generator maps a polygon to something that is not a polygon.
+    Polygon {
+        /// Index of the generator.
+        generator: usize,
+        /// Index of the polygon.
+        polygon: u32,
+    },
+    /// A generator maps polygons of the same facet to polygons of different
+    /// facets.
+    Facet {
+        /// Index of the generator.
+        generator: usize,
+        /// Index of the first polygon whose image has a different facet than
+        /// the images of earlier polygons of the same facet.
+        polygon: u32,
+    },
+}
+impl fmt::Display for SymmetryDefect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymmetryDefect::Vertex {
+                generator,
+                vertex,
+                image,
+            } => write!(
+                f,
+                "generator {generator} maps vertex {vertex} to {image}, which is not a vertex",
+            ),
+            SymmetryDefect::Polygon { generator, polygon } => write!(
+                f,
+                "generator {generator} maps polygon {polygon} to something that is not a polygon",
+            ),
+            SymmetryDefect::Facet { generator, polygon } => write!(
+                f,
+                "generator {generator} maps polygon {polygon} to a polygon of an inconsistent facet",
+            ),
+        }
+    }
+}
+impl std::error::Error for SymmetryDefect {}
+
+/// Error returned by [`Mesh::read_off()`].
+#[derive(Debug)]
+pub enum OffParseError {
+    /// Error reading the input.
+    Io(io::Error),
+    /// The first line is not `OFF` or `nOFF`.
+    BadHeader(String),
+    /// The input ended before all vertices and faces were read.
+    UnexpectedEof,
+    /// A token could not be parsed as a number.
+    InvalidNumber {
+        /// 1-based line number.
+        line: usize,
+        /// The token that failed to parse.
+        token: String,
+    },
+    /// A face refers to a vertex that does not exist.
+    IndexOutOfRange {
+        /// 0-based face index.
+        face: usize,
+        /// The invalid vertex index.
+        index: u32,
+    },
+    /// A face has fewer than 3 vertices.
+    FaceTooSmall {
+        /// 0-based face index.
+        face: usize,
+        /// The number of vertices in the face.
+        len: usize,
+    },
+}
+impl fmt::Display for OffParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OffParseError::Io(e) => write!(f, "error reading OFF file: {e}"),
+            OffParseError::BadHeader(header) => write!(f, "expected OFF header; got {header:?}"),
+            OffParseError::UnexpectedEof => write!(f, "unexpected end of OFF file"),
+            OffParseError::InvalidNumber { line, token } => {
+                write!(f, "invalid number {token:?} on line {line}")
+            }
+            OffParseError::IndexOutOfRange { face, index } => {
+                write!(f, "face {face} refers to nonexistent vertex {index}")
+            }
+            OffParseError::FaceTooSmall { face, len } => {
+                write!(f, "face {face} has {len} vertices; expected at least 3")
+            }
+        }
+    }
+}
+impl std::error::Error for OffParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OffParseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for OffParseError {
+    fn from(e: io::Error) -> Self {
+        OffParseError::Io(e)
+    }
+}
+
+fn parse_token<T: std::str::FromStr>(line: usize, token: &str) -> Result<T, OffParseError> {
+    token.parse().map_err(|_| OffParseError::InvalidNumber {
+        line,
+        token: token.to_owned(),
+    })
+}
+
+/// Removes each element of `v` whose corresponding element of `keep` is
+/// `false`.
+pub(crate) fn retain_by_mask<T>(v: &mut Vec<T>, keep: &[bool]) {
+    let mut keep = keep.iter();
+    v.retain(|_| keep.next() == Some(&true));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolytopeArena;
+
+    #[test]
+    fn test_simplify() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap().mesh().unwrap();
+        assert_eq!(cube.verts.len(), 8);
+
+        // Give each polygon its own copy of its vertices, slightly perturbed.
+        let mut mesh = Mesh::default();
+        for (polygon, &facet) in cube.polygons.iter().zip(&cube.facets) {
+            let start = mesh.verts.len() as u32;
+            for &i in polygon {
+                let offset = vector![EPSILON / 10.0] * (mesh.verts.len() % 3) as f32;
+                mesh.verts.push(&cube.verts[i as usize] + offset);
+            }
+            mesh.polygons
+                .push((start..mesh.verts.len() as u32).collect());
+            mesh.facets.push(facet);
+        }
+        // A polygon with a repeated vertex and a degenerate polygon
+        let first = mesh.polygons[0][0];
+        mesh.polygons[0].insert(1, first);
+        mesh.polygons.push(vec![0, 1, 0]);
+        mesh.facets.push(None);
+        assert_eq!(mesh.verts.len(), 24);
+
+        assert_eq!(mesh.simplify(EPSILON), 1);
+        assert_eq!(mesh.verts.len(), 8);
+        assert_eq!(mesh.polygons.len(), 6);
+        assert_eq!(mesh.polygons_by_facet(), cube.polygons_by_facet());
+        let (a, b) = (mesh.to_polygons(), cube.to_polygons());
+        for (p, q) in a.iter().zip(&b) {
+            assert_eq!(p.facet, q.facet);
+            assert_eq!(p.verts.len(), q.verts.len());
+            for (u, v) in p.verts.iter().zip(&q.verts) {
+                assert!(u.approx_eq(v));
+            }
+        }
+
+        // Already simplified
+        let mut copy = mesh.clone();
+        assert_eq!(copy.simplify(EPSILON), 0);
+        assert_eq!(copy, mesh);
+    }
+
+    #[test]
+    fn test_check_symmetry() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let options = crate::SliceOptions::default();
+        let mut mesh = crate::shape_arena_with_group(&group, &[vector![1.0]], &options)
+            .unwrap()
+            .mesh()
+            .unwrap();
+        assert_eq!(mesh.check_symmetry(&group, EPSILON), Ok(()));
+
+        mesh.verts[0] = &mesh.verts[0] + vector![0.1, 0.02, 0.03];
+        match mesh.check_symmetry(&group, EPSILON) {
+            Err(SymmetryDefect::Vertex { vertex, .. }) => assert_eq!(vertex, 0),
+            other => panic!("expected vertex defect; got {other:?}"),
+        }
+
+        // Swapping the facets of two polygons breaks the facet mapping
+        // without moving any vertices.
+        let mut mesh = crate::shape_arena_with_group(&group, &[vector![1.0]], &options)
+            .unwrap()
+            .mesh()
+            .unwrap();
+        mesh.facets.swap(0, 1);
+        mesh.facets[2] = mesh.facets[0];
+        assert!(matches!(
+            mesh.check_symmetry(&group, EPSILON),
+            Err(SymmetryDefect::Facet { .. }),
+        ));
+
+        for edges in [vec![4, 3], vec![5, 3], vec![3, 3, 3]] {
+            let generators = crate::CoxeterDiagram::with_edges(edges).generators();
+            let options = crate::SliceOptions {
+                debug_verify: true,
+                ..Default::default()
+            };
+            let pole = vector![1.0, 0.7, 0.4, 0.2];
+            let ndim = generators[0].ndim();
+            let shape = crate::shape_geom(ndim, &generators, &[pole], &options);
+            assert!(shape.is_ok(), "{shape:?}");
+        }
+    }
+
+    #[test]
+    fn test_cube_mesh() {
+        let mesh = PolytopeArena::new_cube(3, 1.0).unwrap().mesh().unwrap();
+        assert_eq!(mesh.verts.len(), 8);
+        assert_eq!(mesh.polygons.len(), 6);
+        assert!(mesh.polygons.iter().all(|p| p.len() == 4));
+
+        let (verts, triangles) = mesh.triangulate();
+        assert_eq!(verts.len(), 8);
+        assert_eq!(triangles.len(), 12);
+        assert!(triangles
+            .iter()
+            .flatten()
+            .all(|&i| (i as usize) < verts.len()));
+
+        assert_eq!(mesh.to_polygons().len(), 6);
+    }
+
+    fn obj_string(mesh: &Mesh, up_axis: u8) -> String {
+        let mut out = vec![];
+        mesh.write_obj(&mut out, up_axis).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_polygons_by_facet() {
+        // Cube with truncated corners
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let base_facets = [vector![1.0], vector![0.8, 0.8, 0.8]];
+        let facets = crate::facet_orbit(3, &generators, &base_facets, 1000).unwrap();
+        assert_eq!(facets.len(), 14);
+        // Base facets come first, in order.
+        assert_eq!(facets[0].base_facet, 0);
+        assert_eq!(facets[1].base_facet, 1);
+        assert_eq!(facets[1].pole, base_facets[1]);
+
+        let mesh = || {
+            crate::shape_arena(3, &generators, &base_facets, &Default::default())
+                .unwrap()
+                .mesh()
+                .unwrap()
+        };
+        let groups = mesh().polygons_by_facet();
+        assert_eq!(groups, mesh().polygons_by_facet());
+        let facet_indices: Vec<usize> = groups.iter().map(|(facet, _)| *facet).collect();
+        assert_eq!(facet_indices, (0..facets.len()).collect::<Vec<_>>());
+        assert_eq!(
+            groups
+                .iter()
+                .map(|(_, polygons)| polygons.len())
+                .sum::<usize>(),
+            mesh().polygons.len(),
+        );
+    }
+
+    #[test]
+    fn test_raycast() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap().mesh().unwrap();
+        let face_at = |axis: u8, sign: f32| {
+            cube.polygons
+                .iter()
+                .position(|p| p.iter().all(|&v| cube.verts[v as usize][axis] == sign))
+                .unwrap() as u32
+        };
+
+        let hit = cube.raycast(&vector![5.0], &vector![-1.0]).unwrap();
+        assert_eq!(hit.polygon, face_at(0, 1.0));
+        assert!(crate::util::f32_approx_eq(hit.t, 4.0));
+        assert!(hit.point.approx_eq(vector![1.0]));
+
+        // Direction vectors need not be normalized.
+        let hit = cube
+            .raycast(&vector![0.2, -5.0, 0.3], &vector![0.0, 2.0])
+            .unwrap();
+        assert_eq!(hit.polygon, face_at(1, -1.0));
+        assert!(crate::util::f32_approx_eq(hit.t, 2.0));
+        assert!(hit.point.approx_eq(vector![0.2, -1.0, 0.3]));
+
+        // From inside
+        let hit = cube.raycast(&vector![], &vector![0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(hit.polygon, face_at(2, 1.0));
+
+        // Through an edge shared by two faces
+        let hit = cube
+            .raycast(&vector![5.0, 5.0], &vector![-1.0, -1.0])
+            .unwrap();
+        assert_eq!(hit.polygon, face_at(0, 1.0).min(face_at(1, 1.0)));
+        assert!(crate::util::f32_approx_eq(hit.t, 4.0));
+
+        assert_eq!(cube.raycast(&vector![5.0], &vector![1.0]), None);
+        assert_eq!(cube.raycast(&vector![5.0, 2.0], &vector![-1.0]), None);
+    }
+
+    #[test]
+    fn test_orient_outward() {
+        let mut cube = PolytopeArena::new_cube(3, 1.0).unwrap().mesh().unwrap();
+        let outward = |mesh: &Mesh| {
+            mesh.to_polygons()
+                .iter()
+                .filter(|p| p.normal().unwrap().dot(centroid(&p.verts).unwrap()) > 0.0)
+                .count()
+        };
+        assert!(outward(&cube) < 6);
+        cube.orient_outward();
+        assert_eq!(outward(&cube), 6);
+        let oriented = cube.clone();
+        cube.orient_outward();
+        assert_eq!(cube, oriented);
+    }
+
+    #[test]
+    fn test_write_obj() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap().mesh().unwrap();
+        assert_eq!(obj_string(&cube, 1), include_str!("testdata/cube.obj"));
+
+        let octahedron = Mesh {
+            verts: vec![
+                vector![1.0],
+                vector![-1.0],
+                vector![0.0, 1.0],
+                vector![0.0, -1.0],
+                vector![0.0, 0.0, 1.0],
+                vector![0.0, 0.0, -1.0],
+            ],
+            polygons: vec![
+                vec![0, 2, 4],
+                vec![2, 1, 4],
+                vec![1, 3, 4],
+                vec![3, 0, 4],
+                vec![2, 0, 5],
+                vec![1, 2, 5],
+                vec![3, 1, 5],
+                vec![0, 3, 5],
+            ],
+            facets: vec![None; 8],
+        };
+        assert_eq!(
+            obj_string(&octahedron, 2),
+            include_str!("testdata/octahedron.obj"),
+        );
+
+        let hypercube = PolytopeArena::new_cube(4, 1.0).unwrap().mesh().unwrap();
+        let err = hypercube.write_obj(&mut vec![], 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("project the mesh to 3D"));
+        assert!(hypercube.truncated(3).write_obj(&mut vec![], 1).is_ok());
+    }
+
+    fn off_round_trip(mesh: &Mesh) -> Mesh {
+        let mut out = vec![];
+        mesh.write_off(&mut out).unwrap();
+        Mesh::read_off(&out[..]).unwrap()
+    }
+
+    #[test]
+    fn test_off() {
+        for ndim in [3, 4] {
+            let mesh = PolytopeArena::new_cube(ndim, 1.0).unwrap().mesh().unwrap();
+            let read = off_round_trip(&mesh);
+            assert_eq!(read.polygons, mesh.polygons);
+            assert_eq!(read.verts.len(), mesh.verts.len());
+            for (a, b) in read.verts.iter().zip(&mesh.verts) {
+                assert_eq!(a.ndim(), ndim);
+                assert!(a.approx_eq(b));
+            }
+        }
+
+        let mut out = vec![];
+        PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .mesh()
+            .unwrap()
+            .write_off(&mut out)
+            .unwrap();
+        assert!(out.starts_with(b"OFF\n8 6 12\n"));
+
+        let off = "OFF\n# comment\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+        assert!(matches!(
+            Mesh::read_off(off.as_bytes()),
+            Err(OffParseError::IndexOutOfRange { face: 0, index: 3 }),
+        ));
+        let off = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n2 0 1\n";
+        assert!(matches!(
+            Mesh::read_off(off.as_bytes()),
+            Err(OffParseError::FaceTooSmall { face: 0, len: 2 }),
+        ));
+        let off = "OFF\n3 1 0\n0 0 0\n1 x 0\n";
+        assert!(matches!(
+            Mesh::read_off(off.as_bytes()),
+            Err(OffParseError::InvalidNumber { line: 4, .. }),
+        ));
+    }
+
+    #[test]
+    fn test_write_stl_binary() {
+        let mut out = vec![];
+        let cube = PolytopeArena::new_cube(3, 2.0).unwrap().mesh().unwrap();
+        cube.write_stl_binary(&mut out).unwrap();
+
+        let f32_at = |i: usize| f32::from_le_bytes(out[i..i + 4].try_into().unwrap());
+        let triangle_count = u32::from_le_bytes(out[80..84].try_into().unwrap());
+        assert_eq!(triangle_count, 12);
+        assert_eq!(out.len(), 84 + 50 * 12);
+        for offset in (84..out.len()).step_by(50) {
+            let normal = Vector((0..3).map(|i| f32_at(offset + 4 * i)).collect());
+            assert!(normal.is_unit());
+            for i in 3..12 {
+                assert_eq!(f32_at(offset + 4 * i).abs(), 2.0);
+            }
+        }
+
+        // Every normal points outward, away from the center of the cube.
+        let triangle_centers = (84..out.len()).step_by(50).map(|offset| {
+            let vert = |v: usize| Vector((0..3).map(|i| f32_at(offset + 12 * v + 4 * i)).collect());
+            let normal = vert(0);
+            (normal, (vert(1) + vert(2) + vert(3)) / 3.0)
+        });
+        for (normal, center) in triangle_centers {
+            assert!(normal.dot(center) > 0.0, "inward normal {normal}");
+        }
+
+        let hypercube = PolytopeArena::new_cube(4, 1.0).unwrap().mesh().unwrap();
+        assert!(hypercube.write_stl_binary(&mut vec![]).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use itertools::Itertools;
+use std::fmt;
+
+use crate::vector::{Vector, VectorRef};
+
+/// Permutation of the integers `0..n`, stored in one-line notation: the
+/// permutation sends `i` to `self.one_line()[i]`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Permutation(Vec<usize>);
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.0.iter().join(" "))
+    }
+}
+impl Permutation {
+    /// Returns the identity permutation of `0..n`.
+    pub fn ident(n: usize) -> Self {
+        Self((0..n).collect())
+    }
+    /// Constructs a permutation from one-line notation, returning `None` if
+    /// `one_line` does not contain each of `0..one_line.len()` exactly once.
+    pub fn from_one_line(one_line: Vec<usize>) -> Option<Self> {
+        let mut seen = vec![false; one_line.len()];
+        for &i in &one_line {
+            if std::mem::replace(seen.get_mut(i)?, true) {
+                return None;
+            }
+        }
+        Some(Self(one_line))
+    }
+    /// Returns every permutation of `0..n` in lexicographic order of their
+    /// one-line notation.
+    pub fn all(n: usize) -> impl Iterator<Item = Self> {
+        (0..n).permutations(n).map(Self)
+    }
+
+    /// Returns the permutation in one-line notation.
+    pub fn one_line(&self) -> &[usize] {
+        &self.0
+    }
+    /// Returns the number of elements being permuted.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns whether the permutation is of zero elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Returns the image of `i` under the permutation. Elements beyond the
+    /// end of the permutation are fixed.
+    pub fn image(&self, i: usize) -> usize {
+        self.0.get(i).copied().unwrap_or(i)
+    }
+
+    /// Returns the permutation that applies `other` and then `self`. The
+    /// result has the length of the longer permutation.
+    #[must_use]
+    pub fn compose(&self, other: &Self) -> Self {
+        let n = std::cmp::max(self.len(), other.len());
+        Self((0..n).map(|i| self.image(other.image(i))).collect())
+    }
+    /// Returns the inverse permutation.
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        let mut ret = vec![0; self.len()];
+        for (i, &j) in self.0.iter().enumerate() {
+            ret[j] = i;
+        }
+        Self(ret)
+    }
+    /// Returns `true` if the permutation is odd, or `false` if it is even.
+    pub fn parity(&self) -> bool {
+        let mut seen = vec![false; self.len()];
+        let mut cycles = 0;
+        for start in 0..self.len() {
+            if !seen[start] {
+                cycles += 1;
+                let mut i = start;
+                while !seen[i] {
+                    seen[i] = true;
+                    i = self.0[i];
+                }
+            }
+        }
+        (self.len() - cycles) % 2 == 1
+    }
+
+    /// Moves each element of `slice` at index `i` to index `self.image(i)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice` has a different length than the permutation.
+    pub fn apply<T: Clone>(&self, slice: &[T]) -> Vec<T> {
+        assert_eq!(slice.len(), self.len(), "permutation length mismatch");
+        self.inverse()
+            .0
+            .into_iter()
+            .map(|i| slice[i].clone())
+            .collect()
+    }
+    /// Moves each component of `v` at index `i` to index `self.image(i)`.
+    /// Components beyond the end of the permutation are unchanged, and
+    /// components beyond the end of `v` are zero.
+    pub fn apply_to_vector(&self, v: impl VectorRef<f32>) -> Vector<f32> {
+        let ndim = std::cmp::max(self.len(), v.ndim() as usize);
+        let mut ret = vec![0.0; ndim];
+        for i in 0..ndim {
+            ret[self.image(i)] = v.get(i as u8);
+        }
+        ret.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::permutation_parity;
+
+    #[test]
+    fn test_permutation_parity() {
+        for n in 0..=6 {
+            for (i, p) in Permutation::all(n).enumerate() {
+                assert_eq!(p.parity(), permutation_parity(i), "{p}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_permutation() {
+        assert_eq!(Permutation::from_one_line(vec![0, 0]), None);
+        assert_eq!(Permutation::from_one_line(vec![0, 2]), None);
+        let p = Permutation::from_one_line(vec![1, 2, 0, 3]).unwrap();
+        let q = Permutation::from_one_line(vec![0, 1, 3, 2]).unwrap();
+        assert_eq!(p.to_string(), "[1 2 0 3]");
+        assert!(!p.parity());
+        assert!(q.parity());
+        assert!(p.compose(&q).parity());
+
+        assert_eq!(p.compose(&p.inverse()), Permutation::ident(4));
+        assert_eq!(p.inverse().compose(&p), Permutation::ident(4));
+        assert_eq!(p.compose(&q).one_line(), [1, 2, 3, 0]);
+        assert_eq!(
+            p.compose(&Permutation::ident(6)).one_line(),
+            [1, 2, 0, 3, 4, 5]
+        );
+
+        assert_eq!(p.apply(&['a', 'b', 'c', 'd']), ['c', 'a', 'b', 'd']);
+        let pq = p.compose(&q);
+        let xs = [10, 20, 30, 40];
+        assert_eq!(pq.apply(&xs), p.apply(&q.apply(&xs)));
+        assert_eq!(p.inverse().apply(&p.apply(&xs)), xs);
+
+        assert_eq!(
+            p.apply_to_vector(vector![1.0, 2.0, 3.0, 4.0, 5.0]),
+            vector![3.0, 1.0, 2.0, 4.0, 5.0],
+        );
+        assert_eq!(p.apply_to_vector(vector![1.0]), vector![0.0, 1.0, 0.0, 0.0],);
+    }
+}
@@ -1,61 +1,958 @@
 use smallvec::{smallvec, SmallVec};
-use std::{collections::HashMap, ops::*};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt,
+    ops::*,
+    sync::atomic::{self, AtomicU64},
+    sync::OnceLock,
+};
 
+use crate::group::{Group, GroupElement};
+use crate::hyperplane::Hyperplane;
+use crate::lattice::Lattice;
 use crate::matrix::Matrix;
-use crate::util::EPSILON;
-use crate::vector::{Vector, VectorRef};
+use crate::mesh::{retain_by_mask, Mesh, SymmetryDefect};
+use crate::util::{par_map, Tolerance, EPSILON};
+use crate::vector::{weld_points, PointGrid, Real, Vector, VectorRef};
+
+/// Number of times [`shape_geom()`] will double the radius of the initial
+/// cube before giving up.
+const MAX_RADIUS_RETRIES: usize = 4;
+
+/// Error returned by [`shape_geom()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeGeomError {
+    /// No base facets were given.
+    NoBaseFacets,
+    /// A base facet has a zero pole, so it doesn't define a hyperplane.
+    ZeroPole {
+        /// Index of the base facet.
+        base_facet: usize,
+    },
+    /// Some corners of the initial cube were not sliced off, so the facets
+    /// don't bound a finite shape.
+    UnboundedShape {
+        /// Corners of the initial cube that survived every slice.
+        surviving_corners: Vec<Vector<f32>>,
+    },
+    /// The resulting shape contains an invalid polygon.
+    SliceFailure(PolygonError),
+    /// The orbit of the base facets has more than the maximum number of
+    /// facets, which usually means that the generators don't generate a
+    /// finite group.
+    TooManyFacets {
+        /// Maximum number of facets.
+        limit: usize,
+    },
+    /// The initial shape could not be constructed.
+    Arena(ArenaError),
+    /// The resulting shape is not symmetric, which indicates a bug in
+    /// slicing. This is only checked if [`SliceOptions::debug_verify`] is set.
+    Asymmetric(SymmetryDefect),
+}
+impl From<PolygonError> for ShapeGeomError {
+    fn from(e: PolygonError) -> Self {
+        ShapeGeomError::SliceFailure(e)
+    }
+}
+impl From<ArenaError> for ShapeGeomError {
+    fn from(e: ArenaError) -> Self {
+        ShapeGeomError::Arena(e)
+    }
+}
+impl From<SymmetryDefect> for ShapeGeomError {
+    fn from(e: SymmetryDefect) -> Self {
+        ShapeGeomError::Asymmetric(e)
+    }
+}
+impl fmt::Display for ShapeGeomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeGeomError::NoBaseFacets => write!(f, "no base facets"),
+            ShapeGeomError::ZeroPole { base_facet } => {
+                write!(f, "base facet {base_facet} has a zero pole")
+            }
+            ShapeGeomError::UnboundedShape { surviving_corners } => write!(
+                f,
+                "facets do not bound a finite shape ({} corners of the initial cube remain)",
+                surviving_corners.len(),
+            ),
+            ShapeGeomError::SliceFailure(e) => e.fmt(f),
+            ShapeGeomError::TooManyFacets { limit } => {
+                write!(f, "shape has more than {limit} facets")
+            }
+            ShapeGeomError::Arena(e) => e.fmt(f),
+            ShapeGeomError::Asymmetric(e) => write!(f, "shape is not symmetric: {e}"),
+        }
+    }
+}
+impl std::error::Error for ShapeGeomError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShapeGeomError::SliceFailure(e) => Some(e),
+            ShapeGeomError::Arena(e) => Some(e),
+            ShapeGeomError::Asymmetric(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Location of a point relative to a polytope.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Containment {
+    /// The point is strictly inside the polytope.
+    Inside,
+    /// The point is on the boundary of the polytope.
+    OnBoundary,
+    /// The point is outside the polytope.
+    Outside,
+}
+
+/// Default maximum number of elements of the initial polytope of a
+/// [`PolytopeArena`], which is enough for a 14D hypercube or a 22D simplex.
+pub const DEFAULT_MAX_ARENA_ELEMENTS: usize = 10_000_000;
+
+/// Error returned by operations on a [`PolytopeArena`]: constructing an
+/// initial polytope, restoring a snapshot, slicing with a progress callback,
+/// or transforming by a matrix.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArenaError {
+    /// The initial polytope has too many elements to fit in an arena.
+    DimensionTooLarge {
+        /// Number of dimensions requested.
+        ndim: u8,
+    },
+    /// The initial polytope has more than the maximum number of elements.
+    /// See [`DEFAULT_MAX_ARENA_ELEMENTS`].
+    TooManyElements {
+        /// Number of dimensions requested.
+        ndim: u8,
+        /// Maximum number of elements.
+        limit: usize,
+    },
+    /// The snapshot was not the most recent one taken of the arena.
+    StaleSnapshot,
+    /// Slicing was cancelled by a progress callback.
+    Cancelled,
+    /// The matrix passed to [`PolytopeArena::transformed()`] is not
+    /// invertible.
+    SingularMatrix,
+}
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArenaError::DimensionTooLarge { ndim } => {
+                write!(f, "{ndim}D polytope has too many elements")
+            }
+            ArenaError::TooManyElements { ndim, limit } => {
+                write!(f, "{ndim}D polytope has more than {limit} elements")
+            }
+            ArenaError::StaleSnapshot => write!(f, "snapshot is not the latest one of this arena"),
+            ArenaError::Cancelled => write!(f, "slicing was cancelled"),
+            ArenaError::SingularMatrix => write!(f, "transformation matrix is not invertible"),
+        }
+    }
+}
+impl std::error::Error for ArenaError {}
+
+/// Error returned by [`PolytopeArena::cross_section()`] and
+/// [`PolytopeArena::vertex_figure()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SectionError {
+    /// The arena has no dimensions to take a cross-section of.
+    ZeroDimensional,
+    /// The hyperplane is not perpendicular to the space of the arena.
+    PlaneOutsideSpace {
+        /// Number of dimensions of the arena.
+        ndim: u8,
+    },
+    /// The polytope whose vertex figure was requested is not a vertex.
+    NotAVertex(PolytopeId),
+    /// No hyperplane separates the vertex from the adjacent vertices, such as
+    /// if it is in the interior of the arena or of an edge that was cut into
+    /// pieces.
+    NoVertexFigure(PolytopeId),
+}
+impl fmt::Display for SectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SectionError::ZeroDimensional => write!(f, "cannot take cross-section of a point"),
+            SectionError::PlaneOutsideSpace { ndim } => {
+                write!(f, "hyperplane normal has components beyond {ndim}D")
+            }
+            SectionError::NotAVertex(p) => write!(f, "{p:?} is not a vertex"),
+            SectionError::NoVertexFigure(p) => {
+                write!(f, "no hyperplane separates {p:?} from adjacent vertices")
+            }
+        }
+    }
+}
+impl std::error::Error for SectionError {}
+
+/// Error returned by [`PolytopeArena::from_convex_hull()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HullError {
+    /// There are fewer points than needed to span the space.
+    TooFewPoints {
+        /// Number of points given.
+        count: usize,
+        /// Number of dimensions of the points.
+        ndim: u8,
+    },
+    /// The points lie in a lower-dimensional subspace, so their hull has no
+    /// volume.
+    Degenerate,
+    /// Slicing the hull failed.
+    SliceFailure(ShapeGeomError),
+}
+impl From<ShapeGeomError> for HullError {
+    fn from(e: ShapeGeomError) -> Self {
+        match e {
+            ShapeGeomError::UnboundedShape { .. } => HullError::Degenerate,
+            e => HullError::SliceFailure(e),
+        }
+    }
+}
+impl fmt::Display for HullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HullError::TooFewPoints { count, ndim } => {
+                write!(f, "{count} points cannot span {ndim} dimensions")
+            }
+            HullError::Degenerate => write!(f, "points lie in a lower-dimensional subspace"),
+            HullError::SliceFailure(e) => e.fmt(f),
+        }
+    }
+}
+impl std::error::Error for HullError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HullError::SliceFailure(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Default tolerance for slicing. Unlike [`Tolerance::default()`], this is
+/// mostly relative to the distance of a point from the origin, so that
+/// shapes of any size are sliced the same way. The tiny absolute component
+/// only matters for points near the origin.
+pub const DEFAULT_SLICE_TOLERANCE: Tolerance = Tolerance {
+    abs: EPSILON * EPSILON,
+    rel: EPSILON,
+};
+
+/// Options for slicing a polytope and constructing shapes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SliceOptions {
+    /// Tolerance for deciding whether a point is on a hyperplane, relative to
+    /// the distance of the point from the origin. Defaults to
+    /// [`DEFAULT_SLICE_TOLERANCE`].
+    pub tolerance: Tolerance,
+    /// Maximum number of facets in a shape.
+    pub max_facets: usize,
+    /// Shape to slice when constructing a shape.
+    pub initial_shape: InitialShape,
+    /// Whether to record statistics for each slice. See
+    /// [`PolytopeArena::slice_log()`].
+    pub log_slices: bool,
+    /// Floating-point precision of coordinates while slicing a shape.
+    pub precision: Precision,
+    /// Minimum area of a polygon in [`shape_geom()`] and
+    /// [`PolytopeArena::mesh()`]. Smaller polygons, such as slivers left by a
+    /// cut tangent to an edge, are dropped.
+    pub min_polygon_area: f32,
+    /// Whether [`shape_geom()`] checks that the symmetry group maps the
+    /// resulting mesh onto itself, and whether [`PolytopeArena`] validates
+    /// itself after every slice. Both are slow. See
+    /// [`Mesh::check_symmetry()`] and [`PolytopeArena::validate()`].
+    pub debug_verify: bool,
+    /// Whether to compute orbits, slice, and extract polygons in parallel
+    /// when the `rayon` feature is enabled. The result is the same either
+    /// way.
+    pub parallel: bool,
+}
+
+/// Summary of a single slice of a [`PolytopeArena`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SliceStats {
+    /// Number of polytopes entirely outside the hyperplane.
+    pub removed: usize,
+    /// Number of polytopes entirely inside the hyperplane.
+    pub kept: usize,
+    /// Number of polytopes that straddled the hyperplane and were split.
+    pub modified: usize,
+    /// Number of new polytopes created by the slice.
+    pub created: usize,
+    /// Facet created by the slice in the first piece that it split, or `None`
+    /// if it didn't split any pieces.
+    pub new_facet: Option<PolytopeId>,
+}
+impl fmt::Display for SliceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SliceStats {
+            removed,
+            kept,
+            modified,
+            created,
+            new_facet,
+        } = self;
+        write!(
+            f,
+            "{removed} removed, {kept} kept, {modified} modified, {created} created",
+        )?;
+        if new_facet.is_none() {
+            write!(f, " (no new facet)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Progress of slicing by many hyperplanes, passed to progress callbacks such
+/// as in [`PolytopeArena::slice_by_all_with_progress()`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SliceProgress {
+    /// Number of hyperplanes that have been sliced by so far.
+    pub planes_completed: usize,
+    /// Total number of hyperplanes to slice by.
+    pub plane_count: usize,
+    /// Number of polytopes currently in the arena.
+    pub polytope_count: usize,
+}
+impl SliceProgress {
+    /// Returns the fraction of hyperplanes completed, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        match self.plane_count {
+            0 => 1.0,
+            n => self.planes_completed as f32 / n as f32,
+        }
+    }
+}
+
+/// Shape that [`shape_geom()`] starts with before slicing.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InitialShape {
+    /// Hypercube, which has 3^NDIM elements. See
+    /// [`PolytopeArena::new_cube()`].
+    #[default]
+    Cube,
+    /// Regular simplex, which has 2^(NDIM+1)-1 elements. See
+    /// [`PolytopeArena::new_simplex()`].
+    Simplex,
+}
+/// Floating-point precision that [`shape_geom()`] slices with. The resulting
+/// geometry is always `f32`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// Slice using `f32` coordinates. See [`PolytopeArena`].
+    #[default]
+    Single,
+    /// Slice using `f64` coordinates, which is slower but can separate facets
+    /// that are too close together for `f32`. See [`PolytopeArena64`].
+    Double,
+}
+impl Default for SliceOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_SLICE_TOLERANCE,
+            max_facets: 10_000,
+            initial_shape: InitialShape::default(),
+            log_slices: false,
+            precision: Precision::default(),
+            min_polygon_area: 0.0,
+            debug_verify: false,
+            parallel: true,
+        }
+    }
+}
+/// Facet of a shape constructed by [`shape_geom()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    /// Pole of the facet.
+    pub pole: Vector<f32>,
+    /// Index of the base facet that this facet is an image of.
+    pub base_facet: usize,
+    /// Group element that sends the base facet to this facet.
+    pub transform: Matrix<f32>,
+    /// Index of this facet among the facets with the same base facet, in
+    /// order. The base facet itself has index 0.
+    pub orbit_index: usize,
+    /// Element of the group that sends the base facet to this facet, if the
+    /// shape was constructed from a [`Group`], such as by
+    /// [`shape_geom_with_group()`].
+    pub group_element: Option<GroupElement>,
+}
+
+/// Shape constructed by [`shape_geom()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeGeom {
+    /// Polygons of the shape. [`Polygon::facet`] is an index into `facets`.
+    pub polygons: Vec<Polygon>,
+    /// Every facet of the shape, including the base facets.
+    pub facets: Vec<Facet>,
+    /// Number of base facets that were skipped because they duplicate an
+    /// earlier base facet.
+    pub skipped_duplicates: usize,
+    /// Number of polygons that were dropped because their area is less than
+    /// [`SliceOptions::min_polygon_area`].
+    pub dropped_polygons: usize,
+}
 
 pub fn shape_geom(
     ndim: u8,
     generators: &[Matrix<f32>],
     base_facets: &[Vector<f32>],
-) -> Vec<Polygon> {
-    let radius = base_facets
-        .iter()
-        .map(|pole| pole.mag())
-        .reduce(f32::max)
-        .expect("no base facets");
-    let initial_radius = radius * 2.0 * ndim as f32;
-    // TODO: check if radius is too small (any original point remains).
-    let mut arena = PolytopeArena::new_cube(ndim, initial_radius);
+    options: &SliceOptions,
+) -> Result<ShapeGeom, ShapeGeomError> {
+    shape_geom_with_progress(ndim, generators, base_facets, options, |_| {
+        ControlFlow::Continue(())
+    })
+}
+
+/// Constructs a shape like [`shape_geom()`], calling `progress` after each
+/// facet is sliced. If `progress` returns [`ControlFlow::Break`], slicing
+/// stops and [`ArenaError::Cancelled`] is returned.
+pub fn shape_geom_with_progress(
+    ndim: u8,
+    generators: &[Matrix<f32>],
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+    mut progress: impl FnMut(SliceProgress) -> ControlFlow<()>,
+) -> Result<ShapeGeom, ShapeGeomError> {
+    let facets = facet_orbit_with_options(ndim, generators, base_facets, options)?;
+    shape_geom_from_facets(
+        ndim,
+        generators,
+        base_facets.len(),
+        facets,
+        options,
+        &mut progress,
+    )
+}
+
+/// Constructs the shape bounded by the orbits of `base_facets` under `group`.
+/// This is equivalent to [`shape_geom()`] with the generators of the group,
+/// except that facets are ordered by the group element that produces them.
+pub fn shape_geom_with_group(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<ShapeGeom, ShapeGeomError> {
+    let facets = group_facet_orbit(group, base_facets, options)?;
+    let generators: Vec<Matrix<f32>> = group
+        .generators()
+        .map(|g| group.matrix(g).to_owned())
+        .collect();
+    shape_geom_from_facets(
+        group.ndim(),
+        &generators,
+        base_facets.len(),
+        facets,
+        options,
+        &mut |_| ControlFlow::Continue(()),
+    )
+}
+
+fn shape_geom_from_facets(
+    ndim: u8,
+    generators: &[Matrix<f32>],
+    base_facet_count: usize,
+    facets: Vec<Facet>,
+    options: &SliceOptions,
+    progress: &mut dyn FnMut(SliceProgress) -> ControlFlow<()>,
+) -> Result<ShapeGeom, ShapeGeomError> {
+    let FilteredMesh {
+        mesh,
+        dropped: dropped_polygons,
+        ..
+    } = match options.precision {
+        Precision::Single => {
+            arena_from_facets::<f32>(ndim, &facets, options, progress)?.filtered_mesh()?
+        }
+        Precision::Double => {
+            arena_from_facets::<f64>(ndim, &facets, options, progress)?.filtered_mesh()?
+        }
+    };
+    if options.debug_verify {
+        let radius = mesh.verts.iter().map(|v| v.mag()).fold(0.0, f32::max);
+        mesh.check_symmetry_under(
+            generators.iter().map(Matrix::view),
+            options.tolerance.eps(radius),
+        )?;
+    }
+    let polygons = mesh.to_polygons();
+    debug_assert!(
+        polygons
+            .iter()
+            .flat_map(|p| &p.verts)
+            .all(|v| v.is_finite()),
+        "non-finite vertex in shape geometry",
+    );
+    let skipped_duplicates = (0..base_facet_count)
+        .filter(|&i| !facets.iter().any(|f| f.base_facet == i))
+        .count();
+    Ok(ShapeGeom {
+        polygons,
+        facets,
+        skipped_duplicates,
+        dropped_polygons,
+    })
+}
+
+/// Constructs the shape bounded by the orbits of `base_facets` under
+/// `generators`, returning the arena so that its cells and pieces can be
+/// inspected. See [`shape_geom()`].
+pub fn shape_arena(
+    ndim: u8,
+    generators: &[Matrix<f32>],
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<PolytopeArena, ShapeGeomError> {
+    let facets = facet_orbit_with_options(ndim, generators, base_facets, options)?;
+    arena_from_facets_with_precision(ndim, &facets, options, &mut |_| ControlFlow::Continue(()))
+}
+
+/// Constructs the shape bounded by the orbits of `base_facets` under `group`,
+/// returning the arena. See [`shape_geom_with_group()`].
+pub fn shape_arena_with_group(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<PolytopeArena, ShapeGeomError> {
+    shape_arena_with_group_and_progress(group, base_facets, options, |_| ControlFlow::Continue(()))
+}
+
+/// Constructs the shape bounded by the orbits of `base_facets` under `group`,
+/// calling `progress` after each facet is sliced. See
+/// [`shape_geom_with_progress()`].
+pub fn shape_arena_with_group_and_progress(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+    mut progress: impl FnMut(SliceProgress) -> ControlFlow<()>,
+) -> Result<PolytopeArena, ShapeGeomError> {
+    let facets = group_facet_orbit(group, base_facets, options)?;
+    arena_from_facets_with_precision(group.ndim(), &facets, options, &mut progress)
+}
+
+/// Returns `base_facets` plus one base facet for each orbit of vertices of the
+/// shape they bound under `group`, which cuts off those vertices. Passing the
+/// result to [`shape_arena_with_group()`] or [`shape_geom_with_group()`]
+/// constructs the truncated shape.
+///
+/// Each new facet is perpendicular to its vertex. `t` controls how deep it is:
+/// at 0 it touches the vertex, and at 1 it passes through the midpoint of the
+/// nearest edge incident to the vertex, which is rectification for uniform
+/// polytopes. See [`rectified_poles()`].
+// The origin is strictly inside the shape, so no vertex is there.
+#[allow(clippy::expect_used)]
+pub fn truncated_poles(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    t: f32,
+    options: &SliceOptions,
+) -> Result<Vec<Vector<f32>>, ShapeGeomError> {
+    let arena = shape_arena_with_group(group, base_facets, options)?;
+    let mut ret = base_facets.to_vec();
+    for orbit in arena.element_orbits(group, 0) {
+        let v = orbit[0];
+        let point = arena[v].unwrap_point();
+        // The origin is strictly inside the shape, so no vertex is there.
+        let dir = point.normalized().expect("vertex at origin");
+        // Distance to the plane through the nearest edge midpoint
+        let rectified_distance = arena
+            .parents_of(v)
+            .iter()
+            .flat_map(|&edge| arena.children_of(edge))
+            .filter(|&&w| w != v)
+            .map(|&w| dir.dot((point + arena[w].unwrap_point()) / 2.0))
+            .fold(f32::NEG_INFINITY, f32::max);
+        let distance = point.mag() + t * (rectified_distance - point.mag());
+        ret.push(dir * distance);
+    }
+    Ok(ret)
+}
+
+/// Returns `base_facets` plus base facets that cut off each vertex of the
+/// shape they bound through the midpoints of its edges. This is
+/// [`truncated_poles()`] with `t = 1`.
+pub fn rectified_poles(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<Vec<Vector<f32>>, ShapeGeomError> {
+    truncated_poles(group, base_facets, 1.0, options)
+}
 
-    let mut facet_poles: Vec<Vector<f32>> = base_facets.to_vec();
+/// Returns the orbits of `base_facets` under `generators`, starting with the
+/// base facets themselves.
+///
+/// The order of the output is deterministic: facets are discovered breadth
+/// first, applying each generator in order to each facet in order.
+///
+/// Poles that are approximately equal, relative to their magnitude, are
+/// considered duplicates. Base facets that duplicate an earlier base facet are
+/// skipped.
+pub fn facet_orbit(
+    ndim: u8,
+    generators: &[Matrix<f32>],
+    base_facets: &[Vector<f32>],
+    max_facets: usize,
+) -> Result<Vec<Facet>, ShapeGeomError> {
+    let options = SliceOptions {
+        max_facets,
+        ..Default::default()
+    };
+    facet_orbit_with_options(ndim, generators, base_facets, &options)
+}
+
+/// Returns the orbits of `base_facets` under `generators`, rejecting poles
+/// within the absolute part of [`SliceOptions::tolerance`] of zero. See
+/// [`facet_orbit()`].
+fn facet_orbit_with_options(
+    ndim: u8,
+    generators: &[Matrix<f32>],
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<Vec<Facet>, ShapeGeomError> {
+    check_base_facets(base_facets, options.tolerance.abs)?;
+    let max_facets = options.max_facets;
+    enter_span!(DEBUG, "facet_orbit", base_facets = base_facets.len());
+
+    let mut facets: Vec<Facet> = vec![];
+    let mut poles = PoleSet::new(base_facets);
+    for (i, pole) in base_facets.iter().enumerate() {
+        if poles.insert(&facets, pole) {
+            facets.push(Facet {
+                pole: pole.clone(),
+                base_facet: i,
+                transform: Matrix::ident(ndim),
+                orbit_index: 0,
+                group_element: None,
+            });
+        }
+    }
     let mut next_unprocessed = 0;
-    while next_unprocessed < facet_poles.len() {
-        facet_poles[next_unprocessed].set_ndim(ndim);
+    while next_unprocessed < facets.len() {
+        facets[next_unprocessed].pole.set_ndim(ndim);
         for gen in generators {
-            let new_pole = gen.transform(&facet_poles[next_unprocessed]);
-            if facet_poles.iter().all(|pole| !pole.approx_eq(&new_pole)) {
-                facet_poles.push(new_pole);
+            let facet = &facets[next_unprocessed];
+            let new_pole = gen.transform(&facet.pole);
+            if poles.insert(&facets, &new_pole) {
+                if facets.len() == max_facets {
+                    return Err(ShapeGeomError::TooManyFacets { limit: max_facets });
+                }
+                facets.push(Facet {
+                    pole: new_pole,
+                    base_facet: facet.base_facet,
+                    transform: gen * &facet.transform,
+                    orbit_index: 0,
+                    group_element: None,
+                });
             }
         }
         next_unprocessed += 1;
     }
-    for pole in &facet_poles {
-        arena.slice_by_plane(pole);
+    number_orbits(&mut facets, base_facets.len());
+    trace_event!(DEBUG, facets = facets.len(), "expanded facet orbits");
+    Ok(facets)
+}
+
+/// Returns the orbits of `base_facets` under `group`, starting with the base
+/// facets themselves and then ordered by group element. Duplicates are handled
+/// the same way as in [`facet_orbit()`].
+fn group_facet_orbit(
+    group: &Group,
+    base_facets: &[Vector<f32>],
+    options: &SliceOptions,
+) -> Result<Vec<Facet>, ShapeGeomError> {
+    check_base_facets(base_facets, options.tolerance.abs)?;
+    let max_facets = options.max_facets;
+    enter_span!(DEBUG, "facet_orbit", base_facets = base_facets.len());
+
+    let mut facets: Vec<Facet> = vec![];
+    let mut poles = PoleSet::new(base_facets);
+    let mut orbits = vec![];
+    for (i, pole) in base_facets.iter().enumerate() {
+        let mut pole = pole.clone();
+        pole.set_ndim(group.ndim());
+        if poles.insert(&facets, &pole) {
+            orbits.push((i, group.orbit_with_parallelism(&pole, options.parallel)));
+            facets.push(Facet {
+                pole,
+                base_facet: i,
+                transform: Matrix::ident(group.ndim()),
+                orbit_index: 0,
+                group_element: Some(GroupElement::IDENT),
+            });
+        }
+    }
+    for (base_facet, orbit) in orbits {
+        for (e, pole) in orbit {
+            if poles.insert(&facets, &pole) {
+                if facets.len() == max_facets {
+                    return Err(ShapeGeomError::TooManyFacets { limit: max_facets });
+                }
+                facets.push(Facet {
+                    pole,
+                    base_facet,
+                    transform: group.matrix(e).to_owned(),
+                    orbit_index: 0,
+                    group_element: Some(e),
+                });
+            }
+        }
+    }
+    number_orbits(&mut facets, base_facets.len());
+    trace_event!(DEBUG, facets = facets.len(), "expanded facet orbits");
+    Ok(facets)
+}
+
+/// Sets [`Facet::orbit_index`] of each facet, in order.
+fn number_orbits(facets: &mut [Facet], base_facet_count: usize) {
+    let mut orbit_sizes = vec![0; base_facet_count];
+    for facet in facets {
+        facet.orbit_index = orbit_sizes[facet.base_facet];
+        orbit_sizes[facet.base_facet] += 1;
+    }
+}
+
+fn check_base_facets(base_facets: &[Vector<f32>], eps: f32) -> Result<(), ShapeGeomError> {
+    if base_facets.is_empty() {
+        return Err(ShapeGeomError::NoBaseFacets);
+    }
+    if let Some(i) = base_facets.iter().position(|pole| pole.mag() <= eps) {
+        return Err(ShapeGeomError::ZeroPole { base_facet: i });
+    }
+    Ok(())
+}
+
+/// Returns whether two poles are approximately equal, relative to their
+/// magnitude.
+fn poles_approx_eq(a: &Vector<f32>, b: &Vector<f32>) -> bool {
+    (a - b).mag() < EPSILON * f32::max(a.mag(), b.mag())
+}
+
+/// Set of facet poles, hashed by [`OrderedVector`](crate::OrderedVector) key
+/// so that finding duplicates takes constant time instead of comparing
+/// against every pole.
+///
+/// The result is always the same as comparing against every pole with
+/// `poles_approx_eq()`. See [`PointGrid`].
+struct PoleSet {
+    /// Maximum magnitude of any pole in the set.
+    max_mag: f32,
+    /// Indices into the facet list of the poles, with a cell size that is
+    /// much larger than the tolerance.
+    grid: PointGrid,
+}
+impl PoleSet {
+    fn new(base_facets: &[Vector<f32>]) -> Self {
+        let max_mag = base_facets.iter().map(|v| v.mag()).fold(0.0, f32::max);
+        Self {
+            max_mag,
+            grid: PointGrid::new(16.0 * EPSILON * max_mag),
+        }
+    }
+
+    /// Returns the index of a pole in `facets` that is approximately equal to
+    /// `pole`, if there is one.
+    fn find(&self, facets: &[Facet], pole: &Vector<f32>) -> Option<usize> {
+        let eps = EPSILON * f32::max(pole.mag(), self.max_mag);
+        self.grid
+            .nearby(pole, eps)
+            .find(|&i| poles_approx_eq(&facets[i].pole, pole))
+    }
+    /// Adds `pole` to the set, which will be at index `facets.len()`, and
+    /// returns `true`. If there is already an approximately equal pole, returns
+    /// `false` instead and does nothing.
+    fn insert(&mut self, facets: &[Facet], pole: &Vector<f32>) -> bool {
+        if self.find(facets, pole).is_some() {
+            return false;
+        }
+        self.max_mag = f32::max(self.max_mag, pole.mag());
+        self.grid.insert(pole, facets.len());
+        true
+    }
+}
+
+/// Slices a cube by every facet using the precision in `options`, and then
+/// converts the result to `f32`.
+fn arena_from_facets_with_precision(
+    ndim: u8,
+    facets: &[Facet],
+    options: &SliceOptions,
+    progress: &mut dyn FnMut(SliceProgress) -> ControlFlow<()>,
+) -> Result<PolytopeArena, ShapeGeomError> {
+    match options.precision {
+        Precision::Single => arena_from_facets(ndim, facets, options, progress),
+        Precision::Double => Ok(arena_from_facets::<f64>(ndim, facets, options, progress)?.cast()),
+    }
+}
+
+/// Slices a cube by every facet, in order, so that the index of each facet is
+/// the index of the cut that produced it.
+///
+/// `progress` is called after each facet. If the cube must be enlarged and
+/// sliced again, progress starts over from zero.
+fn arena_from_facets<N: Real>(
+    ndim: u8,
+    facets: &[Facet],
+    options: &SliceOptions,
+    progress: &mut dyn FnMut(SliceProgress) -> ControlFlow<()>,
+) -> Result<PolytopeArena<N>, ShapeGeomError> {
+    let radius = facets
+        .iter()
+        .map(|facet| facet.pole.mag())
+        .reduce(f32::max)
+        .ok_or(ShapeGeomError::NoBaseFacets)?;
+
+    enter_span!(DEBUG, "arena_from_facets", facets = facets.len());
+
+    let mut initial_radius = radius * 2.0 * ndim as f32;
+    let mut retries = 0;
+    let arena = loop {
+        let arena = match options.initial_shape {
+            InitialShape::Cube => PolytopeArena::new_cube(ndim, N::from_f32(initial_radius))?,
+            InitialShape::Simplex => PolytopeArena::new_simplex(ndim, N::from_f32(initial_radius))?,
+        };
+        let mut arena = arena.with_slice_options(*options);
+        let corners: Vec<Vector<f32>> = arena.vertices().map(|(_, v)| v.round_to_f32()).collect();
+        for (i, Facet { pole, .. }) in facets.iter().enumerate() {
+            debug_assert!(pole.is_finite(), "non-finite facet pole {pole}");
+            arena.slice_by_pole(&Vector::from_f32(pole));
+            if progress(arena.progress(i + 1, facets.len())).is_break() {
+                return Err(ArenaError::Cancelled.into());
+            }
+        }
+        arena.weld_vertices(N::from_f32(options.tolerance.eps(radius)));
+
+        // If any corner of the initial shape remains, then either the shape
+        // was too small or the shape is unbounded.
+        let surviving_corners: Vec<Vector<f32>> = arena
+            .vertices()
+            .map(|(_, point)| point.round_to_f32())
+            .filter(|point| {
+                let eps = options.tolerance.eps(initial_radius);
+                corners
+                    .iter()
+                    .any(|corner| corner.approx_eq_eps(point, eps))
+            })
+            .collect();
+        if surviving_corners.is_empty() {
+            break arena;
+        } else if retries == MAX_RADIUS_RETRIES {
+            return Err(ShapeGeomError::UnboundedShape { surviving_corners });
+        }
+        initial_radius *= 2.0;
+        retries += 1;
+    };
+
+    Ok(arena)
+}
+
+/// Saved state of a [`PolytopeArena`], returned by
+/// [`PolytopeArena::snapshot()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArenaSnapshot {
+    /// Unique ID of the snapshot, which must match the arena's journal.
+    id: u64,
+    /// Length of `polytopes`; any polytopes beyond this were created since.
+    len: usize,
+    pieces: Vec<PolytopeId>,
+    cut_count: usize,
+    free: Vec<PolytopeId>,
+    slice_log_len: usize,
+}
+
+/// Original state of polytopes modified since a snapshot was taken.
+#[derive(Debug, Clone)]
+struct Journal<N: Real> {
+    snapshot_id: u64,
+    len: usize,
+    saved: HashMap<PolytopeId, Option<Polytope<N>>>,
+}
+impl<N: Real> Journal<N> {
+    fn save(&mut self, id: PolytopeId, polytope: Option<&Polytope<N>>) {
+        if id.0 as usize >= self.len {
+            return;
+        }
+        self.saved.entry(id).or_insert_with(|| {
+            polytope.map(|p| Polytope {
+                slice_result: SliceResult::Unknown,
+                ..p.clone()
+            })
+        });
     }
-    arena.polygons()
 }
 
-#[derive(Debug)]
-pub struct PolytopeArena {
-    polytopes: Vec<Option<Polytope>>,
-    root: PolytopeId,
+/// Arena of polytopes that can be sliced by hyperplanes, with coordinates of
+/// type `N`. Use [`PolytopeArena64`] when `f32` is not precise enough, such as
+/// for many nearly-parallel cuts.
+#[derive(Debug, Clone)]
+pub struct PolytopeArena<N: Real = f32> {
+    polytopes: Vec<Option<Polytope<N>>>,
+    /// Root polytope of each piece, indexed by [`PieceId`].
+    pieces: Vec<PolytopeId>,
+    /// Number of cuts made so far. Every polytope created by a cut is tagged
+    /// with the index of that cut.
+    cut_count: usize,
+    /// Empty slots in `polytopes`, which are reused before allocating new ones.
+    free: Vec<PolytopeId>,
+    /// Tolerances used when slicing.
+    slice_options: SliceOptions,
+    /// Statistics for each slice, if [`SliceOptions::log_slices`] is set.
+    slice_log: Vec<SliceStats>,
+    /// Supporting hyperplane of each facet of each piece, computed when first
+    /// needed and cleared whenever the geometry changes.
+    facet_planes: OnceLock<Vec<Vec<Hyperplane<N>>>>,
+    /// Original state of every polytope modified since the last call to
+    /// [`PolytopeArena::snapshot()`].
+    journal: Option<Journal<N>>,
+    /// Whether to slice polytopes in parallel.
+    #[cfg(feature = "rayon")]
+    parallel: bool,
 }
-impl Index<PolytopeId> for PolytopeArena {
-    type Output = Polytope;
+/// [`PolytopeArena`] with `f64` coordinates.
+pub type PolytopeArena64 = PolytopeArena<f64>;
 
+impl<N: Real> Index<PolytopeId> for PolytopeArena<N> {
+    type Output = Polytope<N>;
+
+    // Indexing with the ID of a removed polytope is a bug, like indexing out of bounds.
+    #[allow(clippy::expect_used)]
     fn index(&self, index: PolytopeId) -> &Self::Output {
-        self.polytopes[index.0 as usize].as_ref().unwrap()
+        self.polytopes[index.0 as usize]
+            .as_ref()
+            .expect("polytope was removed")
     }
 }
-impl IndexMut<PolytopeId> for PolytopeArena {
+impl<N: Real> IndexMut<PolytopeId> for PolytopeArena<N> {
+    // Indexing with the ID of a removed polytope is a bug, like indexing out of bounds.
+    #[allow(clippy::expect_used)]
     fn index_mut(&mut self, index: PolytopeId) -> &mut Self::Output {
-        self.polytopes[index.0 as usize].as_mut().unwrap()
+        self.polytopes[index.0 as usize]
+            .as_mut()
+            .expect("polytope was removed")
     }
 }
-impl PolytopeArena {
-    pub fn new_cube(ndim: u8, radius: f32) -> Self {
+impl<N: Real> PolytopeArena<N> {
+    /// Constructs a hypercube whose inscribed ball has radius `radius`.
+    ///
+    /// Returns an error if the cube has more than
+    /// [`DEFAULT_MAX_ARENA_ELEMENTS`] elements. Use
+    /// [`PolytopeArena::new_cube_with_max_elements()`] to change the limit.
+    pub fn new_cube(ndim: u8, radius: N) -> Result<Self, ArenaError> {
+        Self::new_cube_with_max_elements(ndim, radius, DEFAULT_MAX_ARENA_ELEMENTS)
+    }
+    /// Constructs a hypercube whose inscribed ball has radius `radius`, or
+    /// returns an error if it has more than `max_elements` elements or too
+    /// many to fit in an arena at all.
+    pub fn new_cube_with_max_elements(
+        ndim: u8,
+        radius: N,
+        max_elements: usize,
+    ) -> Result<Self, ArenaError> {
         // Based on Andrey Astrelin's implementation of `GenCube()` in MPUlt
         // (FaceCuts.cs)
 
@@ -67,15 +964,27 @@ impl PolytopeArena {
         // | # |
         // • - •
         // ```
+        let element_count = 3_u32
+            .checked_pow(ndim as _)
+            .ok_or(ArenaError::DimensionTooLarge { ndim })?;
+        check_element_count(ndim, element_count as usize, max_elements)?;
 
         let mut ret = Self {
             polytopes: vec![],
-            root: PolytopeId(3_u32.pow(ndim as _) / 2), // center of the 3^NDIM cube
+            pieces: vec![PolytopeId(element_count / 2)], // center of the 3^NDIM cube
+            cut_count: 0,
+            free: vec![],
+            slice_options: SliceOptions::default(),
+            slice_log: vec![],
+            facet_planes: OnceLock::new(),
+            journal: None,
+            #[cfg(feature = "rayon")]
+            parallel: true,
         };
 
         let powers_of_3 = || std::iter::successors(Some(1), |x| Some(x * 3));
 
-        for i in 0..3_u32.pow(ndim as _) {
+        for i in 0..element_count {
             let rank = base_3_expansion(i, ndim)
                 .filter(|&digit| digit == 1)
                 .count() as u8;
@@ -83,7 +992,7 @@ impl PolytopeArena {
             let contents = if rank == 0 {
                 // This is a vertex.
                 let point = base_3_expansion(i, ndim)
-                    .map(|digit| (digit as f32 - 1.0) * radius)
+                    .map(|digit| N::from_f32(digit as f32 - 1.0) * radius)
                     .collect();
                 PolytopeContents::Point(point)
             } else {
@@ -94,10 +1003,7 @@ impl PolytopeArena {
                     .filter(|&(_, digit)| digit == 1)
                     // ... add two children along that axis.
                     .flat_map(|(power_of_3, _)| {
-                        [
-                            PolytopeId(i - power_of_3 as u32),
-                            PolytopeId(i + power_of_3 as u32),
-                        ]
+                        [PolytopeId(i - power_of_3), PolytopeId(i + power_of_3)]
                     })
                     .collect();
                 PolytopeContents::Branch { rank, children }
@@ -115,21 +1021,257 @@ impl PolytopeArena {
             ret.push(Polytope {
                 parents,
                 contents,
+                facet: None,
                 slice_result: SliceResult::Unknown,
             });
         }
 
-        ret
+        Ok(ret)
+    }
+
+    /// Constructs a regular simplex whose inscribed ball has radius `radius`.
+    ///
+    /// Returns an error if the simplex has more than
+    /// [`DEFAULT_MAX_ARENA_ELEMENTS`] elements. Use
+    /// [`PolytopeArena::new_simplex_with_max_elements()`] to change the limit.
+    pub fn new_simplex(ndim: u8, radius: N) -> Result<Self, ArenaError> {
+        Self::new_simplex_with_max_elements(ndim, radius, DEFAULT_MAX_ARENA_ELEMENTS)
+    }
+    /// Constructs a regular simplex whose inscribed ball has radius `radius`,
+    /// or returns an error if it has more than `max_elements` elements or too
+    /// many to fit in an arena at all.
+    // The centered basis vectors of a simplex are linearly independent.
+    #[allow(clippy::expect_used)]
+    pub fn new_simplex_with_max_elements(
+        ndim: u8,
+        radius: N,
+        max_elements: usize,
+    ) -> Result<Self, ArenaError> {
+        let vertex_count = ndim as usize + 1;
+        if vertex_count >= 32 {
+            return Err(ArenaError::DimensionTooLarge { ndim });
+        }
+        check_element_count(ndim, (1 << vertex_count) - 1, max_elements)?;
+
+        // The vertices of a regular simplex are the centered standard basis
+        // vectors in one dimension higher. Construct an orthonormal basis for
+        // the hyperplane they lie in.
+        let centered_basis_vector = |i: usize| -> Vector<N> {
+            (0..vertex_count)
+                .map(|j| {
+                    let x = if i == j { N::one() } else { N::zero() };
+                    x - N::one() / N::from_f32(vertex_count as f32)
+                })
+                .collect()
+        };
+        let mut basis: Vec<Vector<N>> = vec![];
+        for i in 0..ndim as usize {
+            let v = basis
+                .iter()
+                .fold(centered_basis_vector(i), |v, b| v.reject_from(b));
+            basis.push(v.normalized().expect("degenerate simplex"));
+        }
+        // The circumradius of a regular simplex is NDIM times its inradius.
+        let verts: Vec<Vector<N>> = (0..vertex_count)
+            .map(|i| {
+                let v = centered_basis_vector(i);
+                let v: Vector<N> = basis.iter().map(|b| v.dot(b)).collect();
+                v.normalized().expect("degenerate simplex") * (radius * N::from_f32(ndim as f32))
+            })
+            .collect();
+
+        // Every nonempty subset of the vertices is an element of the simplex,
+        // so represent each element by a bitmask. The element with bitmask
+        // `m` has ID `m - 1`.
+        let full_mask = (1_u32 << vertex_count) - 1;
+        let mut ret = Self {
+            polytopes: vec![],
+            pieces: vec![PolytopeId(full_mask - 1)],
+            cut_count: 0,
+            free: vec![],
+            slice_options: SliceOptions::default(),
+            slice_log: vec![],
+            facet_planes: OnceLock::new(),
+            journal: None,
+            #[cfg(feature = "rayon")]
+            parallel: true,
+        };
+        for mask in 1..=full_mask {
+            let bits = || (0..vertex_count).map(|i| 1 << i);
+            let contents = if mask.count_ones() == 1 {
+                PolytopeContents::Point(verts[mask.trailing_zeros() as usize].clone())
+            } else {
+                PolytopeContents::Branch {
+                    rank: mask.count_ones() as u8 - 1,
+                    children: bits()
+                        .filter(|bit| mask & bit != 0)
+                        .map(|bit| PolytopeId((mask & !bit) - 1))
+                        .collect(),
+                }
+            };
+            let parents = bits()
+                .filter(|bit| mask & bit == 0)
+                .map(|bit| PolytopeId((mask | bit) - 1))
+                .collect();
+            ret.push(Polytope {
+                parents,
+                contents,
+                facet: None,
+                slice_result: SliceResult::Unknown,
+            });
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns statistics for each slice so far, or an empty slice if
+    /// [`SliceOptions::log_slices`] is not set.
+    pub fn slice_log(&self) -> &[SliceStats] {
+        &self.slice_log
+    }
+
+    /// Saves the current state of the arena so that it can be restored later
+    /// using [`PolytopeArena::restore()`].
+    ///
+    /// This is much cheaper than cloning the arena: from then on, the arena
+    /// keeps a copy of each polytope just before it is first modified, so
+    /// the cost is proportional to the number of polytopes that change.
+    /// Taking a new snapshot invalidates the previous one.
+    pub fn snapshot(&mut self) -> ArenaSnapshot {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed);
+        self.journal = Some(Journal {
+            snapshot_id: id,
+            len: self.polytopes.len(),
+            saved: HashMap::new(),
+        });
+        ArenaSnapshot {
+            id,
+            len: self.polytopes.len(),
+            pieces: self.pieces.clone(),
+            cut_count: self.cut_count,
+            free: self.free.clone(),
+            slice_log_len: self.slice_log.len(),
+        }
+    }
+    /// Restores the arena to the state it was in when `snapshot` was taken.
+    /// The snapshot remains valid, so it can be restored again later.
+    ///
+    /// Returns an error if `snapshot` is not the most recent snapshot of this
+    /// arena (or a clone of it).
+    pub fn restore(&mut self, snapshot: &ArenaSnapshot) -> Result<(), ArenaError> {
+        let journal = match &mut self.journal {
+            Some(journal) if journal.snapshot_id == snapshot.id => journal,
+            _ => return Err(ArenaError::StaleSnapshot),
+        };
+        self.polytopes.resize_with(snapshot.len, || None);
+        for (id, polytope) in journal.saved.drain() {
+            self.polytopes[id.0 as usize] = polytope;
+        }
+        self.pieces = snapshot.pieces.clone();
+        self.cut_count = snapshot.cut_count;
+        self.free = snapshot.free.clone();
+        self.slice_log.truncate(snapshot.slice_log_len);
+        self.facet_planes.take();
+        Ok(())
+    }
+
+    /// Sets the tolerances used when slicing the arena.
+    #[must_use]
+    pub fn with_slice_options(mut self, options: SliceOptions) -> Self {
+        self.slice_options = options;
+        #[cfg(feature = "rayon")]
+        {
+            self.parallel = options.parallel;
+        }
+        self
+    }
+    /// Returns the distance within which a point at distance `scale` from the
+    /// origin is considered to be on a hyperplane. See [`Tolerance::eps()`].
+    fn tolerance(&self, scale: N) -> N {
+        let Tolerance { abs, rel } = self.slice_options.tolerance;
+        N::from_f32(abs) + N::from_f32(rel) * scale
+    }
+    /// Converts the coordinates of the arena to a different floating-point
+    /// type, such as to measure a [`PolytopeArena64`] using methods that are
+    /// only available for `f32`.
+    // Conversion between float types cannot fail.
+    #[allow(clippy::expect_used)]
+    pub fn cast<M: Real>(&self) -> PolytopeArena<M> {
+        let cast_polytope = |p: &Polytope<N>| Polytope {
+            parents: p.parents.clone(),
+            contents: match &p.contents {
+                PolytopeContents::Point(point) => PolytopeContents::Point(
+                    point
+                        .iter()
+                        .map(|x| M::from(x).expect("float conversion failed"))
+                        .collect(),
+                ),
+                PolytopeContents::Branch { rank, children } => PolytopeContents::Branch {
+                    rank: *rank,
+                    children: children.clone(),
+                },
+            },
+            facet: p.facet,
+            slice_result: p.slice_result,
+        };
+        PolytopeArena {
+            polytopes: self
+                .polytopes
+                .iter()
+                .map(|p| p.as_ref().map(cast_polytope))
+                .collect(),
+            pieces: self.pieces.clone(),
+            cut_count: self.cut_count,
+            free: self.free.clone(),
+            slice_options: self.slice_options,
+            slice_log: self.slice_log.clone(),
+            facet_planes: OnceLock::new(),
+            journal: None,
+            #[cfg(feature = "rayon")]
+            parallel: self.parallel,
+        }
     }
 
-    fn push(&mut self, polytope: Polytope) -> PolytopeId {
-        self.polytopes.push(Some(polytope));
-        PolytopeId(self.polytopes.len() as u32 - 1)
+    // Like `Vec`, an arena that outgrows its index type is a capacity overflow.
+    #[allow(clippy::expect_used)]
+    fn push(&mut self, polytope: Polytope<N>) -> PolytopeId {
+        match self.free.pop() {
+            Some(id) => {
+                self.save_for_undo(id);
+                self.polytopes[id.0 as usize] = Some(polytope);
+                id
+            }
+            None => {
+                let id = u32::try_from(self.polytopes.len())
+                    .expect("polytope arena cannot hold more than 2^32 polytopes");
+                self.polytopes.push(Some(polytope));
+                PolytopeId(id)
+            }
+        }
+    }
+    /// Records the current state of a polytope, if it hasn't been recorded
+    /// since the last snapshot.
+    fn save_for_undo(&mut self, id: PolytopeId) {
+        if let Some(journal) = &mut self.journal {
+            journal.save(id, self.polytopes[id.0 as usize].as_ref());
+        }
     }
-    fn push_point(&mut self, point: Vector<f32>) -> PolytopeId {
+    /// Records the current state of every polytope, for operations that
+    /// modify too much of the arena to track individually.
+    fn save_all_for_undo(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            for (i, polytope) in self.polytopes.iter().enumerate() {
+                journal.save(PolytopeId(i as u32), polytope.as_ref());
+            }
+        }
+    }
+
+    fn push_point(&mut self, point: Vector<N>) -> PolytopeId {
         self.push(Polytope {
             parents: smallvec![],
             contents: PolytopeContents::Point(point),
+            facet: None,
             slice_result: SliceResult::Unknown,
         })
     }
@@ -148,10 +1290,12 @@ impl PolytopeArena {
                 rank,
                 children: children.clone(),
             },
+            facet: None,
             slice_result: SliceResult::Unknown,
         });
 
         for &child in &children {
+            self.save_for_undo(child);
             self[child].parents.push(ret);
             debug_assert_eq!(
                 self[child].rank() + 1,
@@ -162,9 +1306,11 @@ impl PolytopeArena {
         ret
     }
     fn add_child(&mut self, parent: PolytopeId, child: PolytopeId) {
+        self.save_for_undo(parent);
+        self.save_for_undo(child);
         match &mut self[parent].contents {
             PolytopeContents::Point(_) => panic!("cannot add child to point"),
-            PolytopeContents::Branch { rank, children } => {
+            PolytopeContents::Branch { children, .. } => {
                 children.push(child);
                 self[child].parents.push(parent);
                 debug_assert_eq!(self[parent].rank(), self[child].rank() + 1);
@@ -172,219 +1318,3508 @@ impl PolytopeArena {
         }
     }
 
-    pub fn polygons(&self) -> Vec<Polygon> {
-        self.polytopes
-            .iter()
-            .filter_map(|x| x.as_ref())
-            .filter(|p| p.rank() == 2)
-            // For each polygon ...
-            .map(|p| {
-                let mut verts = Vec::with_capacity(p.children().len());
-
-                // Make an adjacency list for each vertex.
-                let mut edges: HashMap<PolytopeId, SmallVec<[PolytopeId; 2]>> = HashMap::new();
-                for (v1, v2) in p
-                    .children()
-                    .iter()
-                    .map(|&edge| self[edge].children())
-                    .flat_map(|ch| [(ch[0], ch[1]), (ch[1], ch[0])])
-                {
-                    edges.entry(v1).or_default().push(v2);
-                }
+    /// Returns every polygon in the arena, across all pieces.
+    ///
+    /// In a 2D arena, each piece is itself a polygon, so this returns the
+    /// outline of each piece with its vertices in cyclic order.
+    pub fn polygons(&self) -> Result<Vec<Polygon>, PolygonError> {
+        Ok(self.mesh()?.to_polygons())
+    }
+    /// Returns every polygon in the arena as an indexed mesh, with one vertex
+    /// per vertex in the arena.
+    ///
+    /// Polygons with area less than [`SliceOptions::min_polygon_area`] are
+    /// dropped, along with any vertices that only they use.
+    pub fn mesh(&self) -> Result<Mesh, PolygonError> {
+        Ok(self.filtered_mesh()?.mesh)
+    }
+    /// Returns the mesh of the arena along with the face that each polygon
+    /// comes from. See [`PolytopeArena::mesh()`].
+    fn filtered_mesh(&self) -> Result<FilteredMesh, PolygonError> {
+        let (mut mesh, mut faces) = self.unfiltered_mesh()?;
+        let keep = mesh.polygons_with_min_area(self.slice_options.min_polygon_area);
+        let dropped = mesh.retain_polygons(&keep);
+        retain_by_mask(&mut faces, &keep);
+        Ok(FilteredMesh {
+            mesh,
+            faces,
+            dropped,
+        })
+    }
+    /// Returns every polygon in the arena as an indexed mesh, including
+    /// slivers, along with the face that each polygon comes from.
+    fn unfiltered_mesh(&self) -> Result<(Mesh, Vec<PolytopeId>), PolygonError> {
+        enter_span!(DEBUG, "mesh");
 
-                let first_edge = p.children()[0];
-                let first_vertex = self[first_edge].children()[0];
-                let mut prev = first_vertex;
-                let mut current = self[first_edge].children()[1];
-                verts.push(self[current].unwrap_point().clone());
-                while current != first_vertex {
-                    let new = edges
-                        .get(&current)
-                        .unwrap()
+        let mut mesh = Mesh::default();
+        let mut vertex_indices: HashMap<PolytopeId, u32> = HashMap::new();
+        // Find the vertices of each face in parallel, and then number them in
+        // order so that the mesh doesn't depend on scheduling.
+        let faces: Vec<PolytopeId> = self.faces().collect();
+        let face_verts = par_map(self.is_parallel(), &faces, |&face| {
+            self.polygon_vertices(face)
+        });
+        for (&face, verts) in faces.iter().zip(face_verts) {
+            let polygon = verts?
+                .into_iter()
+                .map(|v| {
+                    *vertex_indices.entry(v).or_insert_with(|| {
+                        mesh.verts.push(self[v].unwrap_point().round_to_f32());
+                        mesh.verts.len() as u32 - 1
+                    })
+                })
+                .collect();
+            mesh.polygons.push(polygon);
+            mesh.facets.push(self[face].facet);
+        }
+        trace_event!(
+            DEBUG,
+            verts = mesh.verts.len(),
+            polygons = mesh.polygons.len(),
+        );
+        Ok((mesh, faces))
+    }
+    fn faces(&self) -> impl '_ + Iterator<Item = PolytopeId> {
+        self.elements_of_rank(2)
+    }
+    /// Returns the polygons on the boundary of a single piece.
+    ///
+    /// Faces created by a cut are shared between the pieces on either side of
+    /// it, so they are returned for both pieces.
+    pub fn polygons_of_piece(&self, piece: PieceId) -> Result<Vec<Polygon>, PolygonError> {
+        self.descendants(self.pieces[piece.0 as usize])
+            .into_iter()
+            .filter(|&p| self[p].rank() == 2)
+            .map(|p| self.polygon(p))
+            .collect()
+    }
+    /// Returns every cell (rank-3 element) in the arena. Polygon indices refer
+    /// to [`Mesh::polygons`] in the output of [`PolytopeArena::mesh()`], so
+    /// polygons that it drops are not included. Cells without vertices, which
+    /// only occur in a corrupted arena, are skipped.
+    pub fn cells(&self) -> Result<Vec<CellGeometry>, PolygonError> {
+        let polygon_indices: HashMap<PolytopeId, u32> =
+            self.filtered_mesh()?.faces.into_iter().zip(0..).collect();
+        let cells = self
+            .elements_of_rank(3)
+            .filter_map(|id| {
+                let descendants = self.descendants(id);
+                let mut polygons: Vec<u32> = descendants
+                    .iter()
+                    .filter_map(|p| polygon_indices.get(p).copied())
+                    .collect();
+                polygons.sort_unstable();
+                let verts: Vec<Vector<f32>> = descendants
+                    .iter()
+                    .filter(|&&p| self[p].rank() == 0)
+                    .map(|&p| self[p].unwrap_point().round_to_f32())
+                    .collect();
+                Some(CellGeometry {
+                    id,
+                    polygons,
+                    centroid: crate::centroid(&verts)?,
+                })
+            })
+            .collect();
+        Ok(cells)
+    }
+    /// Returns a polytope and all of its descendants, without duplicates.
+    fn descendants(&self, p: PolytopeId) -> Vec<PolytopeId> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![p];
+        let mut ret = vec![];
+        while let Some(p) = stack.pop() {
+            if seen.insert(p) {
+                ret.push(p);
+                stack.extend(self[p].children().iter().rev());
+            }
+        }
+        ret
+    }
+    /// Returns the positions of the vertices of a polytope, without duplicates.
+    fn vertices_of(&self, p: PolytopeId) -> Vec<Vector<N>> {
+        self.descendants(p)
+            .into_iter()
+            .filter_map(|p| match &self[p].contents {
+                PolytopeContents::Point(point) => Some(point.clone()),
+                PolytopeContents::Branch { .. } => None,
+            })
+            .collect()
+    }
+    /// Returns every vertex in the arena, along with its position.
+    pub fn vertices(&self) -> impl '_ + Iterator<Item = (PolytopeId, &Vector<N>)> {
+        self.elements_of_rank(0)
+            .map(|id| (id, self[id].unwrap_point()))
+    }
+    /// Returns every polytope of a given rank in the arena, in order of ID.
+    pub fn elements_of_rank(&self, rank: u8) -> impl '_ + Iterator<Item = PolytopeId> {
+        (0..self.polytopes.len() as u32).map(PolytopeId).filter(
+            move |&id| matches!(&self.polytopes[id.0 as usize], Some(p) if p.rank() == rank),
+        )
+    }
+    /// Returns the endpoints of every edge in the arena, in order of edge ID.
+    /// Edges without exactly two endpoints, which only occur in a corrupted
+    /// arena, are skipped. See [`PolytopeArena::validate()`].
+    pub fn edges(&self) -> impl '_ + Iterator<Item = [PolytopeId; 2]> {
+        self.elements_of_rank(1)
+            .filter_map(|edge| match self[edge].children() {
+                &[a, b] => Some([a, b]),
+                _ => None,
+            })
+    }
+    /// Returns the children of a polytope, which are the elements of its
+    /// boundary with rank one lower. Vertices have no children.
+    pub fn children_of(&self, id: PolytopeId) -> &[PolytopeId] {
+        self[id].children()
+    }
+    /// Returns the parents of a polytope, which are the elements with rank one
+    /// higher that it is part of the boundary of. Pieces have no parents.
+    pub fn parents_of(&self, id: PolytopeId) -> &[PolytopeId] {
+        &self[id].parents
+    }
+    /// Returns every pair of facets that share a ridge, which is an element
+    /// two ranks below the pieces. Each pair is sorted, and pairs are sorted
+    /// and unique.
+    ///
+    /// Facets of different pieces may be adjacent, such as where a cut meets
+    /// the boundary of the original shape.
+    pub fn facet_adjacency(&self) -> Vec<[PolytopeId; 2]> {
+        let Some(ridge_rank) = self.max_rank().and_then(|r| r.checked_sub(2)) else {
+            return vec![];
+        };
+        let mut ret: Vec<[PolytopeId; 2]> = self
+            .elements_of_rank(ridge_rank)
+            .flat_map(|ridge| {
+                let facets = self.parents_of(ridge);
+                facets.iter().enumerate().flat_map(move |(i, &a)| {
+                    facets[i + 1..]
                         .iter()
-                        .copied()
-                        .find(|&v| v != prev)
-                        .expect("invalid polygon");
-                    prev = current;
-                    current = new;
-                    verts.push(self[current].unwrap_point().clone());
+                        .map(move |&b| if a < b { [a, b] } else { [b, a] })
+                })
+            })
+            .collect();
+        ret.sort();
+        ret.dedup();
+        ret
+    }
+    /// Returns the facets that share a ridge with `facet`, in order of ID.
+    pub fn adjacent_facets(&self, facet: PolytopeId) -> Vec<PolytopeId> {
+        let mut ret: Vec<PolytopeId> = self
+            .children_of(facet)
+            .iter()
+            .flat_map(|&ridge| self.parents_of(ridge))
+            .copied()
+            .filter(|&p| p != facet)
+            .collect();
+        ret.sort();
+        ret.dedup();
+        ret
+    }
+    fn max_rank(&self) -> Option<u8> {
+        self.polytopes.iter().flatten().map(|p| p.rank()).max()
+    }
+    /// Returns the IDs of all pieces in the arena.
+    pub fn pieces(&self) -> Vec<PieceId> {
+        (0..self.pieces.len() as u32).map(PieceId).collect()
+    }
+    /// Returns the abstract incidence lattice of the arena, with the elements
+    /// of each rank numbered in order of ID.
+    pub fn incidence_lattice(&self) -> Lattice {
+        let max_rank = self.max_rank();
+        let ids_by_rank: Vec<Vec<PolytopeId>> = max_rank
+            .map_or(0..0, |max_rank| 0..max_rank + 1)
+            .map(|rank| self.elements_of_rank(rank).collect())
+            .collect();
+        let index: HashMap<PolytopeId, u32> = ids_by_rank
+            .iter()
+            .flat_map(|ids| ids.iter().zip(0..))
+            .map(|(&id, i)| (id, i))
+            .collect();
+        Lattice::new(
+            ids_by_rank
+                .iter()
+                .map(|ids| {
+                    ids.iter()
+                        .map(|&id| self[id].children().iter().map(|c| index[c]).collect())
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    fn polygon(&self, face: PolytopeId) -> Result<Polygon, PolygonError> {
+        let verts = self.polygon_vertices(face)?;
+        Ok(Polygon {
+            verts: self.points_of(&verts),
+            facet: self[face].facet,
+        })
+    }
+    /// Returns the vertices of a polygon in order around its boundary.
+    fn polygon_vertices(&self, face: PolytopeId) -> Result<Vec<PolytopeId>, PolygonError> {
+        let edge_ids = self[face].children();
+        let mut verts = Vec::with_capacity(edge_ids.len());
+
+        let mut endpoints = Vec::with_capacity(edge_ids.len());
+        for &edge in edge_ids {
+            match *self[edge].children() {
+                [a, b] => endpoints.push([a, b]),
+                ref children => {
+                    return Err(PolygonError::InvalidEdge {
+                        face,
+                        edge,
+                        endpoints: children.len(),
+                    })
                 }
+            }
+        }
+        let Some(&[first_vertex, second_vertex]) = endpoints.first() else {
+            return Err(PolygonError::NoEdges { face });
+        };
 
-                Polygon { verts }
-            })
+        // Make an adjacency list for each vertex.
+        let mut edges: HashMap<PolytopeId, SmallVec<[PolytopeId; 2]>> = HashMap::new();
+        for &[v1, v2] in &endpoints {
+            edges.entry(v1).or_default().push(v2);
+            edges.entry(v2).or_default().push(v1);
+        }
+
+        let mut prev = first_vertex;
+        let mut current = second_vertex;
+        verts.push(current);
+        while current != first_vertex {
+            let next = edges[&current].iter().copied().find(|&v| v != prev);
+            // If we've taken more steps than there are edges, then we're stuck
+            // in a loop that doesn't include the first vertex.
+            let next = match next {
+                Some(v) if verts.len() < edge_ids.len() => v,
+                _ => {
+                    return Err(PolygonError::BrokenCycle {
+                        face,
+                        edges: edge_ids.to_vec(),
+                        partial_loop: self.points_of(&verts),
+                    })
+                }
+            };
+            prev = current;
+            current = next;
+            verts.push(current);
+        }
+
+        if verts.len() != edge_ids.len() {
+            return Err(PolygonError::MultipleComponents {
+                face,
+                edges: edge_ids.to_vec(),
+                partial_loop: self.points_of(&verts),
+            });
+        }
+
+        Ok(verts)
+    }
+    /// Returns the positions of some vertices, rounded to `f32`.
+    fn points_of(&self, verts: &[PolytopeId]) -> Vec<Vector<f32>> {
+        verts
+            .iter()
+            .map(|&v| self[v].unwrap_point().round_to_f32())
             .collect()
     }
 
-    pub fn slice_by_plane(&mut self, pole: &Vector<f32>) {
-        self.slice_polytope(self.root, pole);
+    /// Slices the polytope by the hyperplane through `pole` perpendicular to
+    /// it, keeping the side containing the origin. See
+    /// [`PolytopeArena::slice_by_plane()`].
+    pub fn slice_by_pole(&mut self, pole: &Vector<N>) -> SliceStats {
+        self.slice_by_plane(&Hyperplane::from_pole(pole))
+    }
+    /// Slices every piece by a hyperplane, keeping only the part inside it.
+    /// Pieces entirely outside the hyperplane are removed, which invalidates
+    /// existing [`PieceId`]s.
+    pub fn slice_by_plane(&mut self, plane: &Hyperplane<N>) -> SliceStats {
+        let stats = self.slice_all(plane, false);
+        let pieces = std::mem::take(&mut self.pieces);
+        self.pieces = pieces
+            .into_iter()
+            .filter(|&root| self[root].slice_result != SliceResult::Removed)
+            .collect();
+        self.finish_slice(false, stats);
+        stats
+    }
+    /// Slices every piece by a hyperplane, keeping both halves. Each piece
+    /// that straddles the hyperplane is split in two; the inside half keeps
+    /// its [`PieceId`] and the outside half gets a new one.
+    pub fn slice_into_pieces(&mut self, plane: &Hyperplane<N>) -> SliceStats {
+        let stats = self.slice_all(plane, true);
+        for i in 0..self.pieces.len() {
+            if let SliceResult::Modified {
+                outside: Some(outside),
+                ..
+            } = self[self.pieces[i]].slice_result
+            {
+                self.pieces.push(outside);
+            }
+        }
+        self.finish_slice(true, stats);
+        stats
+    }
+
+    /// Slices every piece by several hyperplanes, keeping only the part inside
+    /// all of them. Pieces entirely outside any hyperplane are removed, which
+    /// invalidates existing [`PieceId`]s.
+    ///
+    /// All the new facets are tagged as a single cut, so a faceted
+    /// approximation of a curved surface (see
+    /// [`Hyperplane::sphere_approximation()`]) is treated as one facet. The
+    /// returned statistics are summed over every hyperplane.
+    // Slicing is only cancelled by a progress callback.
+    #[allow(clippy::expect_used)]
+    pub fn slice_by_all(&mut self, planes: &[Hyperplane<N>]) -> SliceStats {
+        let result = self.slice_by_all_with_progress(planes, |_| ControlFlow::Continue(()));
+        result.expect("slicing cannot be cancelled without a callback")
+    }
+    /// Slices every piece by several hyperplanes like
+    /// [`PolytopeArena::slice_by_all()`], calling `progress` after each
+    /// hyperplane.
+    ///
+    /// If `progress` returns [`ControlFlow::Break`], slicing stops and
+    /// [`ArenaError::Cancelled`] is returned. The hyperplanes sliced by so far
+    /// remain as a single cut, and the arena is left in a valid state.
+    pub fn slice_by_all_with_progress(
+        &mut self,
+        planes: &[Hyperplane<N>],
+        mut progress: impl FnMut(SliceProgress) -> ControlFlow<()>,
+    ) -> Result<SliceStats, ArenaError> {
+        let mut total = SliceStats::default();
+        let mut cancelled = false;
+        for (i, plane) in planes.iter().enumerate() {
+            let stats = self.slice_all(plane, false);
+            let pieces = std::mem::take(&mut self.pieces);
+            self.pieces = pieces
+                .into_iter()
+                .filter(|&root| self[root].slice_result != SliceResult::Removed)
+                .collect();
+            self.clean_up_slice(false);
+            total.removed += stats.removed;
+            total.kept += stats.kept;
+            total.modified += stats.modified;
+            total.created += stats.created;
+            if progress(self.progress(i + 1, planes.len())).is_break() {
+                cancelled = true;
+                break;
+            }
+        }
+        total.new_facet = self.pieces.iter().find_map(|&root| {
+            self[root]
+                .children()
+                .iter()
+                .copied()
+                .find(|&facet| self[facet].facet == Some(self.cut_count))
+        });
+        self.finish_cut(total);
+        match cancelled {
+            true => Err(ArenaError::Cancelled),
+            false => Ok(total),
+        }
+    }
+    fn progress(&self, planes_completed: usize, plane_count: usize) -> SliceProgress {
+        SliceProgress {
+            planes_completed,
+            plane_count,
+            polytope_count: self.polytopes.len() - self.free.len(),
+        }
+    }
 
-        for polytope in &mut self.polytopes {
+    fn finish_slice(&mut self, keep_outside: bool, stats: SliceStats) {
+        self.clean_up_slice(keep_outside);
+        self.finish_cut(stats);
+    }
+    fn finish_cut(&mut self, stats: SliceStats) {
+        self.cut_count += 1;
+        if self.slice_options.log_slices {
+            self.slice_log.push(stats);
+        }
+    }
+    fn clean_up_slice(&mut self, keep_outside: bool) {
+        self.facet_planes.take();
+        for (i, polytope) in self.polytopes.iter_mut().enumerate() {
             if let Some(p) = polytope {
                 match p.slice_result {
                     SliceResult::Unknown => {
                         panic!("orphans in polytope arena")
                     }
                     // Remove dead polytopes.
-                    SliceResult::Removed => *polytope = None,
+                    SliceResult::Removed if !keep_outside => {
+                        if let Some(journal) = &mut self.journal {
+                            journal.save(PolytopeId(i as u32), Some(p));
+                        }
+                        *polytope = None;
+                        self.free.push(PolytopeId(i as u32));
+                    }
                     // Reset slice results.
-                    SliceResult::Kept | SliceResult::Modified(_) => {
+                    SliceResult::Kept | SliceResult::Removed | SliceResult::Modified { .. } => {
                         p.slice_result = SliceResult::Unknown
                     }
                 }
             }
         }
+
+        // Remove references to dead parents.
+        if !keep_outside {
+            let live: Vec<bool> = self.polytopes.iter().map(Option::is_some).collect();
+            for (i, polytope) in self.polytopes.iter_mut().enumerate() {
+                if let Some(p) = polytope {
+                    if p.parents.iter().any(|parent| !live[parent.0 as usize]) {
+                        if let Some(journal) = &mut self.journal {
+                            journal.save(PolytopeId(i as u32), Some(p));
+                        }
+                        p.parents.retain(|parent| live[parent.0 as usize]);
+                    }
+                }
+            }
+        }
+
+        if self.slice_options.debug_verify {
+            if let Err(defects) = self.validate() {
+                panic!("polytope arena is invalid after slicing: {defects:?}");
+            }
+        }
     }
 
-    fn slice_polytope(&mut self, p: PolytopeId, pole: &Vector<f32>) -> SliceResult {
-        if self[p].slice_result != SliceResult::Unknown {
-            return self[p].slice_result;
+    /// Checks the internal consistency of the arena, returning every defect
+    /// found.
+    pub fn validate(&self) -> Result<(), Vec<ArenaDefect>> {
+        let mut defects = vec![];
+        let exists = |id: PolytopeId| matches!(self.polytopes.get(id.0 as usize), Some(Some(_)));
+
+        for (i, root) in self.pieces.iter().enumerate() {
+            if !exists(*root) {
+                defects.push(ArenaDefect::MissingRoot {
+                    piece: PieceId(i as u32),
+                });
+            }
         }
 
-        let ret = match &self[p].contents {
-            PolytopeContents::Point(point) => {
-                if (pole - point).dot(pole) > -EPSILON {
-                    SliceResult::Kept
-                } else {
-                    SliceResult::Removed
+        for id in (0..self.polytopes.len() as u32).map(PolytopeId) {
+            if !exists(id) {
+                continue;
+            }
+            let p = &self[id];
+
+            for &parent in &p.parents {
+                if !exists(parent) {
+                    defects.push(ArenaDefect::DanglingReference {
+                        from: id,
+                        to: parent,
+                    });
+                } else if !self[parent].children().contains(&id) {
+                    defects.push(ArenaDefect::MissingChildLink { parent, child: id });
                 }
             }
-            PolytopeContents::Branch { rank, children } => {
-                let rank = *rank;
-                let mut intersection_boundary = vec![];
-                let old_children = children.clone();
-                let new_children: SmallVec<[PolytopeId; 4]> = old_children
+            let mut children_ok = true;
+            for &child in p.children() {
+                if !exists(child) {
+                    defects.push(ArenaDefect::DanglingReference {
+                        from: id,
+                        to: child,
+                    });
+                    children_ok = false;
+                    continue;
+                }
+                if !self[child].parents.contains(&id) {
+                    defects.push(ArenaDefect::MissingParentLink { parent: id, child });
+                }
+                if self[child].rank() + 1 != p.rank() {
+                    defects.push(ArenaDefect::RankMismatch { parent: id, child });
+                    children_ok = false;
+                }
+            }
+
+            match p.rank() {
+                1 if p.children().len() != 2 => defects.push(ArenaDefect::WrongEdgeEndpoints {
+                    edge: id,
+                    count: p.children().len(),
+                }),
+                2 if p.children().len() < 3 => defects.push(ArenaDefect::TooFewEdges {
+                    face: id,
+                    count: p.children().len(),
+                }),
+                // Only walk the face if its edges are valid.
+                2 if children_ok
+                    && p.children()
+                        .iter()
+                        .all(|&edge| self[edge].children().len() == 2) =>
+                {
+                    if let Err(e) = self.polygon_vertices(id) {
+                        defects.push(ArenaDefect::InvalidFace(e));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if defects.is_empty() {
+            Ok(())
+        } else {
+            Err(defects)
+        }
+    }
+
+    /// Merges vertices within `eps` of each other, then merges or removes any
+    /// edges, faces, etc. that become duplicated or degenerate as a result.
+    ///
+    /// Cuts that pass through existing vertices create new vertices
+    /// approximately equal to the old ones, so this should be called after
+    /// slicing.
+    pub fn weld_vertices(&mut self, eps: N) {
+        self.facet_planes.take();
+        self.save_all_for_undo();
+
+        // Maps each merged polytope to the polytope it was merged into, which
+        // is never itself merged.
+        let mut merged: HashMap<PolytopeId, PolytopeId> = HashMap::new();
+        let mut removed: HashSet<PolytopeId> = HashSet::new();
+
+        // Merge nearby vertices. See `weld_points()`.
+        let verts: Vec<PolytopeId> = self.elements_of_rank(0).collect();
+        let points = verts
+            .iter()
+            .map(|&v| self[v].unwrap_point())
+            .collect::<Vec<_>>();
+        for (v, representative) in std::iter::zip(&verts, weld_points(&points, eps)) {
+            if verts[representative] != *v {
+                merged.insert(*v, verts[representative]);
+            }
+        }
+
+        // Merge polytopes with identical children, and remove polytopes with
+        // too few children, one rank at a time.
+        let max_rank = self.pieces.iter().map(|&root| self[root].rank()).max();
+        for rank in 1..=max_rank.unwrap_or(0) {
+            let mut by_children: HashMap<Vec<PolytopeId>, PolytopeId> = HashMap::new();
+            for p in self.elements_of_rank(rank).collect::<Vec<_>>() {
+                let mut seen = HashSet::new();
+                let children: SmallVec<[PolytopeId; 4]> = self[p]
+                    .children()
                     .iter()
-                    .copied()
-                    .filter(|&child| match self.slice_polytope(child, pole) {
-                        SliceResult::Unknown => panic!("polytope didn't get slice result computed"),
-                        SliceResult::Kept => true,
-                        SliceResult::Removed => false,
-                        SliceResult::Modified(intersection) => {
-                            intersection_boundary.push(intersection);
-                            true
-                        }
-                    })
+                    .map(|child| *merged.get(child).unwrap_or(child))
+                    .filter(|child| !removed.contains(child) && seen.insert(*child))
                     .collect();
 
-                let removed = new_children.len() == 0;
-                *self[p].unwrap_children_mut() = new_children;
+                // A rank-N polytope needs at least N+1 facets.
+                if children.len() <= rank as usize {
+                    removed.insert(p);
+                    continue;
+                }
 
-                if removed {
-                    SliceResult::Removed
-                } else if old_children
-                    .iter()
-                    .all(|&child| self[child].slice_result == SliceResult::Kept)
-                {
-                    SliceResult::Kept
-                } else {
-                    let new_child = if rank == 1 {
-                        let a = self[old_children[0]].unwrap_point();
-                        let b = self[old_children[1]].unwrap_point();
-                        let a_distance = (pole - a).dot(pole);
-                        let b_distance = -(pole - b).dot(pole);
-                        let sum = a_distance + b_distance;
-                        self.push_point((b * a_distance + a * b_distance) / sum)
-                    } else {
-                        self.push_polytope(intersection_boundary)
-                    };
-                    self[new_child].slice_result = SliceResult::Kept;
-                    self.add_child(p, new_child);
-                    SliceResult::Modified(new_child)
+                let mut key = children.to_vec();
+                key.sort_unstable_by_key(|child| child.0);
+                *self[p].unwrap_children_mut() = children;
+                match by_children.entry(key) {
+                    Entry::Occupied(e) => {
+                        merged.insert(p, *e.get());
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert(p);
+                    }
                 }
             }
-        };
-        self[p].slice_result = ret;
-        ret
-    }
-}
+        }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Polytope {
-    parents: SmallVec<[PolytopeId; 4]>,
-    contents: PolytopeContents,
-    slice_result: SliceResult,
-}
-impl Polytope {
-    fn rank(&self) -> u8 {
-        self.contents.rank()
-    }
-    fn unwrap_point(&self) -> &Vector<f32> {
-        match &self.contents {
-            PolytopeContents::Point(point) => point,
-            _ => panic!("expected point, got branch"),
+        self.pieces = self
+            .pieces
+            .iter()
+            .map(|root| *merged.get(root).unwrap_or(root))
+            .filter(|root| !removed.contains(root))
+            .collect();
+
+        // Delete everything that's no longer reachable and rebuild the parent
+        // lists.
+        let reachable: HashSet<PolytopeId> = self
+            .pieces
+            .iter()
+            .flat_map(|&root| self.descendants(root))
+            .collect();
+        for (i, polytope) in self.polytopes.iter_mut().enumerate() {
+            let id = PolytopeId(i as u32);
+            if polytope.is_some() && !reachable.contains(&id) {
+                *polytope = None;
+                self.free.push(id);
+            }
+        }
+        for p in self.polytopes.iter_mut().flatten() {
+            p.parents.clear();
+        }
+        for &p in &reachable {
+            for child in self[p].children().to_vec() {
+                self[child].parents.push(p);
+            }
         }
     }
-    fn children(&self) -> &[PolytopeId] {
-        match &self.contents {
-            PolytopeContents::Point(_) => &[],
-            PolytopeContents::Branch { children, .. } => children,
+    /// Returns the number of live polytopes of each rank, indexed by rank.
+    pub fn element_counts(&self) -> Vec<usize> {
+        let mut counts = vec![];
+        for p in self.polytopes.iter().flatten() {
+            let rank = p.rank() as usize;
+            if counts.len() <= rank {
+                counts.resize(rank + 1, 0);
+            }
+            counts[rank] += 1;
         }
+        counts
     }
-    fn unwrap_children_mut(&mut self) -> &mut SmallVec<[PolytopeId; 4]> {
-        match &mut self.contents {
-            PolytopeContents::Point(_) => panic!("expected brancch, got point"),
-            PolytopeContents::Branch { children, .. } => children,
+    /// Returns the alternating sum of the element counts of every rank except
+    /// the highest, which is the Euler characteristic of the boundary.
+    pub fn euler_characteristic(&self) -> isize {
+        let counts = self.element_counts();
+        counts[..counts.len().saturating_sub(1)]
+            .iter()
+            .zip([1, -1].into_iter().cycle())
+            .map(|(&count, sign)| count as isize * sign)
+            .sum()
+    }
+    /// Asserts that the arena consists of a single piece whose boundary has
+    /// the Euler characteristic of a sphere: 2 if the piece has odd rank or 0
+    /// if it has even rank.
+    #[track_caller]
+    pub fn assert_euler_ok(&self) {
+        assert_eq!(self.pieces.len(), 1, "expected a single piece");
+        let rank = self[self.pieces[0]].rank();
+        let expected = if rank % 2 == 1 { 2 } else { 0 };
+        assert_eq!(
+            self.euler_characteristic(),
+            expected,
+            "bad Euler characteristic for element counts {:?}",
+            self.element_counts(),
+        );
+    }
+
+    /// Renumbers the polytopes in the arena so that there are no empty slots,
+    /// preserving their order. Returns the old ID of each polytope, indexed by
+    /// its new ID.
+    ///
+    /// [`PieceId`]s are not affected.
+    pub fn compact(&mut self) -> Vec<PolytopeId> {
+        self.save_all_for_undo();
+        let old_ids: Vec<PolytopeId> = (0..self.polytopes.len() as u32)
+            .map(PolytopeId)
+            .filter(|id| self.polytopes[id.0 as usize].is_some())
+            .collect();
+        let new_ids: HashMap<PolytopeId, PolytopeId> = old_ids
+            .iter()
+            .zip(0..)
+            .map(|(&old, new)| (old, PolytopeId(new)))
+            .collect();
+        let remap = |ids: &mut SmallVec<[PolytopeId; 4]>| {
+            ids.retain(|id| new_ids.contains_key(id));
+            for id in ids {
+                *id = new_ids[id];
+            }
+        };
+
+        let mut polytopes: Vec<Option<Polytope<N>>> = std::mem::take(&mut self.polytopes)
+            .into_iter()
+            .flatten()
+            .map(Some)
+            .collect();
+        for p in polytopes.iter_mut().flatten() {
+            remap(&mut p.parents);
+            if let PolytopeContents::Branch { children, .. } = &mut p.contents {
+                remap(children);
+            }
+        }
+        self.polytopes = polytopes;
+        for root in &mut self.pieces {
+            *root = new_ids[root];
         }
+        self.free.clear();
+
+        old_ids
     }
-}
 
-#[derive(Debug, Clone, PartialEq)]
-enum PolytopeContents {
-    Point(Vector<f32>),
-    Branch {
-        rank: u8,
-        children: SmallVec<[PolytopeId; 4]>,
-    },
-}
-impl PolytopeContents {
-    fn rank(&self) -> u8 {
-        match self {
-            PolytopeContents::Point(_) => 0,
-            PolytopeContents::Branch { rank, .. } => *rank,
+    /// Computes the slice result of every polytope in the arena, slicing
+    /// polytopes in place so that they only contain the part inside the
+    /// hyperplane. If `keep_outside` is `true`, then a new polytope is also
+    /// constructed for the part outside the hyperplane.
+    ///
+    /// Polytopes are sliced one rank at a time, since each polytope's result
+    /// only depends on its children. With the `rayon` feature, the polytopes
+    /// of each rank are classified in parallel and then modified serially in
+    /// order of ID, so the result is the same either way.
+    fn slice_all(&mut self, plane: &Hyperplane<N>, keep_outside: bool) -> SliceStats {
+        enter_span!(DEBUG, "slice", cut = self.cut_count, ?plane);
+
+        let mut ids_by_rank: Vec<Vec<PolytopeId>> = vec![];
+        for (i, polytope) in self.polytopes.iter().enumerate() {
+            if let Some(p) = polytope {
+                let rank = p.rank() as usize;
+                if ids_by_rank.len() <= rank {
+                    ids_by_rank.resize(rank + 1, vec![]);
+                }
+                ids_by_rank[rank].push(PolytopeId(i as u32));
+            }
+        }
+
+        let mut stats = SliceStats::default();
+        for ids in &ids_by_rank {
+            for (&p, plan) in ids.iter().zip(self.slice_plans(ids, plane, keep_outside)) {
+                match plan {
+                    SlicePlan::Kept => stats.kept += 1,
+                    SlicePlan::Removed => stats.removed += 1,
+                    SlicePlan::Split { .. } => {
+                        stats.modified += 1;
+                        stats.created += if keep_outside { 2 } else { 1 };
+                    }
+                }
+                self[p].slice_result = self.apply_slice_plan(p, plan, keep_outside);
+            }
         }
+        stats.new_facet = self
+            .pieces
+            .iter()
+            .find_map(|&root| match self[root].slice_result {
+                SliceResult::Modified { intersection, .. } => Some(intersection),
+                _ => None,
+            });
+        trace_event!(DEBUG, %stats);
+        stats
     }
-}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct PolytopeId(u32);
+    fn slice_plans(
+        &self,
+        ids: &[PolytopeId],
+        plane: &Hyperplane<N>,
+        keep_outside: bool,
+    ) -> Vec<SlicePlan<N>> {
+        par_map(self.is_parallel(), ids, |&p| {
+            self.slice_plan(p, plane, keep_outside)
+        })
+    }
+    /// Returns whether to slice and extract polygons in parallel.
+    fn is_parallel(&self) -> bool {
+        #[cfg(feature = "rayon")]
+        return self.parallel;
+        #[cfg(not(feature = "rayon"))]
+        return false;
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Polygon {
-    pub verts: Vec<Vector<f32>>,
-}
+    /// Classifies a polytope based on the slice results of its children.
+    fn slice_plan(&self, p: PolytopeId, plane: &Hyperplane<N>, keep_outside: bool) -> SlicePlan<N> {
+        match &self[p].contents {
+            PolytopeContents::Point(point) => {
+                if plane.signed_distance(point) < self.tolerance(point.mag()) {
+                    SlicePlan::Kept
+                } else {
+                    SlicePlan::Removed
+                }
+            }
+            PolytopeContents::Branch { rank, children } => {
+                let mut any_kept = false;
+                let mut any_removed = false;
+                let mut any_modified = false;
+                for &child in children {
+                    match self[child].slice_result {
+                        SliceResult::Unknown => panic!("polytope didn't get slice result computed"),
+                        SliceResult::Kept => any_kept = true,
+                        SliceResult::Removed => any_removed = true,
+                        SliceResult::Modified { .. } => any_modified = true,
+                    }
+                }
 
-struct ConvexPolytope {
-    verts: Vec<Vector<f32>>,
-    faces: Vec<Vec<u32>>,
-}
+                if !any_kept && !any_modified {
+                    return SlicePlan::Removed;
+                } else if !any_removed && !any_modified {
+                    return SlicePlan::Kept;
+                }
 
-fn base_3_expansion(n: u32, digit_count: u8) -> impl Iterator<Item = u32> {
-    std::iter::successors(Some(n), |x| Some(x / 3))
-        .take(digit_count as _)
-        .map(|x| x % 3)
-}
+                // Only polytopes that are actually split need to allocate.
+                let mut outside_children: SmallVec<[PolytopeId; 4]> = smallvec![];
+                let mut intersection_boundary: SmallVec<[PolytopeId; 4]> = smallvec![];
+                for &child in children {
+                    match self[child].slice_result {
+                        SliceResult::Removed if keep_outside => outside_children.push(child),
+                        SliceResult::Modified {
+                            intersection,
+                            outside,
+                        } => {
+                            outside_children.extend(outside);
+                            intersection_boundary.push(intersection);
+                        }
+                        _ => (),
+                    }
+                }
+                let new_point = (*rank == 1).then(|| {
+                    let a = self[children[0]].unwrap_point();
+                    let b = self[children[1]].unwrap_point();
+                    let a_distance = plane.signed_distance(a);
+                    let b_distance = plane.signed_distance(b);
+                    let sum = a_distance - b_distance;
+                    (b * a_distance - a * b_distance) / sum
+                });
+                SlicePlan::Split {
+                    outside_children,
+                    intersection_boundary,
+                    new_point,
+                }
+            }
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Modifies a polytope according to its slice plan and returns its slice
+    /// result.
+    fn apply_slice_plan(
+        &mut self,
+        p: PolytopeId,
+        plan: SlicePlan<N>,
+        keep_outside: bool,
+    ) -> SliceResult {
+        match plan {
+            SlicePlan::Kept => SliceResult::Kept,
+            SlicePlan::Removed => SliceResult::Removed,
+            SlicePlan::Split {
+                mut outside_children,
+                intersection_boundary,
+                new_point,
+            } => {
+                let intersection = match new_point {
+                    Some(point) => self.push_point(point),
+                    None => self.push_polytope(intersection_boundary),
+                };
+                self[intersection].slice_result = SliceResult::Kept;
+                self[intersection].facet = Some(self.cut_count);
+                self.save_for_undo(p);
+                let mut children = std::mem::take(self[p].unwrap_children_mut());
+                children.retain(|child| self[*child].slice_result != SliceResult::Removed);
+                *self[p].unwrap_children_mut() = children;
+                self.add_child(p, intersection);
 
-    #[test]
-    fn test_cube() {
-        panic!();
+                // The intersection is shared by both halves.
+                let outside = keep_outside.then(|| {
+                    for &child in &outside_children {
+                        self.save_for_undo(child);
+                        self[child].parents.retain(|&mut parent| parent != p);
+                    }
+                    outside_children.push(intersection);
+                    let outside = self.push_polytope(outside_children);
+                    self[outside].slice_result = SliceResult::Removed;
+                    self[outside].facet = self[p].facet;
+                    outside
+                });
+                SliceResult::Modified {
+                    intersection,
+                    outside,
+                }
+            }
+        }
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
-enum SliceResult {
-    /// The slice result hasn't been computed yet.
-    #[default]
-    Unknown,
+impl PolytopeArena {
+    /// Constructs the convex hull of a set of points.
+    ///
+    /// The hull is constructed by duality: slicing by the polar hyperplane of
+    /// each point gives the polar dual of the hull, and slicing by the polar
+    /// hyperplane of each vertex of the dual gives the hull itself. Points in
+    /// the interior of the hull are ignored.
+    // There are more points than dimensions, so there is at least one point.
+    #[allow(clippy::expect_used)]
+    pub fn from_convex_hull(points: &[Vector<f32>]) -> Result<Self, HullError> {
+        let ndim = points.iter().map(|p| p.ndim()).max().unwrap_or(0);
+        if points.len() <= ndim as usize {
+            return Err(HullError::TooFewPoints {
+                count: points.len(),
+                ndim,
+            });
+        }
 
-    /// The entire polytope was kept by the slice.
-    Kept,
-    /// The entire polytope was removed by the slice.
-    Removed,
-    /// The polytope was modified by the slice, and this is the intersection of
-    /// the polytope and the slicing hyperplane.
-    Modified(PolytopeId),
+        // Center the points so that the origin is inside the hull.
+        let center = crate::centroid(points).expect("no points");
+        let offsets: Vec<Vector<f32>> = points.iter().map(|p| p - &center).collect();
+        let radius = offsets.iter().map(|p| p.mag()).fold(0.0, f32::max);
+        let options = SliceOptions::default();
+
+        // Check that the points span the space.
+        let mut basis: Vec<Vector<f32>> = vec![];
+        for p in &offsets {
+            let v = basis.iter().fold(p.clone(), |v, b| v.reject_from(b));
+            if v.mag() > options.tolerance.eps(radius) {
+                basis.extend(v.normalized());
+            }
+        }
+        if basis.len() < ndim as usize {
+            return Err(HullError::Degenerate);
+        }
+
+        let polar_facets = |poles: Vec<Vector<f32>>| -> Vec<Facet> {
+            poles
+                .into_iter()
+                .filter(|p| p.mag() > options.tolerance.eps(radius))
+                .enumerate()
+                .map(|(i, p)| Facet {
+                    pole: &p / p.mag2(),
+                    base_facet: i,
+                    transform: Matrix::ident(ndim),
+                    orbit_index: 0,
+                    group_element: None,
+                })
+                .collect()
+        };
+        let no_progress = &mut |_| ControlFlow::Continue(());
+        let dual = arena_from_facets(ndim, &polar_facets(offsets), &options, no_progress)?;
+        let dual_verts = dual.vertices().map(|(_, v)| v.clone()).collect();
+        let mut ret = arena_from_facets(ndim, &polar_facets(dual_verts), &options, no_progress)?;
+
+        for p in ret.polytopes.iter_mut().flatten() {
+            if let PolytopeContents::Point(point) = &mut p.contents {
+                *point = &*point + &center;
+            }
+        }
+        ret.facet_planes.take();
+        Ok(ret)
+    }
+
+    /// Returns a copy of the arena with every vertex transformed by `m`, such
+    /// as to re-orient or rescale a shape. Element IDs, pieces, and facet tags
+    /// are unchanged, so incidence and adjacency are preserved. To keep a
+    /// symmetry group acting on the shape, use [`Group::conjugated()`].
+    ///
+    /// Returns an error if `m` is not invertible, which would collapse the
+    /// arena. The slice log is kept, but any snapshot is invalidated.
+    pub fn transformed(&self, m: &Matrix<f32>) -> Result<Self, ArenaError> {
+        if !m.is_invertible() {
+            return Err(ArenaError::SingularMatrix);
+        }
+        let mut ret = self.clone();
+        ret.journal = None;
+        ret.facet_planes.take();
+        for polytope in ret.polytopes.iter_mut().flatten() {
+            if let PolytopeContents::Point(point) = &mut polytope.contents {
+                *point = m.transform(&*point);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Returns the vertices and faces of the arena, which should consist of a
+    /// single convex piece. In 3D, each face winds counterclockwise when
+    /// viewed from outside the polytope.
+    pub fn to_convex_polytope(&self) -> Result<ConvexPolytope, PolygonError> {
+        let mut mesh = self.mesh()?;
+        mesh.orient_outward();
+        let Mesh {
+            verts, polygons, ..
+        } = mesh;
+        Ok(ConvexPolytope {
+            verts,
+            faces: polygons,
+        })
+    }
+    /// Returns the total content (length, area, volume, etc.) of every piece in
+    /// the arena, measured in the rank of each piece.
+    pub fn volume(&self) -> f32 {
+        self.pieces()
+            .into_iter()
+            .map(|p| self.volume_of_piece(p))
+            .sum()
+    }
+    /// Returns the content of a single piece, measured in its own rank.
+    pub fn volume_of_piece(&self, piece: PieceId) -> f32 {
+        self.content(self.pieces[piece.0 as usize], &mut HashMap::new())
+    }
+    /// Returns the total content of the facets of every piece in the arena.
+    /// Faces created by [`PolytopeArena::slice_into_pieces()`] are counted
+    /// once for each piece that they bound.
+    pub fn surface_measure(&self) -> f32 {
+        let mut memo = HashMap::new();
+        self.pieces
+            .iter()
+            .flat_map(|&p| self[p].children())
+            .map(|&facet| self.content(facet, &mut memo))
+            .sum()
+    }
+    /// Returns the content of a convex polytope, measured in its own rank, by
+    /// summing cones from its centroid over each of its facets.
+    fn content(&self, p: PolytopeId, memo: &mut HashMap<PolytopeId, f32>) -> f32 {
+        if let Some(&ret) = memo.get(&p) {
+            return ret;
+        }
+        let ret = match &self[p].contents {
+            PolytopeContents::Point(_) => 1.0,
+            PolytopeContents::Branch { rank, children } => {
+                match crate::centroid(&self.vertices_of(p)) {
+                    Some(apex) => children
+                        .iter()
+                        .map(|&child| {
+                            let height =
+                                offset_from_affine_hull(&apex, &self.vertices_of(child)).mag();
+                            self.content(child, memo) * height / *rank as f32
+                        })
+                        .sum(),
+                    // Only a corrupted arena has a polytope with no vertices.
+                    None => 0.0,
+                }
+            }
+        };
+        memo.insert(p, ret);
+        ret
+    }
+    /// Returns the minimum and maximum corners of the axis-aligned bounding box
+    /// of the arena, or `None` if it has no vertices.
+    pub fn bounding_box(&self) -> Option<(Vector<f32>, Vector<f32>)> {
+        crate::bounding_box(&self.vertices().map(|(_, v)| v.clone()).collect::<Vec<_>>())
+    }
+    /// Returns the center and radius of a sphere containing the arena, or
+    /// `None` if it has no vertices. See [`crate::bounding_sphere()`].
+    pub fn bounding_sphere(&self) -> Option<(Vector<f32>, f32)> {
+        crate::bounding_sphere(&self.vertices().map(|(_, v)| v.clone()).collect::<Vec<_>>())
+    }
+    /// Partitions the elements of a rank into orbits under `group`, such as
+    /// to distinguish corners from edges of a puzzle. Elements are identified
+    /// by the centroids of their vertices.
+    ///
+    /// Each orbit is sorted by ID, and orbits are sorted by their first
+    /// element. If `group` doesn't map the arena onto itself, elements whose
+    /// images aren't in the arena are grouped only with those that are.
+    /// Elements without vertices, which only occur in a corrupted arena, are
+    /// not in any orbit.
+    pub fn element_orbits(&self, group: &Group, rank: u8) -> Vec<Vec<PolytopeId>> {
+        let elements: Vec<(PolytopeId, Vector<f32>)> = self
+            .elements_of_rank(rank)
+            .filter_map(|p| Some((p, crate::centroid(&self.vertices_of(p))?)))
+            .collect();
+        let radius = self.vertices().map(|(_, v)| v.mag()).fold(0.0, f32::max);
+        let eps = EPSILON * radius;
+
+        let mut orbit_indices: Vec<Option<usize>> = vec![None; elements.len()];
+        let mut orbits: Vec<Vec<PolytopeId>> = vec![];
+        for i in 0..elements.len() {
+            if orbit_indices[i].is_some() {
+                continue;
+            }
+            let mut orbit = vec![];
+            for (_, point) in group.orbit(&elements[i].1) {
+                for (j, (p, centroid)) in elements.iter().enumerate() {
+                    if orbit_indices[j].is_none() && centroid.approx_eq_eps(&point, eps) {
+                        orbit_indices[j] = Some(orbits.len());
+                        orbit.push(*p);
+                    }
+                }
+            }
+            orbit.sort();
+            orbits.push(orbit);
+        }
+        orbits
+    }
+    /// Returns the elements of `group` that map `p` onto itself, in order.
+    ///
+    /// Symmetries of a convex polytope map each element onto itself exactly
+    /// when they fix its centroid, so only the centroid is checked. Returns
+    /// an empty list if `p` has no vertices, which only happens in a corrupted
+    /// arena.
+    pub fn stabilizer(&self, group: &Group, p: PolytopeId) -> Vec<GroupElement> {
+        let Some(centroid) = crate::centroid(&self.vertices_of(p)) else {
+            return vec![];
+        };
+        let radius = self.vertices().map(|(_, v)| v.mag()).fold(0.0, f32::max);
+        let eps = EPSILON * radius;
+        group
+            .elements()
+            .filter(|&e| {
+                group
+                    .matrix(e)
+                    .transform(&centroid)
+                    .approx_eq_eps(&centroid, eps)
+            })
+            .collect()
+    }
+    /// Returns the index of the orbit containing `p` in
+    /// [`PolytopeArena::element_orbits()`], or `None` if it is not in any
+    /// orbit.
+    pub fn orbit_of(&self, group: &Group, p: PolytopeId) -> Option<usize> {
+        self.element_orbits(group, self[p].rank())
+            .iter()
+            .position(|orbit| orbit.contains(&p))
+    }
+    /// Returns whether a point is inside any piece of the arena, on the
+    /// boundary of one, or outside all of them. Points within `eps` of a facet
+    /// are considered to be on it.
+    ///
+    /// Pieces are assumed to be convex and to span the space of the arena.
+    pub fn contains_point(&self, point: impl VectorRef<f32>, eps: f32) -> Containment {
+        let mut ret = Containment::Outside;
+        for piece in self.pieces() {
+            match self.piece_contains_point(piece, &point, eps) {
+                Containment::Inside => return Containment::Inside,
+                Containment::OnBoundary => ret = Containment::OnBoundary,
+                Containment::Outside => (),
+            }
+        }
+        ret
+    }
+    /// Returns whether a point is inside a piece, on its boundary, or outside
+    /// it. See [`PolytopeArena::contains_point()`].
+    pub fn piece_contains_point(
+        &self,
+        piece: PieceId,
+        point: impl VectorRef<f32>,
+        eps: f32,
+    ) -> Containment {
+        let planes = &self.facet_planes()[piece.0 as usize];
+        let max_distance = planes
+            .iter()
+            .map(|plane| plane.signed_distance(&point))
+            .fold(f32::NEG_INFINITY, f32::max);
+        if max_distance > eps {
+            Containment::Outside
+        } else if max_distance >= -eps {
+            Containment::OnBoundary
+        } else {
+            Containment::Inside
+        }
+    }
+    /// Returns the supporting hyperplane of each facet of each piece, with the
+    /// normal pointing out of the piece. Degenerate facets, which have no
+    /// supporting hyperplane, are skipped.
+    fn facet_planes(&self) -> &[Vec<Hyperplane>] {
+        self.facet_planes.get_or_init(|| {
+            self.pieces
+                .iter()
+                .map(|&root| {
+                    let Some(center) = crate::centroid(&self.vertices_of(root)) else {
+                        return vec![];
+                    };
+                    self[root]
+                        .children()
+                        .iter()
+                        .filter_map(|&facet| {
+                            let verts = self.vertices_of(facet);
+                            // The offset from the facet to the center points
+                            // inward.
+                            let normal = -offset_from_affine_hull(&center, &verts);
+                            let distance = normal.normalized()?.dot(verts.first()?);
+                            Some(Hyperplane::new(normal, distance))
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the intersection of every piece with a hyperplane, as an arena
+    /// with one fewer dimension. Coordinates are expressed in an orthonormal
+    /// basis of the hyperplane, given by [`Matrix::complete_basis()`] with the
+    /// hyperplane's normal as the first vector.
+    ///
+    /// Each element of the cross-section has the facet of the element it is
+    /// the intersection of. Pieces that do not cross the hyperplane are
+    /// skipped, so the result may be empty. Elements that lie in the
+    /// hyperplane do not cross it, so they are also skipped.
+    pub fn cross_section(&self, plane: &Hyperplane) -> Result<PolytopeArena, SectionError> {
+        self.cross_section_of_pieces(plane, &self.pieces)
+    }
+    /// Returns the vertex figure of a vertex: the cross-section of the pieces
+    /// containing it by a hyperplane that separates the vertex from all of
+    /// their other vertices. Each facet of the vertex figure has the facet of
+    /// the element it is the intersection of, as in
+    /// [`PolytopeArena::cross_section()`].
+    ///
+    /// The hyperplane is perpendicular to the sum of the outward normals of the
+    /// facets incident to the vertex, and passes halfway between the vertex and
+    /// the nearest adjacent vertex. Coordinates are expressed in a basis of the
+    /// hyperplane, so only the combinatorics and shape of the result are
+    /// meaningful, not its size.
+    pub fn vertex_figure(&self, v: PolytopeId) -> Result<PolytopeArena, SectionError> {
+        let point = match &self[v].contents {
+            PolytopeContents::Point(point) => point,
+            PolytopeContents::Branch { .. } => return Err(SectionError::NotAVertex(v)),
+        };
+        let planes = self.facet_planes();
+        let mut pieces = vec![];
+        let mut normal = Vector::EMPTY;
+        for (&root, planes) in self.pieces.iter().zip(planes) {
+            if !self.descendants(root).contains(&v) {
+                continue;
+            }
+            pieces.push(root);
+            for (&facet, plane) in self[root].children().iter().zip(planes) {
+                if self.descendants(facet).contains(&v) {
+                    normal = &normal + plane.normal();
+                }
+            }
+        }
+        let normal = normal.normalized().ok_or(SectionError::NoVertexFigure(v))?;
+
+        // Depth of the nearest adjacent vertex below the vertex, along the
+        // normal
+        let depth = self
+            .parents_of(v)
+            .iter()
+            .flat_map(|&edge| self.children_of(edge))
+            .filter(|&&w| w != v)
+            .map(|&w| normal.dot(point - self[w].unwrap_point()))
+            .fold(f32::INFINITY, f32::min);
+        if !depth.is_finite() || depth <= 0.0 {
+            return Err(SectionError::NoVertexFigure(v));
+        }
+        let plane = Hyperplane::new(&normal, normal.dot(point) - depth / 2.0);
+        self.cross_section_of_pieces(&plane, &pieces)
+    }
+    /// Returns the intersection of some pieces with a hyperplane. See
+    /// [`PolytopeArena::cross_section()`].
+    // The normal of a hyperplane is a unit vector.
+    #[allow(clippy::expect_used)]
+    fn cross_section_of_pieces(
+        &self,
+        plane: &Hyperplane,
+        pieces: &[PolytopeId],
+    ) -> Result<PolytopeArena, SectionError> {
+        let ndim = self.vertices().map(|(_, v)| v.ndim()).max().unwrap_or(0);
+        if ndim == 0 {
+            return Err(SectionError::ZeroDimensional);
+        }
+        if plane
+            .normal()
+            .iter()
+            .skip(ndim as usize)
+            .any(|x| x.abs() > EPSILON)
+        {
+            return Err(SectionError::PlaneOutsideSpace { ndim });
+        }
+        let basis = Matrix::complete_basis(&[plane.normal().clone()], ndim)
+            .expect("hyperplane normal is not a unit vector");
+
+        let mut sliced = self.clone();
+        sliced.journal = None;
+        sliced.slice_all(plane, false);
+        let mut original: HashMap<PolytopeId, PolytopeId> = HashMap::new();
+        for (i, polytope) in sliced.polytopes.iter().enumerate() {
+            if let Some(Polytope {
+                slice_result: SliceResult::Modified { intersection, .. },
+                ..
+            }) = polytope
+            {
+                original.insert(*intersection, PolytopeId(i as u32));
+            }
+        }
+        let roots: Vec<PolytopeId> = pieces
+            .iter()
+            .filter_map(|&root| match sliced[root].slice_result {
+                SliceResult::Modified { intersection, .. } => Some(intersection),
+                _ => None,
+            })
+            .collect();
+
+        let mut ids: Vec<PolytopeId> = roots.iter().flat_map(|&r| sliced.descendants(r)).collect();
+        ids.sort_unstable_by_key(|id| id.0);
+        ids.dedup();
+        let new_ids: HashMap<PolytopeId, PolytopeId> = ids
+            .iter()
+            .zip(0..)
+            .map(|(&old, new)| (old, PolytopeId(new)))
+            .collect();
+
+        let mut ret = Self {
+            polytopes: vec![],
+            pieces: roots.iter().map(|r| new_ids[r]).collect(),
+            cut_count: self.cut_count,
+            free: vec![],
+            slice_options: self.slice_options,
+            slice_log: vec![],
+            facet_planes: OnceLock::new(),
+            journal: None,
+            #[cfg(feature = "rayon")]
+            parallel: self.parallel,
+        };
+        for id in ids {
+            let p = &sliced[id];
+            let contents = match &p.contents {
+                PolytopeContents::Point(point) => PolytopeContents::Point(
+                    (1..ndim).map(|axis| point.dot(basis.col(axis))).collect(),
+                ),
+                PolytopeContents::Branch { rank, children } => PolytopeContents::Branch {
+                    rank: *rank,
+                    children: children.iter().map(|c| new_ids[c]).collect(),
+                },
+            };
+            ret.push(Polytope {
+                parents: p
+                    .parents
+                    .iter()
+                    .filter_map(|parent| new_ids.get(parent).copied())
+                    .collect(),
+                contents,
+                facet: original
+                    .get(&id)
+                    .map_or(p.facet, |&orig| sliced[orig].facet),
+                slice_result: SliceResult::Unknown,
+            });
+        }
+
+        let radius = ret.vertices().map(|(_, v)| v.mag()).fold(0.0, f32::max);
+        ret.weld_vertices(self.slice_options.tolerance.eps(radius));
+        Ok(ret)
+    }
+}
+
+/// Classification of a polytope by a slice, before any polytopes are
+/// modified.
+#[derive(Debug, Clone, PartialEq)]
+enum SlicePlan<N: Real> {
+    Kept,
+    Removed,
+    Split {
+        /// Children entirely outside the hyperplane, plus the outside halves
+        /// of split children. Only computed when keeping the outside.
+        outside_children: SmallVec<[PolytopeId; 4]>,
+        intersection_boundary: SmallVec<[PolytopeId; 4]>,
+        /// For edges, the point where the edge intersects the hyperplane.
+        new_point: Option<Vector<N>>,
+    },
+}
+
+/// Element of any rank in a [`PolytopeArena`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polytope<N: Real = f32> {
+    parents: SmallVec<[PolytopeId; 4]>,
+    contents: PolytopeContents<N>,
+    /// Index of the cut that created this polytope, or `None` if it is part
+    /// of the original cube.
+    facet: Option<usize>,
+    slice_result: SliceResult,
+}
+impl<N: Real> Polytope<N> {
+    fn rank(&self) -> u8 {
+        self.contents.rank()
+    }
+    fn unwrap_point(&self) -> &Vector<N> {
+        match &self.contents {
+            PolytopeContents::Point(point) => point,
+            _ => panic!("expected point, got branch"),
+        }
+    }
+    fn children(&self) -> &[PolytopeId] {
+        match &self.contents {
+            PolytopeContents::Point(_) => &[],
+            PolytopeContents::Branch { children, .. } => children,
+        }
+    }
+    fn unwrap_children_mut(&mut self) -> &mut SmallVec<[PolytopeId; 4]> {
+        match &mut self.contents {
+            PolytopeContents::Point(_) => panic!("expected brancch, got point"),
+            PolytopeContents::Branch { children, .. } => children,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PolytopeContents<N: Real> {
+    Point(Vector<N>),
+    Branch {
+        rank: u8,
+        children: SmallVec<[PolytopeId; 4]>,
+    },
+}
+impl<N: Real> PolytopeContents<N> {
+    fn rank(&self) -> u8 {
+        match self {
+            PolytopeContents::Point(_) => 0,
+            PolytopeContents::Branch { rank, .. } => *rank,
+        }
+    }
+}
+
+/// Mesh of a [`PolytopeArena`] after dropping small polygons. See
+/// [`PolytopeArena::mesh()`].
+struct FilteredMesh {
+    mesh: Mesh,
+    /// Face that each polygon comes from.
+    faces: Vec<PolytopeId>,
+    /// Number of polygons that were dropped.
+    dropped: usize,
+}
+
+/// Geometry of a single cell (rank-3 element) in a [`PolytopeArena`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellGeometry {
+    /// ID of the cell.
+    pub id: PolytopeId,
+    /// Polygons on the boundary of the cell, as indices into
+    /// [`Mesh::polygons`].
+    pub polygons: Vec<u32>,
+    /// Average of the vertices of the cell.
+    pub centroid: Vector<f32>,
+}
+
+/// Inconsistency in a [`PolytopeArena`], found by
+/// [`PolytopeArena::validate()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaDefect {
+    /// A piece's root polytope does not exist.
+    MissingRoot { piece: PieceId },
+    /// A polytope refers to a parent or child that does not exist.
+    DanglingReference { from: PolytopeId, to: PolytopeId },
+    /// A polytope lists a child that does not list it as a parent.
+    MissingParentLink {
+        parent: PolytopeId,
+        child: PolytopeId,
+    },
+    /// A polytope lists a parent that does not list it as a child.
+    MissingChildLink {
+        parent: PolytopeId,
+        child: PolytopeId,
+    },
+    /// A polytope has a child whose rank is not one less than its own.
+    RankMismatch {
+        parent: PolytopeId,
+        child: PolytopeId,
+    },
+    /// An edge does not have exactly 2 vertices.
+    WrongEdgeEndpoints { edge: PolytopeId, count: usize },
+    /// A face has fewer than 3 edges.
+    TooFewEdges { face: PolytopeId, count: usize },
+    /// The edges of a face do not form a single loop.
+    InvalidFace(PolygonError),
+}
+
+/// Handle to a polytope in a [`PolytopeArena`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PolytopeId(u32);
+
+/// Handle to a piece of a [`PolytopeArena`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PieceId(u32);
+
+/// Error returned when a face in a [`PolytopeArena`] is not a valid polygon,
+/// usually due to floating-point error during slicing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolygonError {
+    /// The edges of the face do not form a closed loop.
+    BrokenCycle {
+        /// ID of the face.
+        face: PolytopeId,
+        /// IDs of the edges of the face.
+        edges: Vec<PolytopeId>,
+        /// Vertices visited before the loop broke.
+        partial_loop: Vec<Vector<f32>>,
+    },
+    /// The edges of the face form more than one closed loop.
+    MultipleComponents {
+        /// ID of the face.
+        face: PolytopeId,
+        /// IDs of the edges of the face.
+        edges: Vec<PolytopeId>,
+        /// Vertices of the first loop.
+        partial_loop: Vec<Vector<f32>>,
+    },
+    /// The face has no edges.
+    NoEdges {
+        /// ID of the face.
+        face: PolytopeId,
+    },
+    /// An edge of the face does not have exactly two endpoints.
+    InvalidEdge {
+        /// ID of the face.
+        face: PolytopeId,
+        /// ID of the edge.
+        edge: PolytopeId,
+        /// Number of endpoints of the edge.
+        endpoints: usize,
+    },
+}
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::BrokenCycle {
+                face,
+                edges,
+                partial_loop,
+            } => write!(
+                f,
+                "edges of face {face:?} do not form a closed loop \
+                 (broke after {} of {} vertices)",
+                partial_loop.len(),
+                edges.len(),
+            ),
+            PolygonError::MultipleComponents {
+                face,
+                edges,
+                partial_loop,
+            } => write!(
+                f,
+                "edges of face {face:?} form multiple loops \
+                 (first loop has {} of {} vertices)",
+                partial_loop.len(),
+                edges.len(),
+            ),
+            PolygonError::NoEdges { face } => write!(f, "face {face:?} has no edges"),
+            PolygonError::InvalidEdge {
+                face,
+                edge,
+                endpoints,
+            } => write!(
+                f,
+                "edge {edge:?} of face {face:?} has {endpoints} endpoints instead of 2",
+            ),
+        }
+    }
+}
+impl std::error::Error for PolygonError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub verts: Vec<Vector<f32>>,
+    /// Index of the cut that created this polygon, or `None` if it is part of
+    /// the original cube.
+    pub facet: Option<usize>,
+}
+impl Polygon {
+    /// Splits the polygon into triangles, which have the same winding order
+    /// as the polygon. Non-convex polygons are supported, but
+    /// self-intersecting ones are not.
+    ///
+    /// Collinear vertices are skipped, and degenerate polygons with near-zero
+    /// area produce no triangles.
+    pub fn triangulate(&self) -> Vec<[Vector<f32>; 3]> {
+        triangulate_loop(&self.verts)
+            .into_iter()
+            .map(|tri| tri.map(|i| self.verts[i].clone()))
+            .collect()
+    }
+
+    /// Returns the area of the polygon, which is zero for degenerate polygons.
+    pub fn area(&self) -> f32 {
+        match PlanarLoop::new(&self.verts) {
+            Some(planar) => (planar.twice_signed_area() / 2.0).abs(),
+            None => 0.0,
+        }
+    }
+
+    /// Returns the center of mass of the polygon, or `None` if it has no
+    /// vertices. Degenerate polygons return the average of their vertices.
+    pub fn centroid(&self) -> Option<Vector<f32>> {
+        let planar = match PlanarLoop::new(&self.verts) {
+            Some(planar) => planar,
+            None => return crate::centroid(&self.verts),
+        };
+        let twice_area = planar.twice_signed_area();
+        if twice_area.abs() < EPSILON * EPSILON {
+            return crate::centroid(&self.verts);
+        }
+        let (mut x, mut y) = (0.0, 0.0);
+        for ([x1, y1], [x2, y2]) in planar.edges() {
+            let cross = x1 * y2 - x2 * y1;
+            x += (x1 + x2) * cross;
+            y += (y1 + y2) * cross;
+        }
+        let scale = 1.0 / (3.0 * twice_area);
+        Some(planar.center + &planar.u * (x * scale) + &planar.v * (y * scale))
+    }
+
+    /// Returns the unit normal of the polygon, oriented by the right-hand rule
+    /// with respect to its winding order, or `None` if the polygon is
+    /// degenerate.
+    ///
+    /// Coordinates beyond the third axis are ignored, so 4D polygons should be
+    /// projected to 3D first.
+    pub fn normal(&self) -> Option<Vector<f32>> {
+        // Newell's method, which is robust to collinear and slightly
+        // non-planar vertices.
+        let verts: Vec<Vector<f32>> = self
+            .verts
+            .iter()
+            .map(|v| v.iter().take(3).collect())
+            .collect();
+        let sum = verts
+            .iter()
+            .zip(verts.iter().cycle().skip(1))
+            .fold(Vector::zero(3), |sum, (a, b)| sum + a.cross(b));
+        if sum.mag() < EPSILON * EPSILON {
+            return None;
+        }
+        sum.normalized()
+    }
+
+    /// Returns the total length of the edges of the polygon.
+    pub fn perimeter(&self) -> f32 {
+        if self.verts.len() < 2 {
+            return 0.0;
+        }
+        self.verts
+            .iter()
+            .zip(self.verts.iter().cycle().skip(1))
+            .map(|(a, b)| (b - a).mag())
+            .sum()
+    }
+}
+
+/// Returns the offset from the affine hull of `verts` to `point`, which is
+/// perpendicular to the affine hull.
+fn offset_from_affine_hull(point: &Vector<f32>, verts: &[Vector<f32>]) -> Vector<f32> {
+    let Some((origin, rest)) = verts.split_first() else {
+        return point.clone();
+    };
+    let mut basis: Vec<Vector<f32>> = vec![];
+    for v in rest {
+        let offset = basis.iter().fold(v - origin, |v, b| v.reject_from(b));
+        if offset.mag() > EPSILON {
+            basis.extend(offset.normalized());
+        }
+    }
+    basis.iter().fold(point - origin, |v, b| v.reject_from(b))
+}
+
+/// Planar loop of points, projected onto a basis for its own plane.
+struct PlanarLoop {
+    center: Vector<f32>,
+    u: Vector<f32>,
+    v: Vector<f32>,
+    points: Vec<[f32; 2]>,
+}
+impl PlanarLoop {
+    /// Projects a loop of points onto a basis for the plane they lie in, or
+    /// returns `None` if there are fewer than three points or they are all
+    /// collinear.
+    fn new(verts: &[Vector<f32>]) -> Option<Self> {
+        let center = crate::centroid(verts).filter(|_| verts.len() >= 3)?;
+
+        let offsets: Vec<Vector<f32>> = verts.iter().map(|v| v - &center).collect();
+        let longest = |vectors: Vec<Vector<f32>>| {
+            vectors
+                .into_iter()
+                .max_by(|a, b| a.mag2().total_cmp(&b.mag2()))?
+                .normalized()
+        };
+        let u = longest(offsets.clone())?;
+        let v = longest(offsets.iter().map(|o| o.reject_from(&u)).collect())?;
+        let points = offsets.iter().map(|o| [o.dot(&u), o.dot(&v)]).collect();
+
+        Some(Self {
+            center,
+            u,
+            v,
+            points,
+        })
+    }
+
+    /// Returns each edge of the loop as a pair of endpoints.
+    fn edges(&self) -> impl '_ + Iterator<Item = ([f32; 2], [f32; 2])> {
+        self.points
+            .iter()
+            .copied()
+            .zip(self.points.iter().copied().cycle().skip(1))
+    }
+
+    /// Returns twice the signed area of the loop, which is positive if the
+    /// loop winds counterclockwise with respect to the basis.
+    fn twice_signed_area(&self) -> f32 {
+        self.edges()
+            .map(|([x1, y1], [x2, y2])| x1 * y2 - x2 * y1)
+            .sum()
+    }
+}
+
+/// Triangulates a planar loop of points using ear clipping, returning triangles
+/// as indices into `verts`.
+pub(crate) fn triangulate_loop(verts: &[Vector<f32>]) -> Vec<[usize; 3]> {
+    let planar = match PlanarLoop::new(verts) {
+        Some(planar) => planar,
+        None => return vec![],
+    };
+
+    let twice_area = planar.twice_signed_area();
+    let points = planar.points;
+    if twice_area.abs() < EPSILON * EPSILON {
+        return vec![];
+    }
+    let orientation = twice_area.signum();
+
+    // Positive for convex corners, negative for reflex corners, and near zero
+    // for collinear points.
+    let corner = |a: usize, b: usize, c: usize| {
+        let [ax, ay] = points[a];
+        let [bx, by] = points[b];
+        let [cx, cy] = points[c];
+        ((bx - ax) * (cy - by) - (by - ay) * (cx - bx)) * orientation
+    };
+    let neighbors = |remaining: &[usize], i: usize| {
+        let n = remaining.len();
+        (
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        )
+    };
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    while let Some(i) = (0..remaining.len()).find(|&i| {
+        let (a, b, c) = neighbors(&remaining, i);
+        corner(a, b, c).abs() < EPSILON * EPSILON
+    }) {
+        remaining.remove(i);
+        if remaining.len() < 3 {
+            return vec![];
+        }
+    }
+
+    let mut triangles = vec![];
+    while remaining.len() > 3 {
+        let ear = (0..remaining.len()).find(|&i| {
+            let (a, b, c) = neighbors(&remaining, i);
+            corner(a, b, c) > 0.0
+                && remaining.iter().all(|&p| {
+                    [a, b, c].contains(&p)
+                        || corner(a, b, p) < 0.0
+                        || corner(b, c, p) < 0.0
+                        || corner(c, a, p) < 0.0
+                })
+        });
+        match ear {
+            Some(i) => {
+                let (a, b, c) = neighbors(&remaining, i);
+                triangles.push([a, b, c]);
+                remaining.remove(i);
+            }
+            // The polygon is self-intersecting, so fall back to a fan.
+            None => break,
+        }
+    }
+    triangles.extend(
+        remaining[1..]
+            .windows(2)
+            .map(|pair| [remaining[0], pair[0], pair[1]]),
+    );
+    triangles
+}
+
+/// Convex polytope as a list of vertices and faces. See
+/// [`PolytopeArena::to_convex_polytope()`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConvexPolytope {
+    /// Vertex positions, without duplicates.
+    pub verts: Vec<Vector<f32>>,
+    /// Faces, each of which is a list of indices into `verts` in order around
+    /// the boundary of the face.
+    pub faces: Vec<Vec<u32>>,
+}
+impl From<ConvexPolytope> for Mesh {
+    fn from(polytope: ConvexPolytope) -> Self {
+        Mesh {
+            facets: vec![None; polytope.faces.len()],
+            verts: polytope.verts,
+            polygons: polytope.faces,
+        }
+    }
+}
+
+fn base_3_expansion(n: u32, digit_count: u8) -> impl Iterator<Item = u32> {
+    std::iter::successors(Some(n), |x| Some(x / 3))
+        .take(digit_count as _)
+        .map(|x| x % 3)
+}
+
+/// Returns an error if an initial polytope with `element_count` elements
+/// has more than `max_elements`.
+fn check_element_count(
+    ndim: u8,
+    element_count: usize,
+    max_elements: usize,
+) -> Result<(), ArenaError> {
+    if element_count > max_elements {
+        return Err(ArenaError::TooManyElements {
+            ndim,
+            limit: max_elements,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimension_too_large() {
+        assert_eq!(
+            PolytopeArena::new_cube(21, 1.0).unwrap_err(),
+            ArenaError::DimensionTooLarge { ndim: 21 },
+        );
+        assert_eq!(
+            PolytopeArena::new_simplex(31, 1.0).unwrap_err(),
+            ArenaError::DimensionTooLarge { ndim: 31 },
+        );
+
+        // These would fit in an arena, but take too much memory.
+        let limit = DEFAULT_MAX_ARENA_ELEMENTS;
+        assert_eq!(
+            PolytopeArena::new_cube(16, 1.0).unwrap_err(),
+            ArenaError::TooManyElements { ndim: 16, limit },
+        );
+        assert_eq!(
+            PolytopeArena::new_simplex(30, 1.0).unwrap_err(),
+            ArenaError::TooManyElements { ndim: 30, limit },
+        );
+        assert_eq!(
+            PolytopeArena::new_cube_with_max_elements(3, 1.0, 26).unwrap_err(),
+            ArenaError::TooManyElements { ndim: 3, limit: 26 },
+        );
+        assert!(PolytopeArena::new_cube_with_max_elements(3, 1.0, 27).is_ok());
+        assert_eq!(
+            PolytopeArena::new_simplex_with_max_elements(3, 1.0, 14).unwrap_err(),
+            ArenaError::TooManyElements { ndim: 3, limit: 14 },
+        );
+        assert!(PolytopeArena::new_simplex_with_max_elements(3, 1.0, 15).is_ok());
+        let poles: Vec<Vector<f32>> = (0..21)
+            .flat_map(|i| [Vector::unit(i), -Vector::unit(i)])
+            .collect();
+        assert_eq!(
+            shape_geom(21, &[], &poles, &SliceOptions::default()),
+            Err(ShapeGeomError::Arena(ArenaError::DimensionTooLarge {
+                ndim: 21
+            })),
+        );
+    }
+
+    #[test]
+    fn test_cube() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap();
+        assert_eq!(cube.element_counts(), [8, 12, 6, 1]);
+        assert_eq!(cube.euler_characteristic(), 2);
+        cube.assert_euler_ok();
+
+        let tesseract = PolytopeArena::new_cube(4, 1.0).unwrap();
+        assert_eq!(tesseract.element_counts(), [16, 32, 24, 8, 1]);
+        assert_eq!(tesseract.euler_characteristic(), 0);
+        tesseract.assert_euler_ok();
+
+        for ndim in 1..=6 {
+            PolytopeArena::new_cube(ndim, 1.0)
+                .unwrap()
+                .assert_euler_ok();
+            PolytopeArena::new_simplex(ndim, 1.0)
+                .unwrap()
+                .assert_euler_ok();
+        }
+
+        // Truncating a corner replaces one vertex with three.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_by_pole(&(vector![1.0, 1.0, 1.0] * 0.8));
+        assert_eq!(arena.element_counts(), [10, 15, 7, 1]);
+        arena.assert_euler_ok();
+    }
+
+    fn unique_vertices(polygons: &[Polygon]) -> Vec<Vector<f32>> {
+        let mut verts = polygons.iter().flat_map(|p| p.verts.clone()).collect();
+        crate::sort_and_dedup_points(&mut verts, EPSILON);
+        verts
+    }
+
+    #[test]
+    fn test_slice_through_origin() {
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_by_plane(&Hyperplane::new(vector![1.0, 0.0, 0.0], 0.0));
+        let polygons = arena.polygons().unwrap();
+        assert_eq!(polygons.len(), 6);
+        let verts = unique_vertices(&polygons);
+        assert_eq!(verts.len(), 8);
+        assert_eq!(verts.iter().filter(|v| v[0] == -1.0).count(), 4);
+        assert_eq!(verts.iter().filter(|v| v[0].abs() < EPSILON).count(), 4);
+    }
+
+    #[test]
+    fn test_parallel_slices() {
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_by_plane(&Hyperplane::new(vector![0.0, 2.0], 0.3));
+        arena.slice_by_plane(&Hyperplane::new(vector![0.0, -2.0], 0.3));
+        let polygons = arena.polygons().unwrap();
+        assert_eq!(polygons.len(), 6);
+        let verts = unique_vertices(&polygons);
+        assert_eq!(verts.len(), 8);
+        for v in &verts {
+            assert!(crate::util::f32_approx_eq(v[1].abs(), 0.3));
+            assert_eq!(v[0].abs(), 1.0);
+            assert_eq!(v[2].abs(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_invalid_polygons() {
+        // Remove an edge from a square.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let face = arena.pieces[0];
+        arena[face].unwrap_children_mut().remove(3);
+        let edges = arena[face].children().to_vec();
+        match arena.polygons() {
+            Err(PolygonError::BrokenCycle {
+                face: f,
+                edges: e,
+                partial_loop,
+            }) => {
+                assert_eq!(f, face);
+                assert_eq!(e, edges);
+                assert!(!partial_loop.is_empty() && partial_loop.len() <= 3);
+            }
+            other => panic!("expected broken cycle, got {other:?}"),
+        }
+
+        // Make a face out of two disjoint triangles.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let points = [
+            vector![0.0, 0.0],
+            vector![1.0, 0.0],
+            vector![0.0, 1.0],
+            vector![5.0, 0.0],
+            vector![6.0, 0.0],
+            vector![5.0, 1.0],
+        ]
+        .map(|p| arena.push_point(p));
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]
+            .map(|(a, b)| arena.push_polytope([points[a], points[b]]));
+        let face = arena.push_polytope(edges);
+        match arena.polygon(face) {
+            Err(PolygonError::MultipleComponents {
+                face: f,
+                edges: e,
+                partial_loop,
+            }) => {
+                assert_eq!(f, face);
+                assert_eq!(e, edges);
+                assert_eq!(partial_loop.len(), 3);
+                assert!(partial_loop.iter().all(|v| v[0] < 2.0));
+            }
+            other => panic!("expected multiple components, got {other:?}"),
+        }
+
+        // Make a face with no edges.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let face = arena.pieces[0];
+        arena[face].unwrap_children_mut().clear();
+        assert_eq!(arena.polygons(), Err(PolygonError::NoEdges { face }));
+
+        // Make an edge with only one endpoint.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let face = arena.pieces[0];
+        let edge = arena[face].children()[2];
+        arena[edge].unwrap_children_mut().pop();
+        assert_eq!(
+            arena.polygons(),
+            Err(PolygonError::InvalidEdge {
+                face,
+                edge,
+                endpoints: 1,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_corrupted_arena_queries() {
+        // Measuring a face with no edges gives zero instead of panicking.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let face = arena.pieces[0];
+        arena[face].unwrap_children_mut().clear();
+        assert_eq!(arena.volume(), 0.0);
+
+        // Edges without two endpoints are skipped.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let edge = arena[arena.pieces[0]].children()[2];
+        arena[edge].unwrap_children_mut().pop();
+        assert_eq!(arena.edges().count(), 3);
+
+        // Cells without vertices are skipped.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        let cell = arena.pieces[0];
+        arena[cell].unwrap_children_mut().clear();
+        assert_eq!(arena.cells(), Ok(vec![]));
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        assert!(arena.stabilizer(&group, cell).is_empty());
+        assert_eq!(arena.orbit_of(&group, cell), None);
+    }
+
+    #[test]
+    fn test_shape_geom_unbounded() {
+        let result = shape_geom(3, &[], &[vector![1.0]], &SliceOptions::default());
+        match result {
+            Err(ShapeGeomError::UnboundedShape { surviving_corners }) => {
+                assert_eq!(surviving_corners.len(), 4);
+                assert!(surviving_corners.iter().all(|v| v[0] < 0.0));
+            }
+            other => panic!("expected unbounded shape error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_shape_geom_errors() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let options = SliceOptions::default();
+        assert_eq!(
+            shape_geom(3, &generators, &[], &options),
+            Err(ShapeGeomError::NoBaseFacets),
+        );
+        assert_eq!(
+            shape_geom(3, &generators, &[vector![1.0], Vector::zero(3)], &options),
+            Err(ShapeGeomError::ZeroPole { base_facet: 1 }),
+        );
+
+        // Rotation by an irrational fraction of a turn generates an infinite
+        // group.
+        let (s, c) = 1.0_f32.sin_cos();
+        let rotation = Matrix::from_cols([vector![c, s], vector![-s, c], vector![0.0, 0.0, 1.0]]);
+        let options = SliceOptions {
+            max_facets: 50,
+            ..options
+        };
+        assert_eq!(
+            shape_geom(3, &[rotation], &[vector![1.0]], &options),
+            Err(ShapeGeomError::TooManyFacets { limit: 50 }),
+        );
+    }
+
+    #[test]
+    fn test_shape_geom_duplicate_poles() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let options = SliceOptions::default();
+        let cube = shape_geom(3, &generators, &[vector![1.0]], &options).unwrap();
+        assert_eq!(cube.skipped_duplicates, 0);
+
+        // Exact duplicate
+        let poles = [vector![1.0], vector![1.0]];
+        let shape = shape_geom(3, &generators, &poles, &options).unwrap();
+        assert_eq!(shape.skipped_duplicates, 1);
+        assert_eq!(shape.facets.len(), 6);
+        assert_eq!(shape.polygons, cube.polygons);
+
+        // Near-duplicates that have drifted in magnitude and direction, and a
+        // pole in the orbit of an earlier one, which is not a duplicate
+        let poles = [
+            vector![1.0],
+            vector![1.0, 0.0, 0.0001],
+            vector![0.99999, 0.0002],
+            vector![0.0, 0.0, -1.0],
+        ];
+        let shape = shape_geom(3, &generators, &poles, &options).unwrap();
+        assert_eq!(shape.skipped_duplicates, 2);
+        assert_eq!(shape.facets.len(), 6);
+        assert_eq!(shape.facets[1].base_facet, 3);
+        assert_eq!(shape.polygons.len(), 6);
+    }
+
+    #[test]
+    fn test_shape_geom_with_group() {
+        for (edges, pole) in [
+            (vec![4, 3], vector![1.0]),
+            (vec![5, 3], vector![1.0]),
+            (vec![3, 5], vector![1.0]),
+        ] {
+            let generators = crate::CoxeterDiagram::with_edges(edges.clone()).generators();
+            let group = crate::CoxeterDiagram::with_edges(edges).group();
+            let options = SliceOptions::default();
+            let poles = std::slice::from_ref(&pole);
+            let a = shape_geom(3, &generators, poles, &options).unwrap();
+            let b = shape_geom_with_group(&group, poles, &options).unwrap();
+            assert_eq!(a.facets.len(), b.facets.len());
+            assert_eq!(a.polygons.len(), b.polygons.len());
+            assert!(b.facets[0].pole.approx_eq(&pole));
+            for facet in &b.facets {
+                assert!(facet.transform.transform(&pole).approx_eq(&facet.pole));
+            }
+            let (a_verts, b_verts) = (unique_vertices(&a.polygons), unique_vertices(&b.polygons));
+            assert_eq!(a_verts.len(), b_verts.len());
+            assert!(a_verts.iter().zip(&b_verts).all(|(a, b)| a.approx_eq(b)));
+        }
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        fn sides(arena: &PolytopeArena) -> Vec<usize> {
+            let mut sides: Vec<usize> = arena
+                .mesh()
+                .unwrap()
+                .polygons
+                .iter()
+                .map(|p| p.len())
+                .collect();
+            sides.sort_unstable();
+            sides
+        }
+
+        // Cube, offset from the origin, with some interior points
+        let offset = vector![3.0, -1.0, 0.5];
+        let mut points: Vec<Vector<f32>> = PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .vertices()
+            .map(|(_, v)| v + &offset)
+            .collect();
+        points.push(offset.clone());
+        points.push(vector![0.5, 0.2, -0.3] + &offset);
+        let cube = PolytopeArena::from_convex_hull(&points).unwrap();
+        assert_eq!(sides(&cube), [4; 6]);
+        assert!(crate::util::f32_approx_eq(cube.volume(), 8.0));
+        for (_, v) in cube.vertices() {
+            assert!(points[..8].iter().any(|p| p.approx_eq(v)));
+        }
+
+        // Icosahedron
+        let phi = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let mut points = vec![];
+        for (a, b) in [(1.0, phi), (1.0, -phi), (-1.0, phi), (-1.0, -phi)] {
+            points.push(vector![0.0, a, b]);
+            points.push(vector![a, b, 0.0]);
+            points.push(vector![b, 0.0, a]);
+        }
+        let icosahedron = PolytopeArena::from_convex_hull(&points).unwrap();
+        assert_eq!(sides(&icosahedron), [3; 20]);
+        assert_eq!(icosahedron.vertices().count(), 12);
+
+        // 16-cell
+        let points: Vec<Vector<f32>> = (0..4)
+            .flat_map(|i| [Vector::unit(i), -Vector::unit(i)])
+            .collect();
+        let arena = PolytopeArena::from_convex_hull(&points).unwrap();
+        assert_eq!(arena.element_counts(), [8, 24, 32, 16, 1]);
+
+        // Degenerate inputs
+        let square =
+            [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].map(|[x, y]| vector![x, y, 0.0]);
+        assert_eq!(
+            PolytopeArena::from_convex_hull(&square).unwrap_err(),
+            HullError::Degenerate,
+        );
+        assert_eq!(
+            PolytopeArena::from_convex_hull(&square[..3]).unwrap_err(),
+            HullError::TooFewPoints { count: 3, ndim: 3 },
+        );
+    }
+
+    #[test]
+    fn test_shape_geom_2d() {
+        let square = PolytopeArena::new_cube(2, 1.0).unwrap();
+        assert_eq!(square.edges().count(), 4);
+        let polygons = square.polygons().unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].verts.len(), 4);
+
+        let group = crate::CoxeterDiagram::with_edges(vec![7]).group();
+        let mirrors = crate::CoxeterDiagram::with_edges(vec![7]).mirrors();
+        // A pole on a mirror has an orbit of 7, and a pole on no mirror has an
+        // orbit of 14.
+        let [x, y] = [0, 1].map(|i| mirrors[1].0[i]);
+        for (pole, n) in [(vector![-y, x], 7), (vector![1.0], 14)] {
+            let arena = shape_arena_with_group(&group, &[pole], &SliceOptions::default()).unwrap();
+            assert_eq!(arena.element_counts(), [n, n, 1]);
+            assert_eq!(arena.edges().count(), n);
+            for [a, b] in arena.edges() {
+                assert!(arena.parents_of(a).len() == 2 && arena.parents_of(b).len() == 2);
+            }
+
+            let polygons = arena.polygons().unwrap();
+            assert_eq!(polygons.len(), 1);
+            let verts = &polygons[0].verts;
+            assert_eq!(verts.len(), n);
+            let radius = verts[0].mag();
+            let side = (&verts[1] - &verts[0]).mag();
+            let expected_radius = 1.0 / (std::f32::consts::PI / n as f32).cos();
+            assert!(crate::util::f32_approx_eq(radius, expected_radius));
+            for (a, b) in verts.iter().zip(verts.iter().cycle().skip(1)) {
+                assert!(crate::util::f32_approx_eq(a.mag(), radius));
+                // Consecutive vertices are adjacent.
+                assert!(crate::util::f32_approx_eq((b - a).mag(), side));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_geom_cube() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let ShapeGeom {
+            polygons, facets, ..
+        } = shape_geom(3, &generators, &[vector![1.0]], &SliceOptions::default()).unwrap();
+        assert_eq!(facets.len(), 6);
+        let mut facet_indices: Vec<usize> = polygons.iter().map(|p| p.facet.unwrap()).collect();
+        facet_indices.sort_unstable();
+        assert_eq!(facet_indices, [0, 1, 2, 3, 4, 5]);
+        for (polygon, facet) in polygons.iter().map(|p| (p, &facets[p.facet.unwrap()])) {
+            assert_eq!(facet.base_facet, 0);
+            assert!(facet
+                .transform
+                .transform(vector![1.0])
+                .approx_eq(&facet.pole));
+            // The facet's pole is the center of the polygon.
+            assert!(crate::centroid(&polygon.verts)
+                .unwrap()
+                .approx_eq(&facet.pole));
+        }
+        assert_eq!(polygons.len(), 6);
+        let verts = unique_vertices(&polygons);
+        assert_eq!(verts.len(), 8);
+        assert!(verts
+            .iter()
+            .all(|v| v.iter().all(|x| crate::util::f32_approx_eq(x.abs(), 1.0))));
+    }
+
+    #[test]
+    fn test_vertices() {
+        let mut arena = PolytopeArena::new_cube(3, 2.0).unwrap();
+        for i in 0..3 {
+            arena.slice_by_pole(&Vector::unit(i));
+            arena.slice_by_pole(&-Vector::unit(i));
+        }
+        arena.weld_vertices(EPSILON);
+        let verts: Vec<(PolytopeId, &Vector<f32>)> = arena.vertices().collect();
+        assert_eq!(verts.len(), 8);
+        for &(id, v) in &verts {
+            assert!(v.iter().all(|x| crate::util::f32_approx_eq(x.abs(), 1.0)));
+            assert!(arena.children_of(id).is_empty());
+            // Every vertex of a cube is on three edges.
+            assert_eq!(arena.parents_of(id).len(), 3);
+            for &edge in arena.parents_of(id) {
+                assert!(arena.children_of(edge).contains(&id));
+            }
+        }
+        let mut signs: Vec<[bool; 3]> = verts
+            .iter()
+            .map(|(_, v)| [0, 1, 2].map(|i| v[i] > 0.0))
+            .collect();
+        signs.sort_unstable();
+        signs.dedup();
+        assert_eq!(signs.len(), 8);
+
+        assert_eq!(arena.elements_of_rank(1).count(), 12);
+        assert_eq!(arena.elements_of_rank(2).count(), 6);
+        assert_eq!(arena.elements_of_rank(3).count(), 1);
+        assert_eq!(arena.elements_of_rank(4).count(), 0);
+        for face in arena.elements_of_rank(2) {
+            assert_eq!(arena.children_of(face).len(), 4);
+            assert_eq!(arena.parents_of(face).len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut cube = PolytopeArena::new_cube(3, 3.0).unwrap();
+        for i in 0..3 {
+            cube.slice_by_pole(&Vector::unit(i));
+            cube.slice_by_pole(&-Vector::unit(i));
+        }
+        let (min, max) = cube.bounding_box().unwrap();
+        assert!(min.approx_eq(vector![-1.0, -1.0, -1.0]));
+        assert!(max.approx_eq(vector![1.0, 1.0, 1.0]));
+
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let pole = vector![1.0, 1.0, 1.0] / 3.0_f32.sqrt();
+        let octahedron = shape_arena(3, &generators, &[pole], &SliceOptions::default()).unwrap();
+        let (center, radius) = octahedron.bounding_sphere().unwrap();
+        assert!(center.approx_eq(vector![]));
+        assert!(crate::util::f32_approx_eq(radius, 3.0_f32.sqrt()));
+
+        let empty = PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .cross_section(&Hyperplane::new(vector![1.0], 2.0))
+            .unwrap();
+        assert_eq!(empty.bounding_box(), None);
+        assert_eq!(empty.bounding_sphere(), None);
+    }
+
+    #[test]
+    fn test_volume() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap();
+        assert!(crate::util::f32_approx_eq(cube.volume(), 8.0));
+        assert!(crate::util::f32_approx_eq(cube.surface_measure(), 24.0));
+        let tesseract = PolytopeArena::new_cube(4, 1.0).unwrap();
+        assert!(crate::util::f32_approx_eq(tesseract.volume(), 16.0));
+        assert!(crate::util::f32_approx_eq(
+            tesseract.surface_measure(),
+            64.0
+        ));
+
+        // Octahedron with inradius 1, which has circumradius sqrt(3)
+        let mut octahedron = PolytopeArena::new_cube(3, 2.0).unwrap();
+        for signs in 0..8 {
+            let sign = |bit: u32| if signs & (1 << bit) == 0 { 1.0 } else { -1.0 };
+            let pole = vector![sign(0), sign(1), sign(2)] / 3.0_f32.sqrt();
+            octahedron.slice_by_pole(&pole);
+        }
+        octahedron.weld_vertices(EPSILON);
+        assert!(crate::util::f32_approx_eq(
+            octahedron.volume(),
+            4.0 * 3.0_f32.sqrt()
+        ));
+        // Each face is an equilateral triangle with side sqrt(6).
+        let face_area = 6.0 * 3.0_f32.sqrt() / 4.0;
+        assert!(crate::util::f32_approx_eq(
+            octahedron.surface_measure(),
+            8.0 * face_area
+        ));
+
+        // The volumes of pieces sum to the whole.
+        let mut arena = PolytopeArena::new_cube(4, 1.0).unwrap();
+        arena.slice_into_pieces(&Hyperplane::new(vector![1.0, 2.0, 3.0, 4.0], 0.5));
+        arena.slice_into_pieces(&Hyperplane::new(vector![-1.0, 1.0, 0.0, 2.0], -0.25));
+        let piece_volumes: Vec<f32> = arena
+            .pieces()
+            .into_iter()
+            .map(|p| arena.volume_of_piece(p))
+            .collect();
+        assert_eq!(piece_volumes.len(), 4);
+        assert!(piece_volumes.iter().all(|&v| v > 0.0));
+        assert!(crate::util::f32_approx_eq(arena.volume(), 16.0));
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let mut cube = PolytopeArena::new_cube(3, 1.0).unwrap();
+        assert_eq!(cube.contains_point(vector![], EPSILON), Containment::Inside);
+        assert_eq!(
+            cube.contains_point(vector![0.0, 0.0, 1.0], EPSILON),
+            Containment::OnBoundary
+        );
+        assert_eq!(
+            cube.contains_point(vector![1.0, -1.0, 1.0], EPSILON),
+            Containment::OnBoundary
+        );
+        assert_eq!(
+            cube.contains_point(vector![2.0], EPSILON),
+            Containment::Outside
+        );
+        assert_eq!(
+            cube.contains_point(vector![0.0, 1.0005], EPSILON),
+            Containment::OnBoundary
+        );
+
+        // The cached planes are updated after slicing.
+        cube.slice_into_pieces(&Hyperplane::new(vector![1.0, 1.0], 0.0));
+        let [inside, outside] = [0, 1].map(PieceId);
+        let p = vector![0.5, 0.5];
+        assert_eq!(
+            cube.piece_contains_point(inside, &p, EPSILON),
+            Containment::Outside
+        );
+        assert_eq!(
+            cube.piece_contains_point(outside, &p, EPSILON),
+            Containment::Inside
+        );
+        let p = vector![0.5, -0.5];
+        assert_eq!(
+            cube.piece_contains_point(inside, &p, EPSILON),
+            Containment::OnBoundary
+        );
+        assert_eq!(
+            cube.piece_contains_point(outside, &p, EPSILON),
+            Containment::OnBoundary
+        );
+        assert_eq!(cube.contains_point(&p, EPSILON), Containment::OnBoundary);
+        assert_eq!(
+            cube.contains_point(vector![0.9, 0.0, 0.5], EPSILON),
+            Containment::Inside
+        );
+    }
+
+    #[test]
+    fn test_cross_section() {
+        let tesseract = PolytopeArena::new_cube(4, 1.0).unwrap();
+
+        let cube = tesseract
+            .cross_section(&Hyperplane::new(vector![0.5], 0.0))
+            .unwrap();
+        assert_eq!(cube.element_counts(), [8, 12, 6, 1]);
+        assert_eq!(cube.validate(), Ok(()));
+        for (_, v) in cube.vertices() {
+            assert_eq!(v.ndim(), 3);
+            assert!(v.iter().all(|x| crate::util::f32_approx_eq(x.abs(), 1.0)));
+        }
+        assert!(crate::util::f32_approx_eq(cube.volume(), 8.0));
+
+        // Perpendicular to a main diagonal through the center, which passes
+        // through 6 vertices
+        let octahedron = tesseract
+            .cross_section(&Hyperplane::new(vector![1.0, 1.0, 1.0, 1.0], 0.0))
+            .unwrap();
+        assert_eq!(octahedron.element_counts(), [6, 12, 8, 1]);
+        assert_eq!(octahedron.validate(), Ok(()));
+        for (_, v) in octahedron.vertices() {
+            assert!(crate::util::f32_approx_eq(v.mag(), 2.0));
+        }
+
+        // Each piece of the section is the section of a piece.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_into_pieces(&Hyperplane::new(vector![1.0], 0.0));
+        let section = arena
+            .cross_section(&Hyperplane::new(vector![0.0, 1.0], 0.5))
+            .unwrap();
+        assert_eq!(section.pieces().len(), 2);
+        assert_eq!(section.element_counts(), [6, 7, 2]);
+
+        let empty = tesseract
+            .cross_section(&Hyperplane::new(vector![1.0], 5.0))
+            .unwrap();
+        assert!(empty.pieces().is_empty());
+        assert_eq!(empty.element_counts(), Vec::<usize>::new());
+
+        assert_eq!(
+            cube.cross_section(&Hyperplane::new(vector![1.0, 0.0, 0.0, 1.0], 0.0))
+                .unwrap_err(),
+            SectionError::PlaneOutsideSpace { ndim: 3 },
+        );
+    }
+
+    #[test]
+    fn test_convex_polytope() {
+        let ConvexPolytope { verts, faces } = PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .to_convex_polytope()
+            .unwrap();
+        assert_eq!(verts.len(), 8);
+        assert_eq!(faces.len(), 6);
+        for face in &faces {
+            assert_eq!(face.len(), 4);
+            assert!(face.iter().all(|&i| (i as usize) < verts.len()));
+            let [a, b, c] = [0, 1, 2].map(|i| &verts[face[i] as usize]);
+            let normal = (b - a).cross(c - b);
+            // The face is centered on its outward normal.
+            let face_verts: Vec<Vector<f32>> =
+                face.iter().map(|&i| verts[i as usize].clone()).collect();
+            assert!(normal.dot(crate::centroid(&face_verts).unwrap()) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_polygon_measures() {
+        let polygons = PolytopeArena::new_cube(3, 1.0).unwrap().polygons().unwrap();
+        assert_eq!(polygons.len(), 6);
+        for p in &polygons {
+            assert!(crate::util::f32_approx_eq(p.area(), 4.0));
+            assert!(crate::util::f32_approx_eq(p.perimeter(), 8.0));
+            let centroid = p.centroid().unwrap();
+            let mut coords: Vec<f32> = (0..3).map(|i| centroid[i].abs()).collect();
+            coords.sort_by(f32::total_cmp);
+            assert!(crate::util::f32_approx_eq(coords[0], 0.0));
+            assert!(crate::util::f32_approx_eq(coords[1], 0.0));
+            assert!(crate::util::f32_approx_eq(coords[2], 1.0));
+        }
+
+        // Non-convex polygon in 4D
+        let l_shape = Polygon {
+            facet: None,
+            verts: [
+                [0.0, 0.0],
+                [2.0, 0.0],
+                [2.0, 1.0],
+                [1.0, 1.0],
+                [1.0, 2.0],
+                [0.0, 2.0],
+            ]
+            .into_iter()
+            .map(|[x, w]| vector![x, 1.0, 1.0, w])
+            .collect(),
+        };
+        assert!(crate::util::f32_approx_eq(l_shape.area(), 3.0));
+        assert!(crate::util::f32_approx_eq(l_shape.perimeter(), 8.0));
+        let expected = vector![5.0 / 6.0, 1.0, 1.0, 5.0 / 6.0];
+        assert!(l_shape.centroid().unwrap().approx_eq(&expected));
+
+        let degenerate = Polygon {
+            facet: None,
+            verts: vec![vector![0.0, 0.0, 0.0], vector![1.0, 1.0, 0.0]],
+        };
+        assert_eq!(degenerate.area(), 0.0);
+        assert!(crate::util::f32_approx_eq(
+            degenerate.perimeter(),
+            2.0 * 2.0_f32.sqrt()
+        ));
+        assert!(degenerate
+            .centroid()
+            .unwrap()
+            .approx_eq(vector![0.5, 0.5, 0.0]));
+    }
+
+    fn triangle_normal([a, b, c]: &[Vector<f32>; 3]) -> Vector<f32> {
+        (b - a).cross(c - a) * 0.5
+    }
+
+    #[test]
+    fn test_triangulate() {
+        // Regular hexagon
+        let hexagon = Polygon {
+            facet: None,
+            verts: (0..6)
+                .map(|i| {
+                    let angle = i as f32 * std::f32::consts::PI / 3.0;
+                    vector![angle.cos(), angle.sin()]
+                })
+                .collect(),
+        };
+        let triangles = hexagon.triangulate();
+        assert_eq!(triangles.len(), 4);
+        let area: f32 = triangles.iter().map(|t| triangle_normal(t)[2]).sum();
+        assert!(crate::util::f32_approx_eq(area, 3.0 * 3.0_f32.sqrt() / 2.0));
+
+        // L shape in the plane `y = 1` with extra collinear vertices
+        let l_shape = Polygon {
+            facet: None,
+            verts: [
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [2.0, 0.0],
+                [2.0, 1.0],
+                [1.0, 1.0],
+                [1.0, 2.0],
+                [0.0, 2.0],
+                [0.0, 1.0],
+            ]
+            .into_iter()
+            .map(|[x, z]| vector![x, 1.0, z])
+            .collect(),
+        };
+        let triangles = l_shape.triangulate();
+        assert_eq!(triangles.len(), 4);
+        let mut area = 0.0;
+        for t in &triangles {
+            let normal = triangle_normal(t);
+            // Every triangle has the same winding as the polygon.
+            assert!(normal[1] < 0.0);
+            area -= normal[1];
+        }
+        assert!(crate::util::f32_approx_eq(area, 3.0));
+
+        // Degenerate polygons
+        let collinear = Polygon {
+            facet: None,
+            verts: vec![vector![0.0], vector![1.0], vector![2.0], vector![3.0]],
+        };
+        assert!(collinear.triangulate().is_empty());
+        assert_eq!(collinear.area(), 0.0);
+        let sliver = Polygon {
+            facet: None,
+            verts: vec![vector![0.0], vector![1.0], vector![2.0, 0.00001]],
+        };
+        assert!(sliver.triangulate().is_empty());
+    }
+
+    #[test]
+    fn test_shape_geom_scale() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![5, 3]).generators();
+        for scale in [0.001, 1.0, 1000.0] {
+            let pole = vector![0.0, 0.0, scale];
+            let arena = shape_arena(3, &generators, &[pole], &SliceOptions::default()).unwrap();
+            let mesh = arena.mesh().unwrap();
+            assert_eq!(mesh.polygons.len(), 12, "scale {scale}");
+            assert_eq!(mesh.verts.len(), 20, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn test_simplex() {
+        for ndim in 1..=8 {
+            let arena = PolytopeArena::new_simplex(ndim, 1.0).unwrap();
+            assert_eq!(arena.polytopes.len(), (1 << (ndim + 1)) - 1);
+            assert_eq!(arena.validate(), Ok(()));
+            let verts: Vec<&Vector<f32>> = arena.vertices().map(|(_, v)| v).collect();
+            assert_eq!(verts.len(), ndim as usize + 1);
+            for v in &verts {
+                assert!(crate::util::f32_approx_eq(v.mag(), ndim as f32));
+            }
+        }
+
+        for (edges, pole) in [
+            (vec![4, 3], vector![1.0]),
+            (vec![5, 3], vector![0.0, 0.0, 1.0]),
+        ] {
+            let generators = crate::CoxeterDiagram::with_edges(edges).generators();
+            let meshes = [InitialShape::Cube, InitialShape::Simplex].map(|initial_shape| {
+                let options = SliceOptions {
+                    initial_shape,
+                    debug_verify: true,
+                    ..Default::default()
+                };
+                let arena =
+                    shape_arena(3, &generators, std::slice::from_ref(&pole), &options).unwrap();
+                arena.mesh().unwrap()
+            });
+            let [mut cube_verts, mut simplex_verts] = meshes.clone().map(|m| m.verts);
+            crate::sort_and_dedup_points(&mut cube_verts, EPSILON);
+            crate::sort_and_dedup_points(&mut simplex_verts, EPSILON);
+            assert_eq!(cube_verts.len(), simplex_verts.len());
+            for (a, b) in cube_verts.iter().zip(&simplex_verts) {
+                assert!(a.approx_eq(b));
+            }
+            let [cube_sides, simplex_sides] = meshes.map(|m| {
+                let mut sides: Vec<usize> = m.polygons.iter().map(|p| p.len()).collect();
+                sides.sort_unstable();
+                sides
+            });
+            assert_eq!(cube_sides, simplex_sides);
+        }
+    }
+
+    #[test]
+    fn test_cells() {
+        let cells = PolytopeArena::new_cube(3, 1.0).unwrap().cells().unwrap();
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].polygons, [0, 1, 2, 3, 4, 5]);
+        assert!(cells[0].centroid.approx_eq(Vector::EMPTY));
+
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3, 3]).generators();
+        let arena =
+            shape_arena(4, &generators, &[Vector::unit(0)], &SliceOptions::default()).unwrap();
+        let mesh = arena.mesh().unwrap();
+        let cells = arena.cells().unwrap();
+        assert_eq!(cells.len(), 8);
+        let mut all_polygons = HashSet::new();
+        for cell in &cells {
+            assert_eq!(cell.polygons.len(), 6);
+            for &i in &cell.polygons {
+                assert_eq!(mesh.polygons[i as usize].len(), 4);
+                all_polygons.insert(i);
+            }
+            // The centroid of each cell is at the center of a facet.
+            assert!(crate::util::f32_approx_eq(cell.centroid.mag(), 1.0));
+        }
+        assert_eq!(all_polygons.len(), 24);
+        assert_eq!(mesh.polygons.len(), 24);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(PolytopeArena::new_cube(3, 1.0).unwrap().validate(), Ok(()));
+        assert_eq!(PolytopeArena::new_cube(4, 1.0).unwrap().validate(), Ok(()));
+
+        // Give an edge a third vertex, without linking it back.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        let face = arena.pieces[0];
+        let edge = arena[face].children()[0];
+        let vertex = arena[arena[face].children()[1]].children()[0];
+        arena[edge].unwrap_children_mut().push(vertex);
+        let defects = arena.validate().unwrap_err();
+        assert!(defects.contains(&ArenaDefect::WrongEdgeEndpoints { edge, count: 3 }));
+        assert!(defects.contains(&ArenaDefect::MissingParentLink {
+            parent: edge,
+            child: vertex,
+        }));
+
+        // Delete a vertex that is still in use.
+        let mut arena = PolytopeArena::new_cube(2, 1.0).unwrap();
+        arena.polytopes[vertex.0 as usize] = None;
+        let defects = arena.validate().unwrap_err();
+        let dangling = defects
+            .iter()
+            .filter(|d| matches!(d, ArenaDefect::DanglingReference { to, .. } if *to == vertex))
+            .count();
+        assert_eq!(dangling, 2);
+
+        // Delete the root.
+        arena.polytopes[face.0 as usize] = None;
+        let defects = arena.validate().unwrap_err();
+        assert!(defects.contains(&ArenaDefect::MissingRoot { piece: PieceId(0) }));
+    }
+
+    #[test]
+    fn test_weld_vertices() {
+        // Cut the cube through four of its vertices, leaving a triangular
+        // prism.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_by_plane(&Hyperplane::new(vector![1.0, 1.0], 0.0));
+        assert!(arena.mesh().unwrap().verts.len() > 6);
+
+        arena.weld_vertices(EPSILON);
+        assert_eq!(arena.validate(), Ok(()));
+        let mesh = arena.mesh().unwrap();
+        assert_eq!(mesh.verts.len(), 6);
+        for (i, a) in mesh.verts.iter().enumerate() {
+            for b in &mesh.verts[..i] {
+                assert!(!a.approx_eq(b));
+            }
+        }
+        for edge in arena.elements_of_rank(1) {
+            let [a, b] = [0, 1].map(|i| arena[arena[edge].children()[i]].unwrap_point());
+            assert!(!a.approx_eq(b));
+        }
+        let mut sides: Vec<usize> = mesh.polygons.iter().map(|p| p.len()).collect();
+        sides.sort_unstable();
+        assert_eq!(sides, [3, 3, 4, 4, 4]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_slicing() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![5, 3]).generators();
+        let facets = facet_orbit(3, &generators, &[vector![0.0, 0.0, 1.0]], 100).unwrap();
+        let meshes = [false, true].map(|parallel| {
+            let mut arena = PolytopeArena::new_cube(3, 6.0).unwrap();
+            arena.parallel = parallel;
+            for facet in &facets {
+                arena.slice_by_pole(&facet.pole);
+            }
+            arena.mesh().unwrap()
+        });
+        assert_eq!(meshes[0].polygons.len(), 12);
+        assert_eq!(meshes[0], meshes[1]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_shape_geom() {
+        let cases = [
+            (vec![5, 3], [vector![0.0, 0.0, 1.0]], 12),
+            (vec![4, 3, 3], [vector![1.0]], 8),
+        ];
+        for (edges, poles, facet_count) in cases {
+            let group = crate::CoxeterDiagram::with_edges(edges).group();
+            let [serial, parallel] = [false, true].map(|parallel| {
+                let options = SliceOptions {
+                    parallel,
+                    ..Default::default()
+                };
+                shape_geom_with_group(&group, &poles, &options).unwrap()
+            });
+            assert_eq!(serial.facets.len(), facet_count);
+            assert_eq!(serial, parallel);
+        }
+    }
+
+    /// Compares the speed of serial and parallel shape construction. Run with
+    /// `cargo test --release --features rayon -- --ignored --nocapture`.
+    #[cfg(feature = "rayon")]
+    #[test]
+    #[ignore]
+    fn bench_parallel_shape_geom() {
+        let group = crate::CoxeterDiagram::with_edges(vec![5, 3, 3]).group();
+        for parallel in [false, true] {
+            let options = SliceOptions {
+                parallel,
+                ..Default::default()
+            };
+            let start = std::time::Instant::now();
+            let shape = shape_geom_with_group(&group, &[vector![1.0]], &options).unwrap();
+            let elapsed = start.elapsed();
+            println!(
+                "parallel={parallel}: {} polygons in {elapsed:?}",
+                shape.polygons.len(),
+            );
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        /// Subscriber that records the name of every span.
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+        impl tracing::Subscriber for SpanNames {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut names = self.0.lock().unwrap();
+                names.push(span.metadata().name());
+                Id::from_u64(names.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let names = Arc::new(Mutex::new(vec![]));
+        tracing::subscriber::with_default(SpanNames(names.clone()), || {
+            let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+            let options = SliceOptions {
+                parallel: false,
+                ..Default::default()
+            };
+            shape_geom_with_group(&group, &[vector![1.0]], &options).unwrap();
+        });
+        let names = names.lock().unwrap();
+        assert_eq!(
+            names[..3],
+            ["generate_group", "facet_orbit", "arena_from_facets"]
+        );
+        assert_eq!(names.iter().filter(|&&name| name == "slice").count(), 6);
+        assert_eq!(names.last(), Some(&"mesh"));
+    }
+
+    #[test]
+    fn test_slice_8d() {
+        let mut rng = crate::testutil::Rng::new(8);
+        let mut arena = PolytopeArena::new_cube(8, 1.0).unwrap();
+        for _ in 0..5 {
+            arena.slice_by_plane(&Hyperplane::new(rng.unit_vector(8), 0.9));
+        }
+        assert_eq!(arena.validate(), Ok(()));
+        assert!(!arena.mesh().unwrap().polygons.is_empty());
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut rng = crate::testutil::Rng::new(1);
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        for _ in 0..20 {
+            let plane = Hyperplane::new(rng.unit_vector(3), rng.range_f32(0.6, 0.9));
+            arena.slice_by_plane(&plane);
+        }
+        let polygons = arena.polygons().unwrap();
+        let mesh = arena.mesh().unwrap();
+        let live_count = arena.polytopes.iter().flatten().count();
+        assert!(arena.polytopes.len() > live_count);
+
+        let old_ids = arena.compact();
+        assert_eq!(old_ids.len(), live_count);
+        assert_eq!(arena.polytopes.len(), live_count);
+        assert_eq!(arena.polygons().unwrap(), polygons);
+        assert_eq!(arena.mesh().unwrap(), mesh);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut rng = crate::testutil::Rng::new(2);
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        for _ in 0..10 {
+            let plane = Hyperplane::new(rng.unit_vector(3), rng.range_f32(0.6, 0.9));
+            arena.slice_by_plane(&plane);
+        }
+        let mesh = arena.mesh().unwrap();
+        let polytopes = arena.polytopes.clone();
+
+        let snapshot = arena.snapshot();
+        for _ in 0..10 {
+            let plane = Hyperplane::new(rng.unit_vector(3), rng.range_f32(0.6, 0.9));
+            arena.slice_by_plane(&plane);
+        }
+        arena.slice_into_pieces(&Hyperplane::new(Vector::unit(0), 0.1));
+        assert_eq!(arena.pieces().len(), 2);
+        arena.restore(&snapshot).unwrap();
+        assert_eq!(arena.polytopes, polytopes);
+        assert_eq!(arena.mesh().unwrap(), mesh);
+
+        // The snapshot can be restored more than once.
+        arena.weld_vertices(0.5);
+        arena.compact();
+        arena.restore(&snapshot).unwrap();
+        assert_eq!(arena.mesh().unwrap(), mesh);
+
+        arena.snapshot();
+        assert_eq!(arena.restore(&snapshot), Err(ArenaError::StaleSnapshot));
+    }
+
+    #[test]
+    fn test_slice_by_all() {
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        // The ball contains every corner except (1, 1, 1).
+        let center = vector![-1.0, -1.0, -1.0];
+        let planes = Hyperplane::sphere_approximation(&center, 2.9, 16, 3);
+        let stats = arena.slice_by_all(&planes);
+        assert!(stats.new_facet.is_some());
+        assert_eq!(arena.cut_count, 1);
+        arena.assert_euler_ok();
+
+        let corner = vector![1.0, 1.0, 1.0];
+        assert!(arena.vertices().all(|(_, v)| !v.approx_eq(&corner)));
+        assert!(arena.vertices().count() > 8);
+
+        let mesh = arena.mesh().unwrap();
+        let cut_polygons = mesh.facets.iter().filter(|&&f| f == Some(0)).count();
+        assert!(
+            cut_polygons > 1,
+            "expected several planes to cut the corner"
+        );
+        assert!(mesh.facets.iter().all(|&f| f.is_none() || f == Some(0)));
+    }
+
+    #[test]
+    fn test_element_orbits() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        // Square faces at distance 1 and triangular faces through the
+        // midpoints of the cube's edges.
+        let poles = [vector![1.0], vector![1.0, 1.0, 1.0] * (2.0 / 3.0)];
+        let cuboctahedron = shape_arena_with_group(&group, &poles, &Default::default()).unwrap();
+        assert_eq!(cuboctahedron.element_counts(), [12, 24, 14, 1]);
+
+        let faces = cuboctahedron.element_orbits(&group, 2);
+        let mut sizes: Vec<usize> = faces.iter().map(Vec::len).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [6, 8]);
+        for (i, orbit) in faces.iter().enumerate() {
+            let sides = cuboctahedron.children_of(orbit[0]).len();
+            for &face in orbit {
+                assert_eq!(cuboctahedron.orbit_of(&group, face), Some(i));
+                assert_eq!(cuboctahedron.children_of(face).len(), sides);
+            }
+        }
+        // Every vertex and every edge is alike.
+        assert_eq!(cuboctahedron.element_orbits(&group, 0).len(), 1);
+        assert_eq!(cuboctahedron.element_orbits(&group, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_facet_adjacency() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap();
+        let facets: Vec<PolytopeId> = cube.elements_of_rank(2).collect();
+        assert_eq!(cube.facet_adjacency().len(), 12);
+        for &f in &facets {
+            let neighbors = cube.adjacent_facets(f);
+            assert_eq!(neighbors.len(), 4);
+            // The opposite facet is the only one that isn't adjacent.
+            let normal = |p| crate::centroid(&cube.vertices_of(p)).unwrap();
+            let opposite = facets.iter().find(|&&g| normal(g).approx_eq(-normal(f)));
+            assert!(!neighbors.contains(opposite.unwrap()));
+        }
+        for [a, b] in cube.facet_adjacency() {
+            assert!(cube.adjacent_facets(a).contains(&b));
+        }
+
+        let group = crate::CoxeterDiagram::with_edges(vec![5, 3]).group();
+        let dodecahedron =
+            shape_arena_with_group(&group, &[vector![0.0, 0.0, 1.0]], &Default::default()).unwrap();
+        assert_eq!(dodecahedron.facet_adjacency().len(), 30);
+        for f in dodecahedron.elements_of_rank(2) {
+            assert_eq!(dodecahedron.adjacent_facets(f).len(), 5);
+        }
+
+        let square = PolytopeArena::new_cube(2, 1.0).unwrap();
+        assert_eq!(square.facet_adjacency().len(), 4);
+        assert!(PolytopeArena::new_cube(1, 1.0)
+            .unwrap()
+            .facet_adjacency()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vertex_figure() {
+        let cube = PolytopeArena::new_cube(3, 1.0).unwrap();
+        for (v, _) in cube.vertices() {
+            let triangle = cube.vertex_figure(v).unwrap();
+            assert_eq!(triangle.element_counts(), [3, 3, 1]);
+            assert_eq!(triangle.validate(), Ok(()));
+        }
+
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let poles = [vector![1.0, 1.0, 1.0]];
+        let octahedron = shape_arena_with_group(&group, &poles, &Default::default()).unwrap();
+        for (v, _) in octahedron.vertices() {
+            let square = octahedron.vertex_figure(v).unwrap();
+            assert_eq!(square.element_counts(), [4, 4, 1]);
+        }
+
+        let tesseract = PolytopeArena::new_cube(4, 1.0).unwrap();
+        let (v, _) = tesseract.vertices().next().unwrap();
+        let tetrahedron = tesseract.vertex_figure(v).unwrap();
+        assert_eq!(tetrahedron.element_counts(), [4, 6, 4, 1]);
+        assert_eq!(tetrahedron.validate(), Ok(()));
+
+        // A vertex in the middle of a cut edge has no vertex figure.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_into_pieces(&Hyperplane::new(vector![1.0], 0.0));
+        let (v, _) = arena
+            .vertices()
+            .find(|(_, p)| p.approx_eq(vector![0.0, 1.0, 1.0]))
+            .unwrap();
+        assert_eq!(
+            arena.vertex_figure(v).unwrap_err(),
+            SectionError::NoVertexFigure(v)
+        );
+        // Only the piece containing a vertex is included in its vertex figure.
+        let (v, _) = arena
+            .vertices()
+            .find(|(_, p)| p.approx_eq(vector![1.0, 1.0, 1.0]))
+            .unwrap();
+        let triangle = arena.vertex_figure(v).unwrap();
+        assert_eq!(triangle.pieces().len(), 1);
+        assert_eq!(triangle.element_counts(), [3, 3, 1]);
+
+        let edge = cube.elements_of_rank(1).next().unwrap();
+        assert_eq!(
+            cube.vertex_figure(edge).unwrap_err(),
+            SectionError::NotAVertex(edge)
+        );
+    }
+
+    #[test]
+    fn test_truncation() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let options = SliceOptions::default();
+        let cube = [vector![1.0]];
+
+        let poles = truncated_poles(&group, &cube, 0.5, &options).unwrap();
+        assert_eq!(poles.len(), 2);
+        let truncated_cube = shape_arena_with_group(&group, &poles, &options).unwrap();
+        assert_eq!(truncated_cube.element_counts(), [24, 36, 14, 1]);
+        assert_eq!(truncated_cube.element_orbits(&group, 2).len(), 2);
+
+        let poles = rectified_poles(&group, &cube, &options).unwrap();
+        // Through the midpoints of the edges around a vertex
+        assert!(poles[1]
+            .iter()
+            .all(|x| crate::util::f32_approx_eq(x.abs(), 2.0 / 3.0)));
+        let cuboctahedron = shape_arena_with_group(&group, &poles, &options).unwrap();
+        assert_eq!(cuboctahedron.element_counts(), [12, 24, 14, 1]);
+
+        // Truncating by 0 adds facets that only touch the vertices.
+        let poles = truncated_poles(&group, &cube, 0.0, &options).unwrap();
+        let shape = shape_geom_with_group(&group, &poles, &options).unwrap();
+        assert_eq!(shape.polygons.len(), 6);
+    }
+
+    #[test]
+    fn test_transformed() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let options = SliceOptions::default();
+        let cube = shape_arena_with_group(&group, &[vector![1.0]], &options).unwrap();
+
+        let (s, c) = 0.7_f32.sin_cos();
+        let (s2, c2) = 1.9_f32.sin_cos();
+        let rotation = &Matrix::from_cols([vector![c, s], vector![-s, c], vector![0.0, 0.0, 1.0]])
+            * &Matrix::from_cols([vector![1.0], vector![0.0, c2, s2], vector![0.0, -s2, c2]]);
+        assert!(rotation.is_orthogonal());
+        let rotated = cube.transformed(&rotation).unwrap();
+        assert_eq!(rotated.element_counts(), cube.element_counts());
+        assert_eq!(rotated.incidence_lattice(), cube.incidence_lattice());
+        assert_eq!(rotated.facet_adjacency(), cube.facet_adjacency());
+        assert!(crate::util::f32_approx_eq(rotated.volume(), cube.volume()));
+        assert!(!rotated.mesh().unwrap().verts[0].approx_eq(&cube.mesh().unwrap().verts[0]));
+
+        let conjugated = group.conjugated(&rotation).unwrap();
+        let mesh = rotated.mesh().unwrap();
+        assert_eq!(mesh.check_symmetry(&conjugated, EPSILON), Ok(()));
+        assert!(mesh.check_symmetry(&group, EPSILON).is_err());
+        assert!(conjugated
+            .elements()
+            .all(|e| conjugated.matrix(e).is_orthogonal()));
+
+        // Scaling changes the volume.
+        let scaled = cube.transformed(&Matrix::ident(3).scale(2.0)).unwrap();
+        assert!(crate::util::f32_approx_eq(scaled.volume(), 64.0));
+        // The determinant of a small scale is tiny, but it is still
+        // invertible.
+        let small = Matrix::ident(3).scale(0.05);
+        let scaled = cube.transformed(&small).unwrap();
+        assert!(crate::util::f32_approx_eq(scaled.volume(), 0.001));
+        let conjugated = group.conjugated(&small).unwrap();
+        let mesh = scaled.mesh().unwrap();
+        assert_eq!(mesh.check_symmetry(&conjugated, EPSILON * 0.05), Ok(()));
+
+        let stretched =
+            Matrix::from_cols([vector![2.0], vector![0.0, 1.0], vector![0.0, 0.0, 1.0]]);
+        assert_eq!(
+            group.conjugated(&stretched).unwrap_err(),
+            crate::GroupError::NonOrthogonalMatrix,
+        );
+        assert!(group.conjugated_non_orthogonal(&stretched).is_ok());
+
+        let singular = Matrix::from_cols([vector![1.0], vector![1.0], vector![0.0, 0.0, 1.0]]);
+        assert_eq!(
+            cube.transformed(&singular).unwrap_err(),
+            ArenaError::SingularMatrix
+        );
+        assert_eq!(
+            group.conjugated(&singular).unwrap_err(),
+            crate::GroupError::SingularMatrix,
+        );
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let dodecahedron = crate::CoxeterDiagram::with_edges(vec![5, 3]).generators();
+        let cube = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let absolute = SliceOptions {
+            tolerance: Tolerance::absolute(EPSILON),
+            ..Default::default()
+        };
+        let relative = SliceOptions {
+            tolerance: Tolerance::relative(EPSILON),
+            ..Default::default()
+        };
+
+        // A tiny shape is smaller than the absolute tolerance.
+        let tiny = [vector![0.0, 0.0, 1e-4]];
+        assert_eq!(
+            shape_arena(3, &dodecahedron, &tiny, &absolute).unwrap_err(),
+            ShapeGeomError::ZeroPole { base_facet: 0 },
+        );
+        let arena = shape_arena(3, &dodecahedron, &tiny, &relative).unwrap();
+        assert_eq!(arena.element_counts(), [20, 30, 12, 1]);
+
+        // Cutting off the corners of a large cube by a small amount is lost
+        // within the relative tolerance.
+        let depth = 0.5 / 3.0_f32.sqrt();
+        let poles = [vector![1000.0], vector![1.0, 1.0, 1.0] * (1000.0 - depth)];
+        let arena = shape_arena(3, &cube, &poles, &absolute).unwrap();
+        assert_eq!(arena.element_counts(), [24, 36, 14, 1]);
+        let arena = shape_arena(3, &cube, &poles, &relative).unwrap();
+        assert_eq!(arena.element_counts(), [8, 12, 6, 1]);
+    }
+
+    #[test]
+    fn test_stabilizer() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let cube = shape_arena_with_group(&group, &[vector![1.0]], &Default::default()).unwrap();
+        for (rank, expected) in [(0, 6), (1, 4), (2, 8), (3, 48)] {
+            let orbits = cube.element_orbits(&group, rank);
+            assert_eq!(orbits.len(), 1);
+            for &p in &orbits[0] {
+                let stabilizer = cube.stabilizer(&group, p);
+                assert_eq!(stabilizer.len(), expected);
+                assert_eq!(stabilizer[0], GroupElement::IDENT);
+                assert_eq!(orbits[0].len() * stabilizer.len(), 48);
+            }
+        }
+    }
+
+    #[test]
+    fn test_facet_orbit_indices() {
+        let group = crate::CoxeterDiagram::with_edges(vec![4, 3]).group();
+        let poles = [vector![1.0], vector![1.0, 1.0, 1.0] * (2.0 / 3.0)];
+        let options = SliceOptions::default();
+        let facets = shape_geom_with_group(&group, &poles, &options)
+            .unwrap()
+            .facets;
+        assert_eq!(facets.len(), 6 + 8);
+        for (base_facet, orbit_size) in [(0, 6), (1, 8)] {
+            let orbit: Vec<&Facet> = facets
+                .iter()
+                .filter(|f| f.base_facet == base_facet)
+                .collect();
+            assert_eq!(orbit.len(), orbit_size);
+            for (i, facet) in orbit.into_iter().enumerate() {
+                assert_eq!(facet.orbit_index, i);
+                let e = facet.group_element.unwrap();
+                assert!(group
+                    .matrix(e)
+                    .transform(&poles[base_facet])
+                    .approx_eq(&facet.pole));
+            }
+        }
+        // Facet metadata is the same every time.
+        assert_eq!(
+            shape_geom_with_group(&group, &poles, &options)
+                .unwrap()
+                .facets,
+            facets
+        );
+
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let facets = facet_orbit(3, &generators, &poles, 100).unwrap();
+        assert!(facets.iter().all(|f| f.group_element.is_none()));
+        assert_eq!(facets.iter().filter(|f| f.orbit_index == 7).count(), 1);
+    }
+
+    #[test]
+    fn test_slice_progress() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let options = SliceOptions::default();
+        let mut calls = vec![];
+        let shape = shape_geom_with_progress(3, &generators, &[vector![1.0]], &options, |p| {
+            calls.push(p);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(shape.facets.len(), 6);
+        assert_eq!(calls.len(), 6);
+        for (i, p) in calls.iter().enumerate() {
+            assert_eq!(p.planes_completed, i + 1);
+            assert_eq!(p.plane_count, 6);
+            assert!(p.polytope_count > 0);
+        }
+        assert_eq!(calls[5].fraction(), 1.0);
+
+        let result =
+            shape_geom_with_progress(3, &generators, &[vector![1.0]], &options, |p| {
+                match p.planes_completed < 2 {
+                    true => ControlFlow::Continue(()),
+                    false => ControlFlow::Break(()),
+                }
+            });
+        assert_eq!(result, Err(ShapeGeomError::Arena(ArenaError::Cancelled)));
+
+        // Cancelling leaves the planes sliced so far as one cut.
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        let planes: Vec<Hyperplane> = (0..3)
+            .flat_map(|axis| {
+                [1.0, -1.0].map(|sign| Hyperplane::new(Vector::unit(axis) * sign, 0.5))
+            })
+            .collect();
+        let mut calls = 0;
+        let result = arena.slice_by_all_with_progress(&planes, |p| {
+            calls += 1;
+            match p.planes_completed < 2 {
+                true => ControlFlow::Continue(()),
+                false => ControlFlow::Break(()),
+            }
+        });
+        assert_eq!(result, Err(ArenaError::Cancelled));
+        assert_eq!(calls, 2);
+        assert_eq!(arena.validate(), Ok(()));
+        assert_eq!(arena.cut_count, 1);
+        arena.assert_euler_ok();
+        assert!(crate::util::f32_approx_eq(arena.volume(), 4.0));
+    }
+
+    #[test]
+    fn test_slice_stats() {
+        let options = SliceOptions {
+            log_slices: true,
+            ..Default::default()
+        };
+        let mut arena = PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .with_slice_options(options);
+
+        let stats = arena.slice_by_plane(&Hyperplane::new(vector![1.0], 5.0));
+        assert_eq!(
+            stats,
+            SliceStats {
+                removed: 0,
+                kept: 27,
+                modified: 0,
+                created: 0,
+                new_facet: None,
+            },
+        );
+
+        // A central cut removes 4 vertices, 4 edges, and a face, and splits 4
+        // edges, 4 faces, and the cube.
+        let stats = arena.slice_by_plane(&Hyperplane::new(vector![1.0], 0.0));
+        assert_eq!(stats.removed, 9);
+        assert_eq!(stats.kept, 9);
+        assert_eq!(stats.modified, 9);
+        assert_eq!(stats.created, 9);
+        let new_facet = stats.new_facet.unwrap();
+        assert_eq!(arena.children_of(new_facet).len(), 4);
+        assert!(arena[new_facet].facet == Some(1));
+
+        let stats = arena.slice_into_pieces(&Hyperplane::new(vector![0.0, 1.0], 0.0));
+        assert_eq!(stats.modified, 9);
+        assert_eq!(stats.created, 18);
+
+        assert_eq!(arena.slice_log().len(), 3);
+        assert_eq!(arena.slice_log()[2], stats);
+    }
+
+    #[test]
+    fn test_slice_into_pieces() {
+        let mut arena = PolytopeArena::new_cube(3, 1.0).unwrap();
+        arena.slice_into_pieces(&Hyperplane::new(vector![1.0], 0.0));
+        assert_eq!(arena.pieces().len(), 2);
+        let mut cut_faces = 0;
+        for piece in arena.pieces() {
+            let polygons = arena.polygons_of_piece(piece).unwrap();
+            assert_eq!(polygons.len(), 6);
+            assert!(polygons.iter().all(|p| p.verts.len() == 4));
+            cut_faces += polygons
+                .iter()
+                .filter(|p| p.verts.iter().all(|v| v[0].abs() < EPSILON))
+                .count();
+            assert_eq!(unique_vertices(&polygons).len(), 8);
+        }
+        assert_eq!(cut_faces, 2);
+
+        arena.slice_into_pieces(&Hyperplane::new(vector![0.0, 1.0], 0.0));
+        arena.slice_into_pieces(&Hyperplane::new(vector![0.0, 0.0, 1.0], 0.0));
+        assert_eq!(arena.pieces().len(), 8);
+        let mut corners = vec![];
+        for piece in arena.pieces() {
+            let polygons = arena.polygons_of_piece(piece).unwrap();
+            assert_eq!(polygons.len(), 6);
+            let verts = unique_vertices(&polygons);
+            assert_eq!(verts.len(), 8);
+            // Each piece is a unit cube with one corner at the origin and the
+            // opposite corner at a corner of the original cube.
+            let corner = verts.iter().find(|v| v.iter().all(|x| x.abs() == 1.0));
+            corners.push(corner.expect("missing corner").clone());
+        }
+        crate::sort_and_dedup_points(&mut corners, EPSILON);
+        assert_eq!(corners.len(), 8);
+    }
+
+    #[test]
+    fn test_facet_orbit_120_cell() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![5, 3, 3]).generators();
+        let facets = facet_orbit(4, &generators, &[vector![1.0]], 10_000).unwrap();
+        assert_eq!(facets.len(), 120);
+        for (i, facet) in facets.iter().enumerate() {
+            assert!(facet
+                .transform
+                .transform(vector![1.0])
+                .approx_eq(&facet.pole));
+            assert!(facets[..i]
+                .iter()
+                .all(|f| !poles_approx_eq(&f.pole, &facet.pole)));
+        }
+    }
+
+    #[test]
+    fn test_pole_set_matches_linear_search() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        for seed in 0..50 {
+            let mut rng = crate::testutil::Rng::new(seed);
+            let base = rng.vector(3, -2.0, 2.0);
+            let mut facets =
+                facet_orbit(3, &generators, std::slice::from_ref(&base), 10_000).unwrap();
+            // Add perturbed copies, some of which are within tolerance.
+            for i in 0..facets.len() {
+                let scale = rng.range_f32(0.0, 2.0) * EPSILON * base.mag();
+                let pole = &facets[i].pole + rng.unit_vector(3) * scale;
+                facets.push(Facet {
+                    pole,
+                    ..facets[i].clone()
+                });
+            }
+
+            let mut hashed = PoleSet::new(&[base]);
+            let mut deduped: Vec<Facet> = vec![];
+            for facet in facets {
+                let is_new = deduped
+                    .iter()
+                    .all(|f| !poles_approx_eq(&f.pole, &facet.pole));
+                assert_eq!(hashed.insert(&deduped, &facet.pole), is_new, "seed {seed}");
+                if is_new {
+                    deduped.push(facet);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_polygon_filtering() {
+        // A cut exactly through an edge of the cube, with no tolerance, leaves
+        // a sliver along that edge.
+        let options = SliceOptions {
+            tolerance: Tolerance::absolute(0.0),
+            ..Default::default()
+        };
+        let mut arena = PolytopeArena::new_cube(3, 1.0)
+            .unwrap()
+            .with_slice_options(options);
+        let normal = vector![1.0, 1.0].normalized().unwrap();
+        arena.slice_by_plane(&Hyperplane::new(normal, 2.0_f32.sqrt()));
+        let mesh = arena.mesh().unwrap();
+        assert_eq!(mesh.polygons.len(), 7);
+        let slivers = mesh
+            .to_polygons()
+            .iter()
+            .filter(|p| p.area() < 1e-6)
+            .count();
+        assert_eq!(slivers, 1);
+
+        let mut arena = arena.with_slice_options(SliceOptions {
+            min_polygon_area: 1e-6,
+            ..options
+        });
+        let FilteredMesh { mesh, dropped, .. } = arena.filtered_mesh().unwrap();
+        assert_eq!(dropped, 1);
+        assert_eq!(mesh.polygons.len(), 6);
+        let mut used = vec![false; mesh.verts.len()];
+        for polygon in &mesh.polygons {
+            assert!(polygon.len() >= 3);
+            for &i in polygon {
+                used[i as usize] = true;
+            }
+        }
+        assert!(used.iter().all(|&u| u), "unused vertex in mesh");
+        assert_eq!(mesh.facets.len(), mesh.polygons.len());
+        for polygon in mesh.to_polygons() {
+            assert!(polygon.area() >= 1e-6);
+            assert!(polygon.normal().is_some());
+        }
+
+        // Cells refer to the polygons of the filtered mesh.
+        let cells = arena.cells().unwrap();
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].polygons, [0, 1, 2, 3, 4, 5]);
+
+        // Welding the vertices also removes the sliver, so none is dropped.
+        arena.weld_vertices(0.01);
+        assert_eq!(arena.filtered_mesh().unwrap().dropped, 0);
+    }
+
+    #[test]
+    fn test_polygon_normal() {
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let shape = shape_geom(3, &generators, &[vector![1.0]], &Default::default()).unwrap();
+        assert_eq!(shape.dropped_polygons, 0);
+        for polygon in &shape.polygons {
+            let normal = polygon.normal().unwrap();
+            let center = polygon.centroid().unwrap();
+            // Each face of the cube is perpendicular to its pole.
+            assert!(normal.approx_eq(&center) || normal.approx_eq(-&center));
+        }
+        let degenerate = Polygon {
+            verts: vec![vector![0.0, 0.0], vector![1.0, 1.0], vector![2.0, 2.0]],
+            facet: None,
+        };
+        assert_eq!(degenerate.normal(), None);
+        let triangle = Polygon {
+            verts: vec![vector![0.0, 0.0], vector![1.0, 0.0], vector![0.0, 1.0]],
+            facet: None,
+        };
+        assert!(triangle.normal().unwrap().approx_eq(vector![0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_double_precision() {
+        // Slices a cube into slabs that are too thin for `f32` to separate,
+        // and returns the number of distinct cuts among the vertices.
+        fn cut_count<N: Real>() -> usize {
+            let options = SliceOptions {
+                tolerance: Tolerance {
+                    abs: 1e-9,
+                    rel: 1e-9,
+                },
+                ..Default::default()
+            };
+            let mut arena = PolytopeArena::<N>::new_cube(3, N::from_f32(10000.0))
+                .unwrap()
+                .with_slice_options(options);
+            for i in 0..50 {
+                let offset = N::from_f32(5000.0) + N::from_f32(1e-4) * N::from_f32(i as f32);
+                arena.slice_into_pieces(&Hyperplane::new(Vector::unit(0), offset));
+            }
+            let mut cuts: Vec<f64> = arena
+                .elements_of_rank(0)
+                .filter_map(|v| arena[v].unwrap_point().get(0).to_f64())
+                .filter(|&x| (x - 5000.0).abs() < 1.0)
+                .collect();
+            cuts.sort_by(f64::total_cmp);
+            cuts.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
+            cuts.len()
+        }
+        assert_eq!(cut_count::<f64>(), 50);
+        // `f32` can't represent every offset, so some cuts coincide.
+        assert!(cut_count::<f32>() < 50);
+
+        let arena = PolytopeArena64::new_cube(3, 1.0).unwrap();
+        assert!(crate::util::f32_approx_eq(
+            arena.cast::<f32>().volume(),
+            8.0
+        ));
+
+        let generators = crate::CoxeterDiagram::with_edges(vec![4, 3]).generators();
+        let options = SliceOptions {
+            precision: Precision::Double,
+            ..Default::default()
+        };
+        let double = shape_geom(3, &generators, &[vector![1.0]], &options).unwrap();
+        let single = shape_geom(3, &generators, &[vector![1.0]], &Default::default()).unwrap();
+        assert_eq!(double.polygons.len(), single.polygons.len());
+        for (a, b) in double.polygons.iter().zip(&single.polygons) {
+            assert!(a.verts.iter().zip(&b.verts).all(|(a, b)| a.approx_eq(b)));
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+enum SliceResult {
+    /// The slice result hasn't been computed yet.
+    #[default]
+    Unknown,
+
+    /// The entire polytope is inside the slicing hyperplane.
+    Kept,
+    /// The entire polytope is outside the slicing hyperplane.
+    Removed,
+    /// The polytope was modified by the slice so that it is inside the slicing
+    /// hyperplane.
+    Modified {
+        /// Intersection of the polytope and the slicing hyperplane.
+        intersection: PolytopeId,
+        /// Part of the polytope outside the slicing hyperplane, if it was
+        /// kept.
+        outside: Option<PolytopeId>,
+    },
 }
@@ -0,0 +1,235 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use crate::coxeter::{CoxeterDiagram, GroupCache};
+use crate::error::CoxeterError;
+use crate::group::Group;
+use crate::matrix::Matrix;
+use crate::mesh::Mesh;
+use crate::polytope::{
+    shape_arena_with_group_and_progress, PolytopeArena, SliceOptions, SliceProgress,
+};
+use crate::util::Tolerance;
+use crate::vector::Vector;
+
+/// Default maximum order of the symmetry group of a [`ShapeSpec`].
+pub const DEFAULT_MAX_GROUP_ORDER: u32 = 100_000;
+
+/// Description of a shape bounded by the orbits of some base facets under the
+/// symmetry group of a Coxeter diagram, which can be generated in one call.
+#[derive(Debug, Clone)]
+pub struct ShapeSpec {
+    diagram: String,
+    poles: Vec<Pole>,
+    options: SliceOptions,
+    max_group_order: u32,
+    cache: Option<Arc<GroupCache>>,
+}
+
+/// Base facet pole of a [`ShapeSpec`], before converting to world
+/// coordinates.
+#[derive(Debug, Clone)]
+enum Pole {
+    MirrorBasis(Vector<f32>),
+    World(Vector<f32>),
+}
+
+/// Shape generated from a [`ShapeSpec`].
+#[derive(Debug, Clone)]
+pub struct GeneratedShape {
+    /// Coxeter diagram of the symmetry group.
+    pub diagram: CoxeterDiagram,
+    /// Symmetry group of the shape.
+    pub group: Arc<Group>,
+    /// Pole of each base facet, in world coordinates.
+    pub poles: Vec<Vector<f32>>,
+    /// Arena containing the shape, which has its full incidence structure.
+    /// See [`PolytopeArena::incidence_lattice()`].
+    pub arena: PolytopeArena,
+    /// Polygons of the shape, tagged with the facet that each one lies on.
+    /// See [`Mesh::facets`].
+    pub mesh: Mesh,
+}
+
+impl ShapeSpec {
+    /// Constructs a specification for a shape with the symmetry group of the
+    /// Coxeter diagram `diagram`, written as comma-separated edge labels such
+    /// as `"4,3"`. The diagram is only parsed by [`ShapeSpec::build()`].
+    pub fn new(diagram: impl Into<String>) -> Self {
+        Self {
+            diagram: diagram.into(),
+            poles: vec![],
+            options: SliceOptions::default(),
+            max_group_order: DEFAULT_MAX_GROUP_ORDER,
+            cache: None,
+        }
+    }
+
+    /// Adds a base facet with a pole given in the basis of the mirrors of the
+    /// diagram: coordinate `i` is the dot product of the pole with the mirror
+    /// vector of node `n - 1 - i`, where `n` is the number of nodes. Missing
+    /// coordinates are zero and extra coordinates are ignored.
+    #[must_use]
+    pub fn pole_mirror_basis(mut self, pole: impl Into<Vector<f32>>) -> Self {
+        self.poles.push(Pole::MirrorBasis(pole.into()));
+        self
+    }
+    /// Adds a base facet with a pole in world coordinates. Missing
+    /// coordinates are zero and extra coordinates are ignored.
+    #[must_use]
+    pub fn pole_world(mut self, pole: impl Into<Vector<f32>>) -> Self {
+        self.poles.push(Pole::World(pole.into()));
+        self
+    }
+    /// Sets the tolerance for slicing. See [`SliceOptions::tolerance`].
+    #[must_use]
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.options.tolerance = tolerance;
+        self
+    }
+    /// Sets the options for slicing, replacing any tolerance set by
+    /// [`ShapeSpec::tolerance()`].
+    #[must_use]
+    pub fn options(mut self, options: SliceOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// Sets the maximum order of the symmetry group, beyond which building
+    /// fails. The default is [`DEFAULT_MAX_GROUP_ORDER`].
+    #[must_use]
+    pub fn max_group_order(mut self, max_group_order: u32) -> Self {
+        self.max_group_order = max_group_order;
+        self
+    }
+    /// Sets a cache to look up the symmetry group in, so that building many
+    /// shapes with the same diagram only generates the group once.
+    #[must_use]
+    pub fn group_cache(mut self, cache: Arc<GroupCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Generates the shape.
+    pub fn build(&self) -> Result<GeneratedShape, CoxeterError> {
+        self.build_with_progress(|_| ControlFlow::Continue(()))
+    }
+    /// Generates the shape, calling `progress` after each facet is sliced. If
+    /// `progress` returns [`ControlFlow::Break`], building stops and
+    /// [`ArenaError::Cancelled`](crate::ArenaError::Cancelled) is returned.
+    pub fn build_with_progress(
+        &self,
+        progress: impl FnMut(SliceProgress) -> ControlFlow<()>,
+    ) -> Result<GeneratedShape, CoxeterError> {
+        let diagram: CoxeterDiagram = self.diagram.parse()?;
+        let group = match &self.cache {
+            Some(cache) => diagram.try_group_cached(cache, self.max_group_order)?,
+            None => Arc::new(diagram.try_group(self.max_group_order)?),
+        };
+        let ndim = diagram.ndim();
+        // The mirror vectors of a finite group are linearly independent, so
+        // this is invertible.
+        let mirror_basis = Matrix::from_cols(diagram.mirrors().iter().rev().map(|m| &m.0))
+            .inverse()
+            .transpose();
+        let poles: Vec<Vector<f32>> = self
+            .poles
+            .iter()
+            .map(|pole| match pole {
+                Pole::MirrorBasis(v) => mirror_basis.transform(with_ndim(v, ndim)),
+                Pole::World(v) => with_ndim(v, ndim),
+            })
+            .collect();
+
+        let arena = shape_arena_with_group_and_progress(&group, &poles, &self.options, progress)?;
+        let mesh = arena.mesh()?;
+        Ok(GeneratedShape {
+            diagram,
+            group,
+            poles,
+            arena,
+            mesh,
+        })
+    }
+}
+
+/// Returns `v` truncated or padded with zeros to `ndim` dimensions.
+fn with_ndim(v: &Vector<f32>, ndim: u8) -> Vector<f32> {
+    let mut ret = v.clone();
+    ret.set_ndim(ndim);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiagramError, GroupError, ShapeGeomError, VectorRef};
+
+    #[test]
+    fn test_shape_spec() {
+        let cube = ShapeSpec::new("4,3")
+            .pole_world(vector![1.0])
+            .build()
+            .unwrap();
+        assert_eq!(cube.arena.element_counts(), [8, 12, 6, 1]);
+        assert_eq!(cube.mesh.polygons.len(), 6);
+        assert_eq!(cube.group.order(), 48);
+
+        let octahedron = ShapeSpec::new("4,3")
+            .pole_world([1.0, 1.0, 1.0])
+            .tolerance(Tolerance::relative(1e-5))
+            .build()
+            .unwrap();
+        assert_eq!(octahedron.arena.element_counts(), [6, 12, 8, 1]);
+
+        // A pole in the mirror basis is on every mirror except one.
+        let shape = ShapeSpec::new("4,3")
+            .pole_mirror_basis([1.0])
+            .build()
+            .unwrap();
+        let mirrors = shape.diagram.mirrors();
+        let dots: Vec<f32> = mirrors.iter().map(|m| m.0.dot(&shape.poles[0])).collect();
+        assert!(dots[0].abs() < 1e-6 && dots[1].abs() < 1e-6);
+        assert!((dots[2] - 1.0).abs() < 1e-6);
+        let world = ShapeSpec::new("4,3").pole_world(shape.poles[0].clone());
+        assert_eq!(world.build().unwrap().mesh, shape.mesh);
+
+        let cache = Arc::new(GroupCache::new());
+        let spec = ShapeSpec::new("4,3")
+            .pole_world(vector![1.0])
+            .group_cache(cache.clone());
+        let [a, b] = [(), ()].map(|()| spec.build().unwrap());
+        assert!(Arc::ptr_eq(&a.group, &b.group));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_shape_spec_errors() {
+        let err = |spec: ShapeSpec| spec.build().unwrap_err();
+        assert!(matches!(
+            err(ShapeSpec::new("4,x").pole_world(vector![1.0])),
+            CoxeterError::Diagram(DiagramError::ParseEdge { index: 1, .. }),
+        ));
+        assert!(matches!(
+            err(ShapeSpec::new("4,1").pole_world(vector![1.0])),
+            CoxeterError::Diagram(DiagramError::InvalidEdge { index: 1, value: 1 }),
+        ));
+        assert!(matches!(
+            err(ShapeSpec::new("7,3").pole_world(vector![1.0])),
+            CoxeterError::Group(GroupError::NonFiniteGenerator { .. }),
+        ));
+        assert!(matches!(
+            err(ShapeSpec::new("4,3")
+                .pole_world(vector![1.0])
+                .max_group_order(10)),
+            CoxeterError::Group(GroupError::TooManyElements { limit: 10 }),
+        ));
+        assert!(matches!(
+            err(ShapeSpec::new("4,3")),
+            CoxeterError::ShapeGeom(ShapeGeomError::NoBaseFacets),
+        ));
+        assert!(matches!(
+            err(ShapeSpec::new("4,3").pole_world(vector![0.0])),
+            CoxeterError::ShapeGeom(ShapeGeomError::ZeroPole { base_facet: 0 }),
+        ));
+    }
+}
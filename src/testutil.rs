@@ -0,0 +1,122 @@
+//! Deterministic pseudo-random generation of vectors and matrices, for
+//! property tests.
+//!
+//! This only uses integer arithmetic and IEEE-754 basic operations, so results
+//! are identical on every platform.
+
+use crate::matrix::Matrix;
+use crate::vector::{Vector, VectorRef};
+
+/// Tiny pseudo-random number generator (SplitMix64).
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a number uniformly distributed in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1_u64 << 24) as f32
+    }
+    /// Returns a number uniformly distributed in `[min, max)`.
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+    /// Returns a number with an approximately standard normal distribution.
+    pub fn normal_f32(&mut self) -> f32 {
+        // Irwin-Hall approximation, which avoids transcendental functions.
+        (0..12).map(|_| self.next_f32()).sum::<f32>() - 6.0
+    }
+
+    /// Returns a vector with each component uniformly distributed in
+    /// `[min, max)`.
+    pub fn vector(&mut self, ndim: u8, min: f32, max: f32) -> Vector<f32> {
+        (0..ndim).map(|_| self.range_f32(min, max)).collect()
+    }
+    /// Returns a unit vector with an approximately uniform distribution over
+    /// the sphere.
+    pub fn unit_vector(&mut self, ndim: u8) -> Vector<f32> {
+        loop {
+            let v: Vector<f32> = (0..ndim).map(|_| self.normal_f32()).collect();
+            if let Some(v) = v.normalized() {
+                return v;
+            }
+        }
+    }
+    /// Returns a rotation matrix (orthogonal with determinant 1).
+    pub fn rotation(&mut self, ndim: u8) -> Matrix<f32> {
+        loop {
+            let cols = (0..ndim)
+                .map(|_| self.unit_vector(ndim))
+                .collect::<Vec<_>>();
+            if let Some(mut m) = Matrix::complete_basis(&cols, ndim) {
+                if m.determinant() < 0.0 {
+                    for row in 0..ndim {
+                        *m.get_mut(0, row) = -m.get(0, row);
+                    }
+                }
+                return m;
+            }
+        }
+    }
+}
+
+impl Vector<f32> {
+    /// Returns a pseudo-random unit vector determined by `seed`.
+    pub fn random_unit(ndim: u8, seed: u64) -> Self {
+        Rng::new(seed).unit_vector(ndim)
+    }
+}
+
+impl Matrix<f32> {
+    /// Returns a pseudo-random rotation matrix determined by `seed`.
+    pub fn random_rotation(ndim: u8, seed: u64) -> Self {
+        Rng::new(seed).rotation(ndim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let mut rng = Rng::new(42);
+        assert_eq!(rng.next_u64(), 0xBDD7_3226_2FEB_6E95);
+        assert_eq!(rng.next_u64(), 0x28EF_E333_B266_F103);
+
+        for seed in 0..10 {
+            let a = Vector::random_unit(5, seed);
+            let b = Vector::random_unit(5, seed);
+            assert_eq!(
+                a.iter().map(f32::to_bits).collect::<Vec<_>>(),
+                b.iter().map(f32::to_bits).collect::<Vec<_>>()
+            );
+            assert!(a.is_unit());
+            assert_eq!(
+                Matrix::random_rotation(4, seed),
+                Matrix::random_rotation(4, seed)
+            );
+        }
+        assert_ne!(Vector::random_unit(5, 1), Vector::random_unit(5, 2));
+    }
+
+    #[test]
+    fn test_random_rotation() {
+        for ndim in 1..=5 {
+            for seed in 0..20 {
+                let m = Matrix::random_rotation(ndim, seed);
+                assert_eq!(m.ndim(), ndim);
+                assert!(m.is_special_orthogonal(), "{m:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Instrumentation using the `tracing` crate, which compiles to nothing
+//! without the `tracing` feature.
+
+/// Enters a span at the given level until the end of the enclosing block.
+macro_rules! enter_span {
+    ($level:ident, $($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $($args)*).entered();
+    };
+}
+
+/// Emits an event at the given level.
+macro_rules! trace_event {
+    ($level:ident, $($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($args)*);
+    };
+}
@@ -4,10 +4,131 @@ pub fn f32_approx_eq(a: f32, b: f32) -> bool {
     (a - b).abs() < EPSILON
 }
 
-pub fn factorial(n: usize) -> usize {
-    (2..=n).fold(1, |x, y| x * y)
+/// Returns whether `a` and `b` differ by at most `abs`, or by at most `rel`
+/// times the larger of their magnitudes. The absolute tolerance handles
+/// numbers near zero, where a relative tolerance is too strict.
+pub fn approx_eq_rel(a: f32, b: f32, rel: f32, abs: f32) -> bool {
+    let diff = (a - b).abs();
+    diff <= abs || diff <= rel * f32::max(a.abs(), b.abs())
 }
 
+/// Returns whether `a` and `b` are at most `max_ulps` representable `f32`
+/// values apart. Zeros of either sign are equal, and NaN is never equal to
+/// anything.
+///
+/// This is only useful for values that should be equal up to rounding error,
+/// since numbers near zero that differ only slightly can be very many ULPs
+/// apart.
+pub fn approx_eq_ulps(a: f32, b: f32, max_ulps: u32) -> bool {
+    if a.is_nan() || b.is_nan() {
+        return false;
+    }
+    // Map the bits of each float to an integer such that adjacent floats map
+    // to adjacent integers, with both zeros at 0.
+    let key = |x: f32| {
+        let bits = x.to_bits() as i32;
+        if bits < 0 {
+            i32::MIN.wrapping_sub(bits) as i64
+        } else {
+            bits as i64
+        }
+    };
+    (key(a) - key(b)).unsigned_abs() <= max_ulps as u64
+}
+
+/// Tolerance for comparing floating-point numbers, with a component that is
+/// absolute and a component that is relative to the magnitude of the numbers
+/// being compared. Neither works alone for every shape: an absolute tolerance
+/// merges everything in a tiny shape, and a relative tolerance is too strict
+/// for numbers near zero.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tolerance {
+    /// Absolute tolerance.
+    pub abs: f32,
+    /// Tolerance relative to the magnitude of the numbers being compared.
+    pub rel: f32,
+}
+impl Default for Tolerance {
+    /// Returns the absolute tolerance `EPSILON`, which is the default for
+    /// group generation. See [`GroupOptions`](crate::GroupOptions).
+    fn default() -> Self {
+        Self::absolute(EPSILON)
+    }
+}
+impl Tolerance {
+    /// Tolerance used by `Vector::approx_eq()` and `Matrix::approx_eq()`,
+    /// relative to the magnitude of the vectors or matrices being compared.
+    pub const APPROX_EQ: Self = Self {
+        abs: EPSILON,
+        rel: EPSILON,
+    };
+
+    /// Returns a purely absolute tolerance.
+    pub const fn absolute(abs: f32) -> Self {
+        Self { abs, rel: 0.0 }
+    }
+    /// Returns a purely relative tolerance.
+    pub const fn relative(rel: f32) -> Self {
+        Self { abs: 0.0, rel }
+    }
+
+    /// Returns the maximum difference between numbers of magnitude `scale`
+    /// for them to be considered equal.
+    pub fn eps(self, scale: f32) -> f32 {
+        self.abs + self.rel * scale
+    }
+    /// Returns whether two numbers are equal within the tolerance.
+    pub fn approx_eq(self, a: f32, b: f32) -> bool {
+        (a - b).abs() <= self.eps(f32::max(a.abs(), b.abs()))
+    }
+}
+
+/// Returns `n!`, which is exact for `n <= 34`.
+///
+/// # Panics
+///
+/// Panics if the result overflows `u128`. See [`checked_factorial()`].
+// Overflow is a documented panic; see `checked_factorial()`.
+#[allow(clippy::expect_used)]
+pub fn factorial(n: u32) -> u128 {
+    (2..=n as u128)
+        .try_fold(1_u128, |acc, i| acc.checked_mul(i))
+        .expect("factorial overflow")
+}
+/// Returns `n!`, or `None` if it overflows `u64` (when `n > 20`).
+pub fn checked_factorial(n: u32) -> Option<u64> {
+    (2..=n as u64).try_fold(1_u64, |acc, i| acc.checked_mul(i))
+}
+/// Returns the binomial coefficient `n` choose `k`, which is 0 if `k > n`, or
+/// `None` if it overflows `u64`.
+pub fn binomial(n: u64, k: u64) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+    let k = std::cmp::min(k, n - k);
+    // After each step, `acc` is `(n - k + i)` choose `i`, so the division is
+    // exact. The intermediate product is at most `n` times the final result.
+    (1..=k as u128)
+        .try_fold(1_u128, |acc, i| {
+            Some(acc.checked_mul((n - k) as u128 + i)? / i)
+        })?
+        .try_into()
+        .ok()
+}
+/// Returns the multinomial coefficient `(k_1 + k_2 + ...)! / (k_1! k_2!
+/// ...)`, or `None` if it or the sum of `ks` overflows `u64`.
+pub fn multinomial(ks: &[u64]) -> Option<u64> {
+    let mut total = 0_u64;
+    let mut ret = 1_u64;
+    for &k in ks {
+        total = total.checked_add(k)?;
+        ret = ret.checked_mul(binomial(total, k)?)?;
+    }
+    Some(ret)
+}
+
+// `usize::is_multiple_of()` needs Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
 pub fn permutation_parity(mut n: usize) -> bool {
     let mut res = false;
     let mut i = 2;
@@ -18,3 +139,120 @@ pub fn permutation_parity(mut n: usize) -> bool {
     }
     res
 }
+
+/// Applies `f` to each item, in parallel if `parallel` is `true` and the
+/// `rayon` feature is enabled. The results are in the same order either way.
+#[cfg(feature = "rayon")]
+pub fn par_map<T: Sync, U: Send>(
+    parallel: bool,
+    items: &[T],
+    f: impl Fn(&T) -> U + Sync + Send,
+) -> Vec<U> {
+    use rayon::prelude::*;
+
+    if parallel {
+        items.par_iter().map(f).collect()
+    } else {
+        items.iter().map(f).collect()
+    }
+}
+/// Applies `f` to each item. Without the `rayon` feature, this is always
+/// serial.
+#[cfg(not(feature = "rayon"))]
+pub fn par_map<T, U>(_parallel: bool, items: &[T], f: impl Fn(&T) -> U) -> Vec<U> {
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approx_eq_rel() {
+        for scale in [1e-3, 1.0, 1e4] {
+            // Rounding error is equal.
+            let x = 0.1_f32 * scale;
+            let y = (0.1_f64 * scale as f64 * 3.0 / 3.0 + 1e-9 * scale as f64) as f32;
+            assert!(approx_eq_rel(x, y, 1e-5, 0.0), "scale {scale}");
+            assert!(approx_eq_ulps(x, y, 4), "scale {scale}");
+            // A difference of 1% is not.
+            assert!(!approx_eq_rel(x, x * 1.01, 1e-3, 0.0), "scale {scale}");
+            assert!(!approx_eq_ulps(x, x * 1.01, 1000), "scale {scale}");
+        }
+        // The old absolute comparison can't tell apart small numbers, and is
+        // too strict for large numbers.
+        assert!(f32_approx_eq(1e-4, 2e-4));
+        assert!(!approx_eq_rel(1e-4, 2e-4, EPSILON, 0.0));
+        assert!(!f32_approx_eq(1e4, 1e4 + 0.01));
+        assert!(approx_eq_rel(1e4, 1e4 + 0.01, EPSILON, 0.0));
+        // The absolute tolerance handles numbers near zero.
+        assert!(!approx_eq_rel(1e-9, -1e-9, EPSILON, 0.0));
+        assert!(approx_eq_rel(1e-9, -1e-9, EPSILON, 1e-6));
+    }
+
+    #[test]
+    fn test_factorial() {
+        assert_eq!(factorial(0), 1);
+        assert_eq!(factorial(5), 120);
+        assert_eq!(factorial(25), 15_511_210_043_330_985_984_000_000);
+        assert_eq!(
+            factorial(34),
+            295_232_799_039_604_140_847_618_609_643_520_000_000
+        );
+
+        assert_eq!(checked_factorial(20), Some(2_432_902_008_176_640_000));
+        assert_eq!(checked_factorial(21), None);
+        assert!(factorial(21) > u64::MAX as u128);
+        for n in 0..=20 {
+            assert_eq!(checked_factorial(n).map(u128::from), Some(factorial(n)));
+        }
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(52, 5), Some(2_598_960));
+        assert_eq!(binomial(5, 0), Some(1));
+        assert_eq!(binomial(5, 5), Some(1));
+        assert_eq!(binomial(5, 6), Some(0));
+        assert_eq!(binomial(0, 0), Some(1));
+        for n in 0..=20 {
+            for k in 0..=n {
+                let expected =
+                    factorial(n as u32) / factorial(k as u32) / factorial((n - k) as u32);
+                assert_eq!(binomial(n, k), Some(expected as u64));
+            }
+        }
+        // Largest central binomial coefficient that fits in u64
+        assert_eq!(binomial(66, 33), Some(7_219_428_434_016_265_740));
+        assert_eq!(binomial(68, 34), None);
+        assert_eq!(binomial(u64::MAX, 1), Some(u64::MAX));
+
+        assert_eq!(multinomial(&[]), Some(1));
+        assert_eq!(multinomial(&[2, 2, 2]), Some(90));
+        assert_eq!(multinomial(&[3, 0, 1]), Some(4));
+        assert_eq!(multinomial(&[20, 20, 20]), None);
+    }
+
+    #[test]
+    fn test_approx_eq_ulps() {
+        assert!(approx_eq_ulps(0.0, -0.0, 0));
+        assert!(approx_eq_ulps(1.0, 1.0, 0));
+        assert!(approx_eq_ulps(
+            1.0,
+            f32::from_bits(1.0_f32.to_bits() + 3),
+            3
+        ));
+        assert!(!approx_eq_ulps(
+            1.0,
+            f32::from_bits(1.0_f32.to_bits() + 4),
+            3
+        ));
+        // Across zero
+        let tiny = f32::from_bits(1);
+        assert!(approx_eq_ulps(tiny, -tiny, 2));
+        assert!(!approx_eq_ulps(tiny, -tiny, 1));
+        assert!(!approx_eq_ulps(1.0, -1.0, 1_000_000));
+        assert!(!approx_eq_ulps(f32::NAN, f32::NAN, u32::MAX));
+        assert!(approx_eq_ulps(f32::MAX, f32::INFINITY, 1));
+    }
+}
@@ -1,18 +1,30 @@
 use itertools::Itertools;
-use num_traits::{Float, Num};
+use num_traits::{Float, Num, NumCast, Signed, ToPrimitive};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
-use std::iter::Cloned;
+use std::iter::{Cloned, Sum};
 use std::marker::PhantomData;
 use std::ops::*;
 
-use crate::util::f32_approx_eq;
+use crate::util::{f32_approx_eq, Tolerance, EPSILON};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Vector<N: Clone + Num>(pub Vec<N>);
 
+/// Vector-like type that can be read by component.
+///
+/// Vectors are conceptually infinite-dimensional and zero-padded: [`get()`]
+/// returns zero for any component past [`ndim()`], and operations on two
+/// vectors of different dimensions pad the smaller one with zeros.
+///
+/// [`get()`]: VectorRef::get
+/// [`ndim()`]: VectorRef::ndim
 pub trait VectorRef<N: Clone + Num>: Sized {
+    /// Returns the number of explicitly stored components.
     fn ndim(&self) -> u8;
 
+    /// Returns a component of the vector, or zero if `idx` is out of bounds.
     fn get(&self, idx: u8) -> N;
 
     fn iter(&self) -> VectorIter<'_, N, Self> {
@@ -23,6 +35,36 @@ pub trait VectorRef<N: Clone + Num>: Sized {
         }
     }
 
+    /// Returns an iterator over `(axis, component)` pairs.
+    fn iter_enumerated(&self) -> std::iter::Zip<Range<u8>, VectorIter<'_, N, Self>> {
+        (0..self.ndim()).zip(self.iter())
+    }
+    /// Returns an iterator over `(axis, component)` pairs, skipping components
+    /// that are exactly zero.
+    ///
+    /// Components smaller than `EPSILON` are not skipped, even for floats.
+    /// The sparse [`VectorRef::dot()`] of a [`Vector`] uses this, and skipping
+    /// only exact zeros keeps it equal to the dense dot product. Skipping tiny
+    /// components would change the result when the other vector is large.
+    fn iter_nonzero(&self) -> impl Iterator<Item = (u8, N)> {
+        self.iter_enumerated().filter(|(_, x)| !x.is_zero())
+    }
+
+    /// Copies the components into an owned vector.
+    fn to_vector(&self) -> Vector<N> {
+        self.iter().collect()
+    }
+    /// Applies a function to each component.
+    fn map(&self, f: impl FnMut(N) -> N) -> Vector<N> {
+        self.iter().map(f).collect()
+    }
+    /// Applies a function to each pair of corresponding components, zero-padding
+    /// to the larger dimension.
+    fn zip_with(&self, other: impl VectorRef<N>, mut f: impl FnMut(N, N) -> N) -> Vector<N> {
+        let ndim = std::cmp::max(self.ndim(), other.ndim());
+        (0..ndim).map(|i| f(self.get(i), other.get(i))).collect()
+    }
+
     fn dot(&self, rhs: impl VectorRef<N>) -> N {
         self.iter()
             .zip(rhs.iter())
@@ -45,6 +87,74 @@ pub trait VectorRef<N: Clone + Num>: Sized {
     {
         self.mag2().sqrt()
     }
+
+    /// Returns a unit vector in the same direction, or `None` if the vector is
+    /// zero (within `EPSILON`).
+    fn normalized(&self) -> Option<Vector<N>>
+    where
+        N: Float,
+    {
+        let mag = self.mag();
+        let epsilon = N::from(EPSILON).unwrap_or_else(N::epsilon);
+        (mag >= epsilon).then(|| self.map(|x| x / mag))
+    }
+
+    /// Returns the component of the vector parallel to `other`. If `other` is
+    /// zero, returns the zero vector.
+    fn project_onto(&self, other: impl VectorRef<N>) -> Vector<N> {
+        let denominator = other.mag2();
+        if denominator.is_zero() {
+            return Vector::EMPTY;
+        }
+        let scale = self.dot(&other) / denominator;
+        other.map(|x| x * scale.clone())
+    }
+    /// Returns the component of the vector perpendicular to `other`. If `other`
+    /// is zero, returns the original vector.
+    fn reject_from(&self, other: impl VectorRef<N>) -> Vector<N> {
+        self.zip_with(self.project_onto(other), |a, b| a - b)
+    }
+
+    /// Returns the componentwise minimum of two vectors, zero-padded to the
+    /// larger dimension.
+    fn componentwise_min(&self, other: impl VectorRef<N>) -> Vector<N>
+    where
+        N: PartialOrd,
+    {
+        self.zip_with(other, |a, b| if b < a { b } else { a })
+    }
+    /// Returns the componentwise maximum of two vectors, zero-padded to the
+    /// larger dimension.
+    fn componentwise_max(&self, other: impl VectorRef<N>) -> Vector<N>
+    where
+        N: PartialOrd,
+    {
+        self.zip_with(other, |a, b| if b > a { b } else { a })
+    }
+    /// Returns the componentwise absolute value of the vector.
+    fn abs(&self) -> Vector<N>
+    where
+        N: Signed,
+    {
+        self.map(|x| x.abs())
+    }
+
+    /// Returns the angle between two vectors, in radians. If either vector is
+    /// zero, they are considered perpendicular.
+    // Converting an `f32` constant to a float type cannot fail.
+    #[allow(clippy::expect_used)]
+    fn angle_to(&self, other: impl VectorRef<N>) -> N
+    where
+        N: Float,
+    {
+        let denominator = self.mag() * other.mag();
+        if denominator.is_zero() {
+            return N::from(std::f32::consts::FRAC_PI_2).expect("float conversion failed");
+        }
+        // Floating-point error can push this slightly outside [-1, 1].
+        let cos = (self.dot(other) / denominator).max(-N::one()).min(N::one());
+        cos.acos()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -68,6 +178,14 @@ impl<N: Clone + Num> VectorRef<N> for Vector<N> {
     fn get(&self, idx: u8) -> N {
         self.0.get(idx as usize).cloned().unwrap_or(N::zero())
     }
+
+    fn dot(&self, rhs: impl VectorRef<N>) -> N {
+        // Vectors such as mirror normals are often mostly zeros, so skip those
+        // components.
+        self.iter_nonzero()
+            .map(|(i, x)| x * rhs.get(i))
+            .fold(N::zero(), |l, r| l + r)
+    }
 }
 
 impl<N: Clone + Num, V: VectorRef<N>> VectorRef<N> for &'_ V {
@@ -80,21 +198,71 @@ impl<N: Clone + Num, V: VectorRef<N>> VectorRef<N> for &'_ V {
     }
 }
 
+impl<N: Clone + Num> VectorRef<N> for &'_ [N] {
+    fn ndim(&self) -> u8 {
+        self.len() as _
+    }
+
+    fn get(&self, idx: u8) -> N {
+        <[N]>::get(self, idx as usize).cloned().unwrap_or(N::zero())
+    }
+}
+impl<N: Clone + Num, const D: usize> VectorRef<N> for [N; D] {
+    fn ndim(&self) -> u8 {
+        D as _
+    }
+
+    fn get(&self, idx: u8) -> N {
+        <[N]>::get(self, idx as usize).cloned().unwrap_or(N::zero())
+    }
+}
+
+/// Names of the first few axes, used when displaying vectors.
+pub const AXIS_NAMES: [&str; 8] = ["x", "y", "z", "w", "u", "v", "dim7", "dim8"];
+
+/// Formats each component using the formatter's width and precision. The
+/// alternate form (`{:#}`) labels each component with its axis name.
 impl<N: Clone + Num + fmt::Display> fmt::Display for Vector<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "(")?;
-        let mut iter = self.0.iter();
-        if let Some(first) = iter.next() {
-            write!(f, "{first}")?;
-            for elem in iter {
-                write!(f, ", {elem}")?;
+        for (i, elem) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if f.alternate() {
+                match AXIS_NAMES.get(i) {
+                    Some(name) => write!(f, "{name}: ")?,
+                    None => write!(f, "dim{}: ", i + 1)?,
+                }
             }
+            fmt::Display::fmt(elem, f)?;
         }
         write!(f, ")")?;
         Ok(())
     }
 }
 
+#[cfg(feature = "serde")]
+impl<N: Clone + Num + serde::Serialize> serde::Serialize for Vector<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de, N: Clone + Num + serde::Deserialize<'de>> serde::Deserialize<'de> for Vector<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<N>::deserialize(deserializer)?;
+        // The number of dimensions must fit in a `u8`.
+        if elems.len() > u8::MAX as usize {
+            return Err(serde::de::Error::invalid_length(
+                elems.len(),
+                &"a sequence of at most 255 elements",
+            ));
+        }
+        Ok(Vector(elems))
+    }
+}
+
 #[macro_export]
 macro_rules! vector {
     [$($tok:tt)*] => {
@@ -102,18 +270,28 @@ macro_rules! vector {
     };
 }
 
+/// Rearranges the components of a vector using axis names, like
+/// `swizzle!(v, x, z, y)`. Axes are named `x`, `y`, `z`, `w`, `u`, and `v`.
+#[macro_export]
+macro_rules! swizzle {
+    ($v:expr, $($axis:ident),+ $(,)?) => {
+        $v.permuted(&[$($crate::swizzle!(@axis $axis)),+])
+    };
+    (@axis x) => { 0 };
+    (@axis y) => { 1 };
+    (@axis z) => { 2 };
+    (@axis w) => { 3 };
+    (@axis u) => { 4 };
+    (@axis v) => { 5 };
+}
+
 macro_rules! define_zero_padded_op {
     (impl<$num:ident> $trait_name:ident for $type_name:ty { fn $fn_name:ident() }) => {
         impl<$num: Clone + Num, T: VectorRef<$num>> $trait_name<T> for $type_name {
             type Output = Vector<$num>;
 
             fn $fn_name(self, rhs: T) -> Self::Output {
-                use itertools::Itertools;
-
-                let result_ndim = std::cmp::max(self.ndim(), rhs.ndim());
-                let lhs = self.iter().pad_using(result_ndim as _, |_| N::zero());
-                let rhs = rhs.iter().pad_using(result_ndim as _, |_| N::zero());
-                lhs.zip(rhs).map(|(l, r)| l.$fn_name(r)).collect()
+                self.zip_with(rhs, |l, r| l.$fn_name(r))
             }
         }
     };
@@ -127,7 +305,7 @@ macro_rules! impl_vector_ops {
             type Output = Vector<N>;
 
             fn neg(self) -> Self::Output {
-                self.iter().map(|n| -n).collect()
+                self.map(|n| -n)
             }
         }
 
@@ -135,14 +313,14 @@ macro_rules! impl_vector_ops {
             type Output = Vector<$num>;
 
             fn mul(self, rhs: $num) -> Self::Output {
-                self.iter().map(|x| x * rhs.clone()).collect()
+                self.map(|x| x * rhs.clone())
             }
         }
         impl<$num: Clone + Num> Div<$num> for $type_name {
             type Output = Vector<$num>;
 
             fn div(self, rhs: $num) -> Self::Output {
-                self.iter().map(|x| x / rhs.clone()).collect()
+                self.map(|x| x / rhs.clone())
             }
         }
     };
@@ -150,20 +328,30 @@ macro_rules! impl_vector_ops {
 impl_vector_ops!(impl<N> for Vector<N>);
 impl_vector_ops!(impl<N> for &'_ Vector<N>);
 
+/// Panics if `index` is out of bounds. Use [`VectorRef::get()`] to treat the
+/// vector as zero-padded or [`Vector::get_checked()`] to get an `Option`.
 impl<N: Clone + Num> Index<u8> for Vector<N> {
     type Output = N;
 
+    #[track_caller]
     fn index(&self, index: u8) -> &Self::Output {
-        &self.0[index as usize]
+        match self.0.get(index as usize) {
+            Some(x) => x,
+            None => panic_index_out_of_bounds(index, self.ndim()),
+        }
     }
 }
+/// Panics if `index` is out of bounds. Use [`VectorRef::get()`] to treat the
+/// vector as zero-padded or [`Vector::get_checked()`] to get an `Option`.
 impl<N: Clone + Num> Index<u8> for &'_ Vector<N> {
     type Output = N;
 
+    #[track_caller]
     fn index(&self, index: u8) -> &Self::Output {
-        &self.0[index as usize]
+        &(**self)[index]
     }
 }
+/// Grows the vector (padding with zeros) if `index` is out of bounds.
 impl<N: Clone + Num> IndexMut<u8> for Vector<N> {
     fn index_mut(&mut self, index: u8) -> &mut Self::Output {
         if self.ndim() <= index {
@@ -173,9 +361,30 @@ impl<N: Clone + Num> IndexMut<u8> for Vector<N> {
     }
 }
 
+#[track_caller]
+fn panic_index_out_of_bounds(index: u8, ndim: u8) -> ! {
+    panic!(
+        "index {index} out of bounds for vector with {ndim} dimensions; \
+         use `VectorRef::get()` to treat the vector as zero-padded",
+    )
+}
+
 impl<N: Clone + Num> Vector<N> {
     pub const EMPTY: Self = Self(vec![]);
 
+    /// Returns a reference to a component, or `None` if `idx` is out of
+    /// bounds.
+    pub fn get_checked(&self, idx: u8) -> Option<&N> {
+        self.0.get(idx as usize)
+    }
+
+    pub fn zero(ndim: u8) -> Self {
+        vector![N::zero(); ndim as usize]
+    }
+    pub fn ones(ndim: u8) -> Self {
+        vector![N::one(); ndim as usize]
+    }
+
     pub fn unit(axis: u8) -> Self {
         let mut ret = vector![N::zero(); axis as usize+1];
         ret[axis] = N::one();
@@ -185,6 +394,60 @@ impl<N: Clone + Num> Vector<N> {
     pub fn iter(&self) -> impl '_ + Iterator<Item = N> {
         self.0.iter().cloned()
     }
+
+    /// Returns a vector whose `i`th component is component `perm[i]` of this
+    /// vector. Out-of-range indices read as zero, so this can also be used to
+    /// embed a vector in a higher-dimensional space.
+    pub fn permuted(&self, perm: &[u8]) -> Vector<N> {
+        perm.iter().map(|&i| VectorRef::get(self, i)).collect()
+    }
+
+    /// Converts the vector to a different scalar type, returning `None` if any
+    /// component cannot be represented in the new type.
+    pub fn cast<M: Clone + Num + NumCast>(&self) -> Option<Vector<M>>
+    where
+        N: ToPrimitive,
+    {
+        self.iter().map(M::from).collect::<Option<_>>().map(Vector)
+    }
+
+    /// Returns the cross product of two vectors, each of which is zero-padded
+    /// to 3 dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either vector has more than 3 dimensions. The cross product
+    /// is only defined in 3D; use a wedge product (bivector) instead.
+    #[track_caller]
+    pub fn cross(&self, rhs: impl VectorRef<N>) -> Vector<N> {
+        assert!(
+            self.ndim() <= 3 && rhs.ndim() <= 3,
+            "cross product is only defined for vectors with at most 3 dimensions; \
+             use a wedge product instead",
+        );
+        let [a0, a1, a2] = [0, 1, 2].map(|i| self.get(i));
+        let [b0, b1, b2] = [0, 1, 2].map(|i| rhs.get(i));
+        vector![
+            a1.clone() * b2.clone() - a2.clone() * b1.clone(),
+            a2 * b0.clone() - a0.clone() * b2,
+            a0 * b1 - a1 * b0,
+        ]
+    }
+
+    /// Returns the scalar triple product `a · (b × c)`, which is the signed
+    /// volume of the parallelepiped spanned by the three vectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any vector has more than 3 dimensions.
+    #[track_caller]
+    pub fn triple_product(a: &Self, b: &Self, c: &Self) -> N {
+        assert!(
+            a.ndim() <= 3,
+            "triple product is only defined for vectors with at most 3 dimensions",
+        );
+        a.dot(b.cross(c))
+    }
 }
 
 impl<N: Clone + Num> IntoIterator for Vector<N> {
@@ -212,17 +475,448 @@ impl<N: Clone + Num> FromIterator<N> for Vector<N> {
     }
 }
 
+impl<N: Clone + Num, const D: usize> From<[N; D]> for Vector<N> {
+    fn from(array: [N; D]) -> Self {
+        Self(array.into())
+    }
+}
+impl<N: Clone + Num, const D: usize> TryFrom<Vector<N>> for [N; D] {
+    /// On dimension mismatch, the original vector is returned.
+    type Error = Vector<N>;
+
+    fn try_from(v: Vector<N>) -> Result<Self, Self::Error> {
+        v.0.try_into().map_err(Vector)
+    }
+}
+
+macro_rules! impl_from_tuple {
+    ($($elem:ident),+) => {
+        impl<N: Clone + Num> From<($(impl_from_tuple!(@ty $elem),)+)> for Vector<N> {
+            fn from(($($elem,)+): ($(impl_from_tuple!(@ty $elem),)+)) -> Self {
+                vector![$($elem),+]
+            }
+        }
+    };
+    (@ty $elem:ident) => { N };
+}
+impl_from_tuple!(x);
+impl_from_tuple!(x, y);
+impl_from_tuple!(x, y, z);
+impl_from_tuple!(x, y, z, w);
+impl_from_tuple!(x, y, z, w, u);
+impl_from_tuple!(x, y, z, w, u, v);
+impl_from_tuple!(x, y, z, w, u, v, a);
+impl_from_tuple!(x, y, z, w, u, v, a, b);
+
+impl<N: Clone + Num> Sum<Vector<N>> for Vector<N> {
+    fn sum<I: Iterator<Item = Vector<N>>>(iter: I) -> Self {
+        iter.fold(Vector::EMPTY, |a, b| a + b)
+    }
+}
+impl<'a, N: Clone + Num> Sum<&'a Vector<N>> for Vector<N> {
+    fn sum<I: Iterator<Item = &'a Vector<N>>>(iter: I) -> Self {
+        iter.fold(Vector::EMPTY, |a, b| a + b)
+    }
+}
+
+/// Returns the average of a set of points, or `None` if there are no points.
+pub fn centroid(points: &[Vector<f32>]) -> Option<Vector<f32>> {
+    if points.is_empty() {
+        return None;
+    }
+    Some(points.iter().sum::<Vector<f32>>() / points.len() as f32)
+}
+
+/// Returns the minimum and maximum corners of the axis-aligned bounding box of
+/// a set of points, or `None` if there are no points.
+pub fn bounding_box(points: &[Vector<f32>]) -> Option<(Vector<f32>, Vector<f32>)> {
+    let first = points.first()?;
+    Some(
+        points[1..]
+            .iter()
+            .fold((first.clone(), first.clone()), |(min, max), p| {
+                (min.componentwise_min(p), max.componentwise_max(p))
+            }),
+    )
+}
+
+/// Returns the center and radius of a sphere containing a set of points, or
+/// `None` if there are no points.
+///
+/// This uses Ritter's algorithm, so the sphere is not necessarily the smallest
+/// one. It is typically 5-20% larger, and there is no tight bound on how much
+/// larger it can be, so don't rely on it where the exact radius matters.
+pub fn bounding_sphere(points: &[Vector<f32>]) -> Option<(Vector<f32>, f32)> {
+    let farthest_from = |p: &Vector<f32>| {
+        points
+            .iter()
+            .max_by(|a, b| (*a - p).mag2().total_cmp(&(*b - p).mag2()))
+    };
+    let x = points.first()?;
+    let y = farthest_from(x)?;
+    let z = farthest_from(y)?;
+    let mut center = (y + z) / 2.0;
+    let mut radius = (z - y).mag() / 2.0;
+    // Grow the sphere to include each point outside it.
+    for p in points {
+        let distance = (p - &center).mag();
+        if distance > radius {
+            let new_radius = (radius + distance) / 2.0;
+            center = &center + (p - &center) * ((new_radius - radius) / distance);
+            radius = new_radius;
+        }
+    }
+    Some((center, radius))
+}
+
+/// Vector quantized to a grid so that it can be compared, sorted, and hashed.
+///
+/// Each component is rounded to the nearest multiple of `eps`, and comparison
+/// uses only the rounded values. Vectors within `eps` of each other usually
+/// have the same key, but may not if they straddle a rounding boundary.
+#[derive(Debug, Clone)]
+pub struct OrderedVector {
+    key: Vec<i64>,
+    vector: Vector<f32>,
+}
+impl OrderedVector {
+    pub fn new(vector: Vector<f32>, eps: f32) -> Self {
+        let mut key = vector
+            .iter()
+            .map(|x| (x / eps).round() as i64)
+            .collect_vec();
+        // Trailing zeros are insignificant, since vectors are zero-padded.
+        while key.last() == Some(&0) {
+            key.pop();
+        }
+        Self { key, vector }
+    }
+
+    /// Returns the quantized components.
+    pub fn key(&self) -> &[i64] {
+        &self.key
+    }
+    /// Returns the original vector.
+    pub fn vector(&self) -> &Vector<f32> {
+        &self.vector
+    }
+    /// Returns the original vector.
+    pub fn into_vector(self) -> Vector<f32> {
+        self.vector
+    }
+}
+impl From<OrderedVector> for Vector<f32> {
+    fn from(v: OrderedVector) -> Self {
+        v.vector
+    }
+}
+impl PartialEq for OrderedVector {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for OrderedVector {}
+impl PartialOrd for OrderedVector {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for OrderedVector {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare lexicographically, zero-padding the shorter key.
+        let ndim = std::cmp::max(self.key.len(), other.key.len());
+        let get = |key: &[i64], i| key.get(i).copied().unwrap_or(0);
+        (0..ndim)
+            .map(|i| get(&self.key, i).cmp(&get(&other.key, i)))
+            .find(|&ord| ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+impl std::hash::Hash for OrderedVector {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+/// Points hashed by [`OrderedVector`] key, so that finding the points near a
+/// given point takes constant time instead of comparing against every point.
+///
+/// Points near the edge of a grid cell may be near points in neighboring
+/// cells, so every cell within tolerance is probed.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PointGrid {
+    /// Side length of each grid cell, which should be much larger than the
+    /// tolerance so that most lookups probe only one cell.
+    cell_size: f32,
+    /// Indices of the points in each grid cell.
+    cells: HashMap<Vec<i64>, Vec<usize>>,
+}
+impl PointGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the indices of the points in every grid cell within `eps` of
+    /// `point` in each component, which includes every point that is within
+    /// `eps` of it.
+    pub fn nearby(&self, point: &Vector<f32>, eps: f32) -> impl '_ + Iterator<Item = usize> {
+        let mut keys: Vec<Vec<i64>> = vec![vec![]];
+        for x in point.iter() {
+            let lo = ((x - eps) / self.cell_size).round() as i64;
+            let hi = ((x + eps) / self.cell_size).round() as i64;
+            keys = keys
+                .into_iter()
+                .flat_map(|key| {
+                    (lo..=hi).map(move |k| {
+                        let mut key = key.clone();
+                        key.push(k);
+                        key
+                    })
+                })
+                .collect();
+        }
+        keys.into_iter()
+            .filter_map(|mut key| {
+                // Trailing zeros are insignificant; see `OrderedVector::new()`.
+                while key.last() == Some(&0) {
+                    key.pop();
+                }
+                self.cells.get(&key)
+            })
+            .flatten()
+            .copied()
+    }
+    /// Adds a point with the given index.
+    pub fn insert(&mut self, point: &Vector<f32>, index: usize) {
+        let key = OrderedVector::new(point.clone(), self.cell_size);
+        self.cells.entry(key.key).or_default().push(index);
+    }
+}
+
+/// Finds points that are within `eps` of each other in every component,
+/// returning for each point the index of the point it is merged into. A point
+/// that is not merged into another maps to itself, and no point is merged into
+/// a point that is itself merged.
+///
+/// The points are sorted by [`OrderedVector`] and swept in that order, so each
+/// point is only compared against points in neighboring grid cells along the
+/// first axis. Unlike comparing keys directly, this never misses a pair that
+/// straddles a grid cell boundary.
+pub(crate) fn weld_points<N: Real>(points: &[impl VectorRef<N>], eps: N) -> Vec<usize> {
+    let cell_size = eps.round_to_f32();
+    let keys = points
+        .iter()
+        .map(|p| OrderedVector::new(p.iter().map(N::round_to_f32).collect(), cell_size))
+        .collect_vec();
+    // Trailing zeros are omitted from the key.
+    let first_key = |i: usize| keys[i].key().first().copied().unwrap_or(0);
+    let mut order = (0..points.len()).collect_vec();
+    order.sort_by(|&a, &b| keys[a].cmp(&keys[b]).then(a.cmp(&b)));
+
+    let mut ret = (0..points.len()).collect_vec();
+    for (i, &p) in order.iter().enumerate() {
+        // Points within `eps` have first keys that differ by at most one, plus
+        // one more to allow for rounding to `f32`.
+        let representative = order[..i]
+            .iter()
+            .rev()
+            .take_while(|&&q| first_key(p).abs_diff(first_key(q)) <= 2)
+            .filter(|&&q| ret[q] == q)
+            .find(|&&q| {
+                let ndim = std::cmp::max(points[p].ndim(), points[q].ndim());
+                (0..ndim).all(|k| (points[p].get(k) - points[q].get(k)).abs() < eps)
+            });
+        if let Some(&q) = representative {
+            ret[p] = q;
+        }
+    }
+    ret
+}
+
+/// Sorts points by [`Vector::canonical_cmp()`] and removes points within `eps`
+/// of the previous point.
+pub fn sort_and_dedup_points(points: &mut Vec<Vector<f32>>, eps: f32) {
+    points.sort_by(|a, b| a.canonical_cmp(b, eps));
+    points.dedup_by(|a, b| a.approx_eq_eps(&*b, eps));
+}
+
+/// Floating-point type that coordinates can be stored in, such as in a
+/// [`PolytopeArena`](crate::PolytopeArena).
+pub trait Real: Float + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// Converts from `f32`, which is exact.
+    fn from_f32(x: f32) -> Self;
+    /// Converts to `f32`, rounding to the nearest representable value.
+    fn round_to_f32(self) -> f32;
+}
+impl Real for f32 {
+    fn from_f32(x: f32) -> Self {
+        x
+    }
+    fn round_to_f32(self) -> f32 {
+        self
+    }
+}
+impl Real for f64 {
+    fn from_f32(x: f32) -> Self {
+        x as f64
+    }
+    fn round_to_f32(self) -> f32 {
+        self as f32
+    }
+}
+impl<N: Real> Vector<N> {
+    /// Converts a vector from `f32`, which is exact.
+    pub fn from_f32(v: &Vector<f32>) -> Self {
+        v.iter().map(N::from_f32).collect()
+    }
+    /// Converts the vector to `f32`, rounding each component.
+    pub fn round_to_f32(&self) -> Vector<f32> {
+        self.iter().map(N::round_to_f32).collect()
+    }
+}
+
 impl Vector<f32> {
+    /// Returns whether the two vectors are approximately equal, using
+    /// [`Tolerance::APPROX_EQ`]. See [`Vector::approx_eq_with()`].
     pub fn approx_eq(&self, other: impl VectorRef<f32>) -> bool {
+        self.approx_eq_with(other, Tolerance::APPROX_EQ)
+    }
+    /// Returns whether every component of the two vectors differs by less
+    /// than `tolerance`, relative to the larger magnitude of the two vectors.
+    ///
+    /// Use [`Tolerance::default()`] for a purely absolute comparison.
+    pub fn approx_eq_with(&self, other: impl VectorRef<f32>, tolerance: Tolerance) -> bool {
+        let scale = f32::max(self.mag(), other.mag());
+        self.approx_eq_eps(other, tolerance.eps(scale))
+    }
+    /// Returns whether every component of the two vectors differs by less
+    /// than `eps`.
+    pub fn approx_eq_eps(&self, other: impl VectorRef<f32>, eps: f32) -> bool {
+        let ndim = std::cmp::max(self.ndim(), other.ndim()) as usize;
+        let self_xs = self.iter().pad_using(ndim, |_| 0.0);
+        let other_xs = other.iter().pad_using(ndim, |_| 0.0);
+        self_xs.zip(other_xs).all(|(l, r)| (l - r).abs() < eps)
+    }
+    /// Compares two vectors lexicographically, treating components that
+    /// differ by less than `eps` as equal.
+    ///
+    /// This is only a total order if every cluster of points within `eps` of
+    /// each other is well-separated from other points.
+    pub fn canonical_cmp(&self, other: impl VectorRef<f32>, eps: f32) -> Ordering {
         let ndim = std::cmp::max(self.ndim(), other.ndim()) as usize;
         let self_xs = self.iter().pad_using(ndim, |_| 0.0);
         let other_xs = other.iter().pad_using(ndim, |_| 0.0);
-        self_xs.zip(other_xs).all(|(l, r)| f32_approx_eq(l, r))
+        self_xs
+            .zip(other_xs)
+            .find(|(l, r)| (l - r).abs() >= eps)
+            .map_or(Ordering::Equal, |(l, r)| l.total_cmp(&r))
     }
 
-    pub fn rotate_toward(&self, other: &Self, fraction_of_pi: usize) -> Vector<f32> {
-        let angle = std::f32::consts::PI / fraction_of_pi as f32;
-        self * angle.cos() + other * angle.sin()
+    /// Rotates the vector by `angle` radians in the plane spanned by `self` and
+    /// `other`, toward `other`. The magnitude of the vector is preserved.
+    /// `other` does not need to be orthogonal to `self` or normalized.
+    ///
+    /// If `other` is parallel to `self` (or either vector is zero), returns the
+    /// vector unchanged. If `other` is antiparallel to `self`, the plane of
+    /// rotation is ambiguous and this returns `None`.
+    pub fn rotate_toward(&self, other: impl VectorRef<f32>, angle: f32) -> Option<Vector<f32>> {
+        let mag = self.mag();
+        if mag < EPSILON {
+            return Some(self.clone());
+        }
+        // Gram-Schmidt to get an orthonormal basis for the plane of rotation.
+        let u = self / mag;
+        let w = match other.reject_from(&u).normalized() {
+            Some(w) => w,
+            None if other.dot(self) < 0.0 => return None,
+            None => return Some(self.clone()),
+        };
+        Some((u * angle.cos() + w * angle.sin()) * mag)
+    }
+
+    /// Normalizes the vector in place, returning `false` and leaving it
+    /// unchanged if it is zero (within `EPSILON`).
+    pub fn normalize(&mut self) -> bool {
+        match self.normalized() {
+            Some(v) => {
+                *self = v;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the reflection of the vector across the hyperplane through the
+    /// origin perpendicular to `normal`. `normal` does not need to be
+    /// normalized. If `normal` is zero, returns the vector unchanged.
+    pub fn reflected_across(&self, normal: impl VectorRef<f32>) -> Vector<f32> {
+        match normal.normalized() {
+            Some(n) => self - &n * (2.0 * self.dot(&n)),
+            None => self.clone(),
+        }
+    }
+    /// Reflects the vector in place across the hyperplane through the origin
+    /// perpendicular to `normal`. See [`Vector::reflected_across()`].
+    pub fn reflect_across(&mut self, normal: impl VectorRef<f32>) {
+        *self = self.reflected_across(normal);
+    }
+
+    /// Linearly interpolates between two vectors, zero-padding to the larger
+    /// dimension. `t = 0.0` gives `self` and `t = 1.0` gives `other`.
+    pub fn lerp(&self, other: impl VectorRef<f32>, t: f32) -> Vector<f32> {
+        self * (1.0 - t) + other.map(|x| x * t)
+    }
+    /// Spherically interpolates between two unit vectors, moving at constant
+    /// angular speed. The result is unspecified if the vectors are antipodal.
+    pub fn slerp(&self, other: impl VectorRef<f32>, t: f32) -> Vector<f32> {
+        let angle = self.angle_to(&other);
+        if angle < EPSILON {
+            let ret = self.lerp(other, t);
+            return ret.normalized().unwrap_or(ret);
+        }
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        self * a + other.map(|x| x * b)
+    }
+
+    /// Returns the signed distance from the point to the hyperplane
+    /// `{x : x · n̂ = offset}`, where `n̂` is `normal` normalized. The distance
+    /// is positive on the side that `normal` points toward.
+    ///
+    /// `normal` must be nonzero.
+    pub fn signed_distance_to_hyperplane(&self, normal: impl VectorRef<f32>, offset: f32) -> f32 {
+        debug_assert!(normal.mag2() != 0.0, "hyperplane normal must be nonzero");
+        self.dot(&normal) / normal.mag() - offset
+    }
+    /// Returns the closest point on the hyperplane `{x : x · n̂ = offset}`,
+    /// where `n̂` is `normal` normalized.
+    ///
+    /// `normal` must be nonzero.
+    pub fn projected_onto_hyperplane(
+        &self,
+        normal: impl VectorRef<f32>,
+        offset: f32,
+    ) -> Vector<f32> {
+        let distance = self.signed_distance_to_hyperplane(&normal, offset);
+        self - normal.map(|x| x * distance / normal.mag())
+    }
+
+    /// Returns whether the vector has magnitude zero (within `EPSILON`).
+    pub fn is_zero(&self) -> bool {
+        self.mag() < EPSILON
+    }
+    /// Returns whether the vector has magnitude one (within `EPSILON`).
+    pub fn is_unit(&self) -> bool {
+        f32_approx_eq(self.mag(), 1.0)
+    }
+    /// Returns whether every component of the vector is finite (not NaN or
+    /// infinite).
+    pub fn is_finite(&self) -> bool {
+        self.0.iter().all(|x| x.is_finite())
     }
 
     pub fn set_ndim(&mut self, ndim: u8) {
@@ -237,6 +931,14 @@ impl Vector<f32> {
 mod tests {
     use super::*;
 
+    #[test]
+    pub fn test_zero_and_ones() {
+        assert_eq!(Vector::<f32>::zero(5).ndim(), 5);
+        assert_eq!(Vector::<i32>::zero(3), vector![0, 0, 0]);
+        assert_eq!(Vector::<i32>::ones(2), vector![1, 1]);
+        assert_eq!(Vector::<i32>::zero(0), Vector::EMPTY);
+    }
+
     #[test]
     pub fn test_vector_add() {
         let v1 = vector![1, 2, -10];
@@ -260,10 +962,603 @@ mod tests {
         assert_eq!(-v1, vector![-1, -2, 10]);
     }
 
+    #[test]
+    pub fn test_vector_op_properties() {
+        let mut rng = crate::testutil::Rng::new(1);
+        for i in 0..100 {
+            let a = rng.vector(i % 5, -10.0, 10.0);
+            let b = rng.vector(i % 7, -10.0, 10.0);
+            let c = rng.vector(i % 3, -10.0, 10.0);
+            let s = rng.range_f32(-5.0, 5.0);
+
+            assert_eq!(&a + &b, &b + &a);
+            assert!((&(&a + &b) - &b).approx_eq(&a));
+            assert!((&(&a + &b) + &c).approx_eq(&a + &(&b + &c)));
+            assert_eq!(&a - &b, -(&b - &a));
+            assert!(f32_approx_eq(a.dot(&b), b.dot(&a)));
+            assert!(f32_approx_eq((&a * s).dot(&b), a.dot(&b) * s));
+            assert!((&(&a * s) / s).approx_eq(&a));
+        }
+    }
+
     #[test]
     pub fn test_dot_product() {
         let v1 = vector![1, 2, -10];
         let v2 = vector![-5, 16];
         assert_eq!(v1.dot(v2), 27);
     }
+
+    #[test]
+    pub fn test_cross_product() {
+        assert_eq!(
+            Vector::<i32>::unit(0).cross(Vector::unit(1)),
+            Vector::unit(2)
+        );
+        assert_eq!(
+            Vector::<i32>::unit(1).cross(Vector::unit(2)),
+            vector![1, 0, 0]
+        );
+
+        let v1 = vector![1, 2, -10];
+        let v2 = vector![-5, 16];
+        assert_eq!(v1.cross(&v2), vector![160, 50, 26]);
+        assert_eq!(v1.cross(&v2), -v2.cross(&v1));
+
+        let v1 = vector![0.3, -1.2, 2.5];
+        let v2 = &v1 * -3.7;
+        assert!(v1.cross(&v2).approx_eq(Vector::EMPTY));
+
+        let [x, y, z] = [0, 1, 2].map(Vector::<i32>::unit);
+        assert_eq!(Vector::triple_product(&x, &y, &z), 1);
+        assert_eq!(Vector::triple_product(&y, &x, &z), -1);
+        let coplanar = [vector![1, 2], vector![3, -1], vector![5, 5]];
+        assert_eq!(
+            Vector::triple_product(&coplanar[0], &coplanar[1], &coplanar[2]),
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_cross_product_4d() {
+        vector![1, 0, 0, 0].cross(vector![0, 1, 0]);
+    }
+
+    #[test]
+    pub fn test_approx_eq() {
+        for scale in [1e-3, 1.0, 1e4] {
+            let v = vector![0.6, -0.8, 0.0] * scale;
+            // Rounding error is equal, even for large vectors.
+            let rotated = crate::Matrix::from_cols([vector![0.0, 1.0], vector![-1.0, 0.0]]);
+            let round_trip = (0..4).fold(v.clone(), |v, _| rotated.transform(&v));
+            assert!(v.approx_eq(&round_trip), "scale {scale}");
+            assert!(v.approx_eq(&v * (1.0 + 1e-5)), "scale {scale}");
+            // A difference of 1% is not, unless it's within the absolute
+            // tolerance.
+            assert_eq!(v.approx_eq(&v * 1.01), scale < 1.0, "scale {scale}");
+        }
+        // Small vectors are still compared absolutely.
+        assert!(vector![5e-4, 0.0].approx_eq(vector![0.0, 1e-4]));
+        // Large vectors are compared relatively, which the old absolute
+        // comparison can recover.
+        let v = vector![1e4, 0.0];
+        let w = vector![1e4 + 1.0, 0.0];
+        assert!(v.approx_eq(&w));
+        assert!(!v.approx_eq_with(&w, Tolerance::default()));
+    }
+
+    #[test]
+    pub fn test_normalize() {
+        assert_eq!(vector![0.0, 0.0, 0.0].normalized(), None);
+        assert_eq!(Vector::<f32>::EMPTY.normalized(), None);
+        assert_eq!(vector![0.0, 0.0005].normalized(), None);
+        let mut v = vector![0.0, 0.0];
+        assert!(!v.normalize());
+        assert_eq!(v, vector![0.0, 0.0]);
+
+        let v = vector![0.0, 1.0, 0.0];
+        assert_eq!(v.normalized(), Some(v));
+
+        let mut v = vector![3.0, -4.0, 12.0, 0.5];
+        assert!(v.normalize());
+        assert!(f32_approx_eq(v.mag(), 1.0));
+        assert!(v.approx_eq(vector![3.0, -4.0, 12.0, 0.5] / 13.009_612));
+
+        let m = crate::Matrix::from_elems(vec![1.0, 2.0, 3.0, 4.0]);
+        let col = m.col(1).normalized().unwrap();
+        assert!(f32_approx_eq(col.mag(), 1.0));
+    }
+
+    #[test]
+    pub fn test_angle_to() {
+        use std::f32::consts::PI;
+
+        let v = vector![0.3, -1.2, 2.5];
+        assert_eq!(v.angle_to(&v), 0.0);
+        assert_eq!(v.angle_to(&v * 1000.0), 0.0);
+        assert_eq!(v.angle_to(-&v), PI);
+        assert_eq!(v.angle_to(&v * -0.001), PI);
+        assert_eq!(v.angle_to(Vector::EMPTY), PI / 2.0);
+
+        let mirrors = crate::CoxeterDiagram::with_edges(vec![4, 3]).mirrors();
+        assert!(f32_approx_eq(
+            mirrors[0].0.angle_to(&mirrors[1].0),
+            PI / 4.0
+        ));
+        assert!(f32_approx_eq(
+            mirrors[1].0.angle_to(&mirrors[2].0),
+            PI / 3.0
+        ));
+        assert!(f32_approx_eq(
+            mirrors[0].0.angle_to(&mirrors[2].0),
+            PI / 2.0
+        ));
+    }
+
+    #[test]
+    pub fn test_project_and_reject() {
+        let v = vector![3.0, 4.0, 5.0];
+        assert_eq!(v.project_onto(vector![0.0, 2.0]), vector![0.0, 4.0]);
+        assert_eq!(v.reject_from(vector![0.0, 2.0]), vector![3.0, 0.0, 5.0]);
+        assert_eq!(v.project_onto(Vector::EMPTY), Vector::EMPTY);
+        assert_eq!(v.reject_from(vector![0.0, 0.0]), v);
+
+        let mut rng = crate::testutil::Rng::new(12345);
+        for i in 0..100 {
+            let v = rng.vector(i % 5 + 1, -2.0, 2.0);
+            let other = rng.vector(i % 7 + 1, -2.0, 2.0);
+            let projection = v.project_onto(&other);
+            let rejection = v.reject_from(&other);
+            assert!((&projection + &rejection).approx_eq(&v));
+            assert!(f32_approx_eq(projection.dot(&rejection), 0.0));
+            assert!(f32_approx_eq(rejection.dot(&other), 0.0));
+        }
+    }
+
+    #[test]
+    pub fn test_reflect_across() {
+        let v = vector![0.3, -1.2, 2.5, 1.0];
+        let normal = vector![1.0, 2.0, -2.0];
+        let reflected = v.reflected_across(&normal);
+        assert!(!reflected.approx_eq(&v));
+        assert!(reflected.reflected_across(&normal).approx_eq(&v));
+        assert!(f32_approx_eq(reflected.mag(), v.mag()));
+
+        let mirror = crate::Mirror(normal.normalized().unwrap());
+        let mirror_matrix = crate::Matrix::from(mirror);
+        assert!(reflected.approx_eq(mirror_matrix.transform(&v)));
+
+        let mut w = v.clone();
+        w.reflect_across(&normal);
+        assert_eq!(w, reflected);
+
+        assert_eq!(v.reflected_across(Vector::EMPTY), v);
+    }
+
+    #[test]
+    pub fn test_lerp() {
+        let a = vector![0.3, -1.2, 2.5];
+        let b = vector![1.7, 0.1, -0.9];
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+        assert!(a.lerp(&b, 0.25).approx_eq(vector![0.65, -0.875, 1.65]));
+
+        let a = vector![1.0, 2.0];
+        let b = vector![3.0, 4.0, 8.0];
+        assert!(a.lerp(&b, 0.0).approx_eq(&a));
+        assert!(a.lerp(&b, 0.5).approx_eq(vector![2.0, 3.0, 4.0]));
+        assert!(b.lerp(&a, 0.5).approx_eq(vector![2.0, 3.0, 4.0]));
+    }
+
+    #[test]
+    pub fn test_slerp() {
+        let a = Vector::unit(0);
+        let b = Vector::unit(2);
+        let mid = a.slerp(&b, 0.5);
+        assert!(f32_approx_eq(mid.mag(), 1.0));
+        assert!(mid.approx_eq(vector![1.0, 0.0, 1.0] / 2.0_f32.sqrt()));
+        assert!(a.slerp(&b, 0.0).approx_eq(&a));
+        assert!(a.slerp(&b, 1.0).approx_eq(&b));
+
+        // Nearly-parallel vectors
+        let b = vector![1.0, 0.0001].normalized().unwrap();
+        let mid = a.slerp(&b, 0.5);
+        assert!(mid.iter().all(f32::is_finite));
+        assert!(f32_approx_eq(mid.mag(), 1.0));
+    }
+
+    #[test]
+    pub fn test_sum_and_centroid() {
+        assert_eq!(
+            std::iter::empty::<Vector<i32>>().sum::<Vector<i32>>(),
+            Vector::EMPTY
+        );
+        let vectors = [vector![1, 2], vector![-3], vector![0, 0, 7]];
+        assert_eq!(vectors.iter().sum::<Vector<i32>>(), vector![-2, 2, 7]);
+        assert_eq!(vectors.into_iter().sum::<Vector<i32>>(), vector![-2, 2, 7]);
+
+        assert_eq!(centroid(&[]), None);
+        let cube_verts = (0..8)
+            .map(|i| {
+                (0..3)
+                    .map(|axis| if i >> axis & 1 == 0 { -1.0 } else { 1.0 })
+                    .collect()
+            })
+            .collect::<Vec<Vector<f32>>>();
+        assert!(centroid(&cube_verts).unwrap().approx_eq(Vector::EMPTY));
+        let points = [vector![1.0, 2.0], vector![3.0, 0.0, 3.0]];
+        assert!(centroid(&points).unwrap().approx_eq(vector![2.0, 1.0, 1.5]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    pub fn test_serde() {
+        let v = vector![0.5_f32, -1.25, 3.0];
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "[0.5,-1.25,3.0]");
+        assert_eq!(serde_json::from_str::<Vector<f32>>(&json).unwrap(), v);
+
+        let v = vector![1_i64, -20, 300];
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "[1,-20,300]");
+        assert_eq!(serde_json::from_str::<Vector<i64>>(&json).unwrap(), v);
+
+        let json = serde_json::to_string(&vec![1_i64; 255]).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vector<i64>>(&json).unwrap().ndim(),
+            255
+        );
+        let json = serde_json::to_string(&vec![1_i64; 256]).unwrap();
+        let err = serde_json::from_str::<Vector<i64>>(&json).unwrap_err();
+        assert!(err.to_string().contains("at most 255"), "{err}");
+    }
+
+    #[test]
+    pub fn test_array_conversions() {
+        let v = Vector::from([1.0, 0.0, 0.5]);
+        assert_eq!(v, vector![1.0, 0.0, 0.5]);
+        assert_eq!(<[f32; 3]>::try_from(v.clone()), Ok([1.0, 0.0, 0.5]));
+        assert_eq!(<[f32; 2]>::try_from(v.clone()), Err(v));
+
+        let v = vector![1, 2, 3, 4];
+        assert_eq!(<[i32; 3]>::try_from(v.clone()), Err(v));
+
+        assert_eq!(Vector::from((1, 2, 3)), vector![1, 2, 3]);
+        assert_eq!(Vector::from((1,)), vector![1]);
+        assert_eq!(Vector::from((1, 2, 3, 4, 5, 6, 7, 8)).ndim(), 8);
+    }
+
+    #[test]
+    pub fn test_slice_and_array_vectors() {
+        let v = vector![1, 2, -10];
+        let slice: &[i32] = &[-5, 16];
+        assert_eq!(v.dot(slice), 27);
+        assert_eq!(slice.dot(&v), 27);
+        assert_eq!(&v + slice, vector![-4, 18, -10]);
+        assert_eq!(v.dot([-5, 16]), 27);
+        assert_eq!(&v - [1, 1, 1, 1], vector![0, 1, -11, -1]);
+        assert_eq!(VectorRef::get(&slice, 5), 0);
+
+        let m = crate::Matrix::<f32>::ident(3);
+        assert_eq!(m.transform(&[1.0, 2.0, 0.5][..]), vector![1.0, 2.0, 0.5]);
+        assert!(vector![1.0, 2.0].approx_eq([1.0, 2.0, 0.0]));
+    }
+
+    #[test]
+    pub fn test_componentwise_min_max_abs() {
+        let v1 = vector![1, -2, -10];
+        let v2 = vector![-5, 16];
+        assert_eq!(v1.componentwise_min(&v2), vector![-5, -2, -10]);
+        assert_eq!(v1.componentwise_max(&v2), vector![1, 16, 0]);
+        assert_eq!(v2.componentwise_max(&v1), vector![1, 16, 0]);
+        assert_eq!(v1.abs(), vector![1, 2, 10]);
+
+        assert_eq!(bounding_box(&[]), None);
+        let points = [vector![1.0, -2.0], vector![-0.5, 3.0, -1.0], vector![0.25]];
+        let (min, max) = bounding_box(&points).unwrap();
+        assert_eq!(min, vector![-0.5, -2.0, -1.0]);
+        assert_eq!(max, vector![1.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    pub fn test_bounding_sphere() {
+        assert_eq!(bounding_sphere(&[]), None);
+        let (center, radius) = bounding_sphere(&[vector![1.0, 2.0]]).unwrap();
+        assert_eq!((center, radius), (vector![1.0, 2.0], 0.0));
+
+        let points = [
+            vector![0.0, 0.0],
+            vector![4.0, 0.0],
+            vector![2.0, 1.0],
+            vector![2.0, -1.0],
+        ];
+        let (center, radius) = bounding_sphere(&points).unwrap();
+        assert!(center.approx_eq(vector![2.0]));
+        assert!(f32_approx_eq(radius, 2.0));
+
+        // Points that are not on the initial diameter grow the sphere.
+        let points = [
+            vector![-1.0, 0.0],
+            vector![1.0, 0.0],
+            vector![0.0, 1.5],
+            vector![0.0, -1.5],
+        ];
+        let (center, radius) = bounding_sphere(&points).unwrap();
+        assert!(points
+            .iter()
+            .all(|p| (p - &center).mag() <= radius + EPSILON));
+        assert!(radius < 1.5 * 1.1);
+    }
+
+    #[test]
+    pub fn test_canonical_ordering() {
+        let eps = 0.01;
+        assert!(vector![1.0, 2.0].approx_eq_eps(vector![1.005, 2.0, 0.009], eps));
+        assert!(!vector![1.0, 2.0].approx_eq_eps(vector![1.0, 2.0, 0.011], eps));
+
+        assert_eq!(
+            vector![1.0, 2.0].canonical_cmp(vector![1.005, 1.0], eps),
+            Ordering::Greater,
+        );
+        assert_eq!(
+            vector![1.0, 2.0].canonical_cmp(vector![0.995, 2.005], eps),
+            Ordering::Equal,
+        );
+
+        let expected = vec![
+            vector![-1.0, 0.0, 1.0],
+            vector![0.0, -1.0, 0.0],
+            vector![0.0, 0.0, -1.0],
+            vector![0.0, 0.0, 1.0],
+            vector![0.0, 1.0, 0.0],
+            vector![1.0, -1.0, 0.0],
+        ];
+        let mut points = vec![
+            vector![0.0, 0.0, 1.0],
+            vector![1.0, -1.0, 0.0],
+            vector![0.0, -1.0],
+            vector![-1.0, 0.0, 1.0],
+            vector![0.0, 1.0, 0.0],
+            vector![0.001, 0.0, 0.998],
+            vector![0.0, 0.0, -1.0],
+            vector![-0.002, 1.003, 0.0],
+            vector![0.0, -1.0, 0.0],
+            vector![1.0, -0.999],
+        ];
+        sort_and_dedup_points(&mut points, eps);
+        assert_eq!(points.len(), expected.len());
+        for (p, q) in points.iter().zip(&expected) {
+            assert!(p.approx_eq_eps(q, eps));
+        }
+
+        // Sorting is stable regardless of the input order.
+        let mut reversed = points.iter().rev().cloned().collect();
+        sort_and_dedup_points(&mut reversed, eps);
+        assert_eq!(reversed, points);
+    }
+
+    #[test]
+    pub fn test_rotate_toward() {
+        use std::f32::consts::PI;
+
+        let v = Vector::unit(0).rotate_toward(vector![1.0, 1.0, 0.0], PI / 2.0);
+        let v = v.unwrap();
+        assert!(f32_approx_eq(v.mag(), 1.0));
+        assert!(f32_approx_eq(v.dot(Vector::unit(0)), 0.0));
+        assert!(v.approx_eq(Vector::unit(1)));
+
+        // Orthonormal input
+        let a = vector![0.6, 0.8];
+        let b = vector![0.0, 0.0, 1.0];
+        for angle in [PI / 2.0, PI / 3.0, PI / 4.0] {
+            let expected = &a * angle.cos() + &b * angle.sin();
+            assert!(a.rotate_toward(&b, angle).unwrap().approx_eq(&expected));
+        }
+
+        // Magnitude is preserved.
+        let a = vector![3.0, 0.0, 4.0];
+        let v = a.rotate_toward(vector![2.0, 7.0, 0.0], 1.0).unwrap();
+        assert!(f32_approx_eq(v.mag(), 5.0));
+        assert!(f32_approx_eq(v.angle_to(&a), 1.0));
+
+        // Parallel and antiparallel
+        assert_eq!(a.rotate_toward(&a * 2.0, 1.0), Some(a.clone()));
+        assert_eq!(a.rotate_toward(&a * -2.0, 1.0), None);
+    }
+
+    #[test]
+    pub fn test_sanity_checks() {
+        assert!(Vector::<f32>::EMPTY.is_zero());
+        assert!(vector![0.0, 0.0005].is_zero());
+        assert!(!vector![0.0, 0.5].is_zero());
+
+        assert!(vector![0.6, 0.0, -0.8].is_unit());
+        assert!(!vector![0.6, 0.0, 0.9].is_unit());
+        assert!(!Vector::<f32>::EMPTY.is_unit());
+
+        assert!(vector![1.0, -1e30].is_finite());
+        assert!(!vector![1.0, f32::NAN].is_finite());
+        assert!(!vector![f32::NEG_INFINITY].is_finite());
+    }
+
+    #[test]
+    pub fn test_cast() {
+        let v = vector![1_i64, -20, 300];
+        let v_f32 = v.cast::<f32>().unwrap();
+        assert_eq!(v_f32, vector![1.0, -20.0, 300.0]);
+        assert_eq!(v_f32.cast::<i64>(), Some(v));
+
+        assert_eq!(vector![1.0_f64, 1e20].cast::<i32>(), None);
+        assert_eq!(vector![f32::NAN].cast::<i32>(), None);
+        assert_eq!(vector![-1_i32].cast::<u8>(), None);
+    }
+
+    #[test]
+    pub fn test_sparse_iteration() {
+        let v = vector![0, 3, 0, -1];
+        assert_eq!(
+            v.iter_enumerated().collect_vec(),
+            vec![(0, 0), (1, 3), (2, 0), (3, -1)],
+        );
+        assert_eq!(v.iter_nonzero().collect_vec(), vec![(1, 3), (3, -1)]);
+
+        let mirrors = crate::CoxeterDiagram::with_edges(vec![3, 3, 3, 3]).mirrors();
+        assert_eq!(mirrors[2].0.iter_nonzero().count(), 2);
+
+        // Sparse dot product (from `Vector`) matches dense dot product (from
+        // slice).
+        for m1 in &mirrors {
+            for m2 in &mirrors {
+                let dense = m1.0 .0.as_slice().dot(&m2.0);
+                assert_eq!(m1.0.dot(&m2.0), dense);
+            }
+        }
+
+        // Tiny components are not skipped.
+        let v = vector![1e-5, 0.0, 1.0];
+        assert_eq!(v.iter_nonzero().count(), 2);
+        let w = vector![1e5, 1.0, 0.0];
+        assert_eq!(v.dot(&w), v.0.as_slice().dot(&w));
+        assert_eq!(v.dot(&w), 1e-5 * 1e5);
+    }
+
+    #[test]
+    pub fn test_permuted() {
+        let v = vector![1, 2, 3, 4];
+        assert_eq!(v.permuted(&[0, 1, 2, 3]), v);
+        let cycle = [1, 2, 0, 3];
+        assert_eq!(v.permuted(&cycle), vector![2, 3, 1, 4]);
+        assert_eq!(v.permuted(&cycle).permuted(&cycle).permuted(&cycle), v);
+        assert_eq!(v.permuted(&[3, 7, 0]), vector![4, 0, 1]);
+
+        assert_eq!(swizzle!(v, x, z, y), vector![1, 3, 2]);
+        assert_eq!(swizzle!(v, w, w, u, v), vector![4, 4, 0, 0]);
+    }
+
+    #[test]
+    pub fn test_map_and_zip() {
+        let v1 = vector![1, 2, -10];
+        let v2 = vector![-5];
+        assert_eq!(v1.map(|x| x * x), vector![1, 4, 100]);
+        assert_eq!(v1.zip_with(&v2, |a, b| a * 10 + b), vector![5, 20, -100]);
+        assert_eq!(v2.zip_with(&v1, |a, b| a * 10 + b), vector![-49, 2, -10]);
+
+        let m = crate::Matrix::from_elems(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        for i in 0..3 {
+            assert_eq!(m.col(i).to_vector(), m.col(i).iter().collect());
+            assert_eq!(m.row(i).to_vector(), m.row(i).iter().collect());
+        }
+        assert_eq!(m.row(1).to_vector(), vector![2, 5, 8]);
+        assert_eq!(m.col(1).map(|x| -x), vector![-4, -5, -6]);
+    }
+
+    #[test]
+    pub fn test_display() {
+        let v = vector![1.0_f32, 0.0, -2.5, 1.0 / 3.0];
+        assert_eq!(v.to_string(), "(1, 0, -2.5, 0.33333334)");
+        assert_eq!(format!("{v:.3}"), "(1.000, 0.000, -2.500, 0.333)");
+        assert_eq!(format!("{v:7.2}"), "(   1.00,    0.00,   -2.50,    0.33)");
+        assert_eq!(
+            format!("{v:#.3}"),
+            "(x: 1.000, y: 0.000, z: -2.500, w: 0.333)",
+        );
+        assert_eq!(
+            format!("{:#}", Vector::<i32>::unit(8)),
+            "(x: 0, y: 0, z: 0, w: 0, u: 0, v: 0, dim7: 0, dim8: 0, dim9: 1)"
+        );
+        assert_eq!(Vector::<i32>::EMPTY.to_string(), "()");
+    }
+
+    #[test]
+    pub fn test_access_out_of_bounds() {
+        let v = vector![1, 2];
+        assert_eq!(v[1], 2);
+        assert_eq!(v.get(1), 2);
+        assert_eq!(v.get_checked(1), Some(&2));
+
+        assert_eq!(v.get(2), 0);
+        assert_eq!(v.get_checked(2), None);
+        let result = std::panic::catch_unwind(|| v[2]);
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("index 2 out of bounds"), "{message}");
+        assert!(message.contains("zero-padded"), "{message}");
+
+        let mut v = v;
+        v[4] = 5;
+        assert_eq!(v, vector![1, 2, 0, 0, 5]);
+    }
+
+    #[test]
+    pub fn test_hyperplane_distance() {
+        let normal = vector![0.0, 0.0, 2.0];
+        let p = vector![1.0, 2.0, 3.0];
+        assert_eq!(p.signed_distance_to_hyperplane(&normal, 1.0), 2.0);
+        assert_eq!(p.signed_distance_to_hyperplane(&normal, 5.0), -2.0);
+        assert_eq!(
+            p.projected_onto_hyperplane(&normal, 1.0),
+            vector![1.0, 2.0, 1.0]
+        );
+
+        let normal = vector![3.0, 4.0];
+        let p = vector![6.0, 8.0, -1.0];
+        assert!(f32_approx_eq(
+            p.signed_distance_to_hyperplane(&normal, 10.0),
+            0.0
+        ));
+        assert!(p.projected_onto_hyperplane(&normal, 10.0).approx_eq(&p));
+        let projected = p.projected_onto_hyperplane(&normal, 2.5);
+        assert!(projected.approx_eq(vector![1.5, 2.0, -1.0]));
+        assert!(f32_approx_eq(
+            projected.signed_distance_to_hyperplane(&normal, 2.5),
+            0.0
+        ));
+        assert!(f32_approx_eq(
+            p.signed_distance_to_hyperplane(-&normal, 2.5),
+            -12.5
+        ));
+    }
+
+    #[test]
+    fn test_weld_points() {
+        let eps = 0.001;
+        // The first two points straddle a grid cell boundary, so their keys
+        // differ even though they are within `eps`.
+        let points = [
+            vector![0.0004, 1.0],
+            vector![0.0006, 1.0],
+            vector![0.0, 1.0, 0.01],
+            vector![0.5],
+            vector![0.5, 0.0, 0.0, 0.0005],
+        ];
+        assert_ne!(
+            OrderedVector::new(points[0].clone(), eps),
+            OrderedVector::new(points[1].clone(), eps),
+        );
+        assert_eq!(weld_points(&points, eps), [0, 0, 2, 3, 3]);
+
+        let points: Vec<Vector<f64>> = points.iter().map(Vector::from_f32).collect();
+        assert_eq!(weld_points(&points, 0.001), [0, 0, 2, 3, 3]);
+    }
+
+    #[test]
+    pub fn test_ordered_vector() {
+        let eps = 0.001;
+        let v = vector![0.5, -2.0, 1.0];
+        let a = OrderedVector::new(v.clone(), eps);
+        let b = OrderedVector::new(&v + vector![0.0003, -0.0004, 0.0, 0.0002], eps);
+        let c = OrderedVector::new(&v + vector![0.01], eps);
+        let d = OrderedVector::new(&v + vector![0.0, 0.0, 0.0, -0.01], eps);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+        assert!(a < c);
+        assert!(d < a);
+
+        let set: std::collections::HashSet<_> = [&a, &b, &c, &d].into_iter().collect();
+        assert_eq!(set.len(), 3);
+        let set: std::collections::BTreeSet<_> = [&a, &b, &c, &d].into_iter().collect();
+        assert_eq!(set.len(), 3);
+
+        assert_eq!(Vector::from(a), v);
+    }
 }
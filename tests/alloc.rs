@@ -0,0 +1,45 @@
+//! Allocation counts, which need their own test binary because they replace
+//! the global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use symmetries::{CoxeterDiagram, Group};
+
+/// Allocator that counts live allocations on each thread, so that tests
+/// running in parallel don't interfere.
+struct CountingAllocator;
+thread_local! {
+    static LIVE_ALLOCATIONS: Cell<isize> = const { Cell::new(0) };
+}
+fn count_allocation(delta: isize) {
+    // The counter may already be destroyed if the thread is exiting.
+    let _ = LIVE_ALLOCATIONS.try_with(|n| n.set(n.get() + delta));
+}
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(1);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        count_allocation(-1);
+        System.dealloc(ptr, layout)
+    }
+}
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_group_matrix_allocations() {
+    let generators = CoxeterDiagram::with_edges(vec![4, 3, 3]).generators();
+    let before = LIVE_ALLOCATIONS.with(Cell::get);
+    let group = Group::from_generators(&generators);
+    let live = LIVE_ALLOCATIONS.with(Cell::get) - before;
+    assert_eq!(group.order(), 384);
+    // The matrices share one allocation, so each element only has an
+    // allocation for its decomposition.
+    assert!(
+        live <= group.order() as isize + 8,
+        "{live} live allocations"
+    );
+}